@@ -16,16 +16,20 @@ pub mod rustaceo_libre {
     //
 
     use crate::structs::usuario::{
-        ErrorAscenderRolUsuario, ErrorRegistrarUsuario, Rol, RolDeSeleccion, StockProductos, Usuario
+        DataComprador, DataVendedor, ErrorAscenderRolUsuario, ErrorDescenderRolUsuario, ErrorDireccionComprador, ErrorListasComprador, ErrorRegistrarUsuario, Rol, RolDeSeleccion, StockProductos, Usuario
     };
 
     use crate::structs::producto::{
-        CategoriaProducto, ErrorIngresarStockProducto, ErrorRegistrarProducto, ErrorRetirarStockProducto, ErrorVerStockPropio, Producto
+        DatosProducto, ErrorIngresarStockProducto, ErrorModerarProducto, ErrorRegistrarProducto, ErrorRetirarStockProducto, ErrorVerStockPropio, Producto
     };
 
     use crate::structs::publicacion::{
         Publicacion,
+        DetallePublicacion,
         ErrorModificarCantidadOfertada,
+        ErrorModificarPrecioPublicacion,
+        ErrorReposicionAutomatica,
+        ReposicionAutomatica,
         ErrorVerPublicacionesVendedor,
         ErrorRealizarPublicacion,
     };
@@ -33,11 +37,16 @@ pub mod rustaceo_libre {
     use crate::structs::pedido::{
         Pedido,
         EstadoPedido,
+        DireccionEntrega,
+        ErrorVerificarDireccion,
         ErrorProductoDespachado,
+        ErrorActualizarSeguimiento,
         ErrorProductoRecibido,
         ErrorCalificarPedido,
         ErrorComprarProducto,
         ErrorCancelarPedido,
+        ErrorCancelarUnidadesRestantes,
+        ErrorReembolsoParcial,
         ErrorRetirarFondos,
         ErrorVerCompras,
         ErrorVerVentas,
@@ -45,11 +54,109 @@ pub mod rustaceo_libre {
 
     use crate::structs::disputa::{
         Disputa,
+        DetalleDisputa,
         DisputaResuelta,
         ErrorDisputarPedido,
         ErrorResolverDisputa
     };
 
+    use crate::structs::mensaje::{
+        ContenidoMensaje,
+        MensajePedido,
+        ErrorMensajePedido,
+    };
+
+    use crate::structs::lote::{
+        OperacionLote,
+        ResultadoOperacionLote,
+        ErrorOperacionesLote,
+    };
+
+    use crate::structs::alerta_stock::{
+        ErrorUmbralStock,
+        ReporteStockBajo,
+    };
+
+    use crate::structs::devolucion::{
+        Devolucion,
+        ErrorDevolucion,
+    };
+
+    use crate::structs::movimiento_stock::{
+        MovimientoStock,
+        MovimientosStock,
+        ErrorVerMovimientosStock,
+    };
+
+    use crate::structs::transferencia_stock::{
+        TransferenciaStock,
+        ErrorTransferenciaStock,
+    };
+
+    use crate::structs::categoria::{
+        Categoria,
+        ErrorCategoria,
+        CATEGORIAS_INICIALES,
+    };
+
+    use crate::structs::atributo::{
+        Atributo,
+        DefinicionAtributo,
+        FiltroAtributo,
+        ErrorEsquemaAtributos,
+        ErrorFiltrarProductos,
+    };
+
+    use crate::structs::multimedia::{
+        Multimedia,
+        ErrorMultimedia,
+    };
+
+    use crate::structs::identificador::IdentificadorProducto;
+
+    use crate::structs::perfil::{
+        PerfilUsuario,
+        PerfilPublico,
+        ErrorPerfil,
+    };
+
+    use crate::structs::cuenta::{
+        CuentaEliminada,
+        ErrorEliminarCuenta,
+    };
+
+    use crate::structs::migracion::{
+        MigracionCuenta,
+        ErrorMigracionCuenta,
+    };
+
+    use crate::structs::operador::{
+        Operador,
+        PermisoOperador,
+        ErrorOperador,
+    };
+
+    use crate::structs::tienda_compartida::{
+        TiendaCompartida,
+        AccionPropuesta,
+        PropuestaTienda,
+        ErrorTiendaCompartida,
+    };
+
+    use crate::structs::verificacion::{
+        NivelVerificacion,
+        Verificacion,
+        SolicitudVerificacion,
+        ErrorVerificacion,
+    };
+
+    use crate::structs::invariantes::{
+        AmbitoInvariantes,
+        ColeccionIds,
+        ReporteInvariantes,
+        ErrorVerificarInvariantes,
+    };
+
     //
     // RustaceoLibre: main struct
     //
@@ -81,7 +188,113 @@ pub mod rustaceo_libre {
         /// ID del dueño del contrato
         pub owner: AccountId,
         /// Staff declarado por owner.
-        pub staff: Vec<AccountId>
+        pub staff: Vec<AccountId>,
+        /// Categorías cuyos pedidos deben despacharse con información de seguimiento.
+        pub categorias_seguimiento_obligatorio: Vec<u128>,
+        /// Valor total a partir del cual un pedido debe despacharse con información de seguimiento.
+        pub valor_minimo_seguimiento_obligatorio: Option<u128>,
+        /// <ID del pedido, Devolucion>
+        pub devoluciones: BTreeMap<u128, Devolucion>,
+        /// <ID del vendedor, días para solicitar una devolución desde la recepción>
+        pub plazos_devolucion: BTreeMap<AccountId, u32>,
+        /// <ID del pedido, hilo de mensajes entre comprador y vendedor>
+        pub mensajes_pedidos: BTreeMap<u128, Vec<MensajePedido>>,
+        /// <ID de la publicación, cantidad de compradores que la tienen entre sus favoritas>
+        pub interesados_publicaciones: BTreeMap<u128, u32>,
+        /// <(ID del vendedor, ID del producto), umbral de stock bajo>
+        pub umbrales_stock_productos: BTreeMap<(AccountId, u128), u32>,
+        /// <ID de la publicación, umbral de stock bajo>
+        pub umbrales_stock_publicaciones: BTreeMap<u128, u32>,
+        /// <ID de la publicación, ReposicionAutomatica>
+        pub reposiciones_automaticas: BTreeMap<u128, ReposicionAutomatica>,
+        /// <(ID del vendedor, ID del producto), registro de movimientos de stock>
+        pub movimientos_stock: MovimientosStock,
+        /// <ID, TransferenciaStock>
+        pub transferencias_stock: BTreeMap<u128, TransferenciaStock>,
+        /// Lleva un recuento de la próxima ID disponible para las transferencias de stock.
+        transferencias_stock_siguiente_id: u128,
+        /// <ID, Categoria>
+        pub categorias: BTreeMap<u128, Categoria>,
+        /// Lleva un recuento de la próxima ID disponible para las categorías.
+        categorias_siguiente_id: u128,
+        /// <ID de la categoría, definiciones de atributos propias de la categoría>
+        pub esquemas_atributos: BTreeMap<u128, Vec<DefinicionAtributo>>,
+        /// <GTIN de 14 dígitos normalizado, ID del producto>
+        pub productos_por_identificador: BTreeMap<String, u128>,
+        /// <ID del usuario, PerfilUsuario>
+        pub perfiles: BTreeMap<AccountId, PerfilUsuario>,
+        /// <nombre visible en minúsculas, ID del usuario>
+        pub nombres_visibles: BTreeMap<String, AccountId>,
+        /// <ID del usuario, CuentaEliminada>
+        pub cuentas_eliminadas: BTreeMap<AccountId, CuentaEliminada>,
+        /// <ID del usuario, datos del rol Comprador que dejó>
+        pub datos_comprador_archivados: BTreeMap<AccountId, DataComprador>,
+        /// <ID del usuario, datos del rol Vendedor que dejó>
+        pub datos_vendedor_archivados: BTreeMap<AccountId, DataVendedor>,
+        /// <ID de la cuenta anterior, MigracionCuenta>
+        pub migraciones_pendientes: BTreeMap<AccountId, MigracionCuenta>,
+        /// <ID del operador, Operador>
+        pub operadores: BTreeMap<AccountId, Operador>,
+        /// <ID de la cuenta de la tienda, TiendaCompartida>
+        pub tiendas_compartidas: BTreeMap<AccountId, TiendaCompartida>,
+        /// <ID, PropuestaTienda>
+        pub propuestas_tiendas: BTreeMap<u128, PropuestaTienda>,
+        /// Lleva un recuento de la próxima ID disponible para las propuestas de tiendas compartidas.
+        propuestas_tiendas_siguiente_id: u128,
+        /// <ID del vendedor, insignia de verificación>
+        pub verificaciones: BTreeMap<AccountId, Verificacion>,
+        /// <ID del vendedor, última solicitud de verificación>
+        pub solicitudes_verificacion: BTreeMap<AccountId, SolicitudVerificacion>,
+        /// <ID de la categoría, nivel de verificación exigido para publicar en ella y en sus subcategorías>
+        pub verificacion_exigida_categorias: BTreeMap<u128, NivelVerificacion>,
+    }
+
+    //
+    // eventos
+    //
+
+    /// Emitido cuando baja el precio de una publicación que algún comprador tiene entre sus favoritas.
+    /// No incluye a los interesados: los indexadores los relacionan por la publicación con las llamadas
+    /// a agregar y eliminar publicaciones favoritas.
+    #[ink(event)]
+    pub struct PrecioPublicacionReducido {
+        #[ink(topic)]
+        pub publicacion: u128,
+        pub precio_anterior: Balance,
+        pub precio_nuevo: Balance,
+    }
+
+    /// Emitido cuando una compra deja la cantidad ofertada de una publicación
+    /// en o por debajo del umbral configurado por su vendedor
+    #[ink(event)]
+    pub struct StockBajo {
+        #[ink(topic)]
+        pub vendedor: AccountId,
+        #[ink(topic)]
+        pub publicacion: u128,
+        pub cantidad_ofertada: u32,
+        pub umbral: u32,
+    }
+
+    /// Emitido cuando la reposición automática de una publicación deja el stock del producto
+    /// en o por debajo del umbral configurado por su vendedor
+    #[ink(event)]
+    pub struct StockProductoBajo {
+        #[ink(topic)]
+        pub vendedor: AccountId,
+        #[ink(topic)]
+        pub producto: u128,
+        pub cantidad: u32,
+        pub umbral: u32,
+    }
+
+    /// Emitido cuando vuelve a tener stock una publicación que algún comprador tiene entre sus favoritas.
+    /// Ídem PrecioPublicacionReducido, no incluye a los interesados.
+    #[ink(event)]
+    pub struct PublicacionConStock {
+        #[ink(topic)]
+        pub publicacion: u128,
+        pub cantidad_ofertada: u32,
     }
 
     #[ink(impl)]
@@ -114,7 +327,36 @@ pub mod rustaceo_libre {
                 publicaciones_siguiente_id: 0,
                 tarifa_de_servicio,
                 owner: Self::env().caller(),
-                staff: Default::default()
+                staff: Default::default(),
+                categorias_seguimiento_obligatorio: Default::default(),
+                valor_minimo_seguimiento_obligatorio: None,
+                devoluciones: Default::default(),
+                plazos_devolucion: Default::default(),
+                mensajes_pedidos: Default::default(),
+                interesados_publicaciones: Default::default(),
+                umbrales_stock_productos: Default::default(),
+                umbrales_stock_publicaciones: Default::default(),
+                reposiciones_automaticas: Default::default(),
+                movimientos_stock: Default::default(),
+                transferencias_stock: Default::default(),
+                transferencias_stock_siguiente_id: 0,
+                categorias: Self::categorias_iniciales(),
+                categorias_siguiente_id: CATEGORIAS_INICIALES.len() as u128,
+                esquemas_atributos: Default::default(),
+                productos_por_identificador: Default::default(),
+                perfiles: Default::default(),
+                nombres_visibles: Default::default(),
+                cuentas_eliminadas: Default::default(),
+                datos_comprador_archivados: Default::default(),
+                datos_vendedor_archivados: Default::default(),
+                migraciones_pendientes: Default::default(),
+                operadores: Default::default(),
+                tiendas_compartidas: Default::default(),
+                propuestas_tiendas: Default::default(),
+                propuestas_tiendas_siguiente_id: 0,
+                verificaciones: Default::default(),
+                solicitudes_verificacion: Default::default(),
+                verificacion_exigida_categorias: Default::default(),
             }
         }
 
@@ -164,6 +406,17 @@ pub mod rustaceo_libre {
             true
         }
 
+        /// Solo ejecutable por Staff u OWNER
+        /// Establece qué pedidos deben despacharse obligatoriamente con información de seguimiento:
+        /// aquellos cuyo producto pertenezca a alguna de las categorías indicadas (o a sus subcategorías)
+        /// o cuyo valor total sea igual o mayor al valor mínimo, si se indica.
+        /// 
+        /// Devolverá true si la operación fue exitosa o false si caller no es Staff ni OWNER.
+        #[ink(message)]
+        pub fn staff_establecer_politica_seguimiento(&mut self, categorias: Vec<u128>, valor_minimo: Option<u128>) -> bool {
+            self._staff_establecer_politica_seguimiento(self.env().caller(), categorias, valor_minimo)
+        }
+
         //
         // ReportesView
         //
@@ -236,6 +489,96 @@ pub mod rustaceo_libre {
             self._ascender_rol_usuario(self.env().caller())
         }
 
+        /// Si caller es Ambos, deja el rol indicado (Comprador o Vendedor) y conserva el otro.
+        /// Los datos del rol que deja se archivan y se restauran si vuelve a ascender.
+        /// 
+        /// Devuelve error si el usuario no existe, no es Ambos o se indicó Ambos, o si en el rol que deja tiene
+        /// pedidos pendientes o despachados, fondos en custodia, disputas en curso, publicaciones con unidades
+        /// ofertadas o transferencias de stock ofrecidas. Una tienda compartida no puede dejar de vender
+        /// hasta que sus socios la disuelvan.
+        #[ink(message)]
+        pub fn descender_rol_usuario(&mut self, rol_a_quitar: RolDeSeleccion) -> Result<(), ErrorDescenderRolUsuario> {
+            self._descender_rol_usuario(self.env().caller(), rol_a_quitar)
+        }
+
+        /// Guarda una dirección de entrega cifrada en el perfil del comprador.
+        /// El cifrado se realiza fuera de la cadena: el contrato sólo almacena el contenido cifrado.
+        /// 
+        /// Devuelve el índice de la dirección guardada.
+        /// Devolverá error si el usuario no está registrado, no es comprador,
+        /// la dirección está vacía o excede el tamaño máximo o ya alcanzó el máximo de direcciones.
+        #[ink(message)]
+        pub fn agregar_direccion(&mut self, direccion_cifrada: Vec<u8>) -> Result<u32, ErrorDireccionComprador> {
+            self._agregar_direccion(self.env().caller(), direccion_cifrada)
+        }
+
+        /// Elimina una dirección cifrada del perfil del comprador según su índice.
+        /// 
+        /// Devolverá error si el usuario no está registrado, no es comprador o la dirección no existe.
+        #[ink(message)]
+        pub fn eliminar_direccion(&mut self, indice: u32) -> Result<(), ErrorDireccionComprador> {
+            self._eliminar_direccion(self.env().caller(), indice)
+        }
+
+        /// Devuelve las direcciones cifradas guardadas en el perfil del comprador.
+        /// 
+        /// Devolverá error si el usuario no está registrado o no es comprador.
+        #[ink(message)]
+        pub fn ver_direcciones(&self) -> Result<Vec<Vec<u8>>, ErrorDireccionComprador> {
+            self._ver_direcciones(self.env().caller())
+        }
+
+        /// Agrega una publicación a la lista de favoritas del comprador.
+        /// Mientras esté en la lista, el comprador será notificado si su precio baja o vuelve a tener stock.
+        /// 
+        /// Devolverá error si el usuario no está registrado, no es comprador, la publicación no existe,
+        /// ya está en la lista o la lista alcanzó su máximo.
+        #[ink(message)]
+        pub fn agregar_publicacion_favorita(&mut self, id_publicacion: u128) -> Result<(), ErrorListasComprador> {
+            self._agregar_publicacion_favorita(self.env().caller(), id_publicacion)
+        }
+
+        /// Quita una publicación de la lista de favoritas del comprador.
+        /// 
+        /// Devolverá error si el usuario no está registrado, no es comprador o la publicación no está en la lista.
+        #[ink(message)]
+        pub fn eliminar_publicacion_favorita(&mut self, id_publicacion: u128) -> Result<(), ErrorListasComprador> {
+            self._eliminar_publicacion_favorita(self.env().caller(), id_publicacion)
+        }
+
+        /// Devuelve las publicaciones favoritas del comprador.
+        /// 
+        /// Devolverá error si el usuario no está registrado o no es comprador.
+        #[ink(message)]
+        pub fn ver_publicaciones_favoritas(&self) -> Result<Vec<u128>, ErrorListasComprador> {
+            self._ver_publicaciones_favoritas(self.env().caller())
+        }
+
+        /// Agrega un vendedor a la lista de vendedores seguidos por el comprador.
+        /// 
+        /// Devolverá error si el usuario no está registrado, no es comprador, el vendedor no existe
+        /// o no es vendedor (incluido uno mismo), ya lo sigue o la lista alcanzó su máximo.
+        #[ink(message)]
+        pub fn seguir_vendedor(&mut self, id_vendedor: AccountId) -> Result<(), ErrorListasComprador> {
+            self._seguir_vendedor(self.env().caller(), id_vendedor)
+        }
+
+        /// Quita un vendedor de la lista de vendedores seguidos por el comprador.
+        /// 
+        /// Devolverá error si el usuario no está registrado, no es comprador o no sigue al vendedor.
+        #[ink(message)]
+        pub fn dejar_de_seguir_vendedor(&mut self, id_vendedor: AccountId) -> Result<(), ErrorListasComprador> {
+            self._dejar_de_seguir_vendedor(self.env().caller(), id_vendedor)
+        }
+
+        /// Devuelve los vendedores seguidos por el comprador.
+        /// 
+        /// Devolverá error si el usuario no está registrado o no es comprador.
+        #[ink(message)]
+        pub fn ver_vendedores_seguidos(&self) -> Result<Vec<AccountId>, ErrorListasComprador> {
+            self._ver_vendedores_seguidos(self.env().caller())
+        }
+
         //
         // /structs/publicacion.rs    /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
         //
@@ -245,17 +588,73 @@ pub mod rustaceo_libre {
         /// Devuelve Error si el precio o la cantidad son 0, o si `caller` no existe o no es vendedor.
         #[ink(message)]
         pub fn realizar_publicacion(&mut self, id_producto: u128, cantidad_ofertada: u32, precio: Balance) -> Result<u128, ErrorRealizarPublicacion> {
-            self._realizar_publicacion(self.env().caller(), id_producto, cantidad_ofertada, precio)
+            self._realizar_publicacion(self.env().block_timestamp(), self.env().caller(), id_producto, cantidad_ofertada, precio)
         }
 
         /// Modifica la cantidad ofertada en una publicación,
         /// modificando también el stock del vendedor.
         /// 
         /// Devuelve Error si el usuario no está registrado, la venta no existe,
-        /// el usuario no es el vendedor o la operación es imposible por falta de stock/cantidad ofertada.
+        /// el usuario no es el vendedor, la operación es imposible por falta de stock/cantidad ofertada
+        /// o se suman unidades sin el nivel de verificación exigido por la categoría del producto.
         #[ink(message)]
         pub fn modificar_cantidad_ofertada(&mut self, id_publicacion: u128, nueva_cantidad_ofertada: u32) -> Result<(), ErrorModificarCantidadOfertada> {
-            self._modificar_cantidad_ofertada(self.env().caller(), id_publicacion, nueva_cantidad_ofertada)
+            let anterior = self._ver_publicacion(id_publicacion);
+            self._modificar_cantidad_ofertada(self.env().block_timestamp(), self.env().caller(), id_publicacion, nueva_cantidad_ofertada)?;
+            self.notificar_interesados_publicacion(id_publicacion, anterior);
+            Ok(())
+        }
+
+        /// Modifica el precio unitario de una publicación.
+        /// 
+        /// Devuelve Error si el usuario no está registrado, la publicación no existe,
+        /// el usuario no es el vendedor o el precio es 0 o igual al actual.
+        #[ink(message)]
+        pub fn modificar_precio_publicacion(&mut self, id_publicacion: u128, nuevo_precio: Balance) -> Result<(), ErrorModificarPrecioPublicacion> {
+            let anterior = self._ver_publicacion(id_publicacion);
+            self._modificar_precio_publicacion(self.env().block_timestamp(), self.env().caller(), id_publicacion, nuevo_precio)?;
+            self.notificar_interesados_publicacion(id_publicacion, anterior);
+            Ok(())
+        }
+
+        /// Activa, modifica o (con None) desactiva la reposición automática de una publicación:
+        /// cuando su cantidad ofertada cae por debajo del mínimo luego de una compra,
+        /// se repone hasta el objetivo tomando unidades del stock del vendedor.
+        /// 
+        /// Devuelve Error si el usuario no está registrado, la publicación no existe, el usuario no es el vendedor,
+        /// el mínimo es 0 o el objetivo es menor al mínimo.
+        #[ink(message)]
+        pub fn establecer_reposicion_automatica(&mut self, id_publicacion: u128, reposicion: Option<ReposicionAutomatica>) -> Result<(), ErrorReposicionAutomatica> {
+            self._establecer_reposicion_automatica(self.env().block_timestamp(), self.env().caller(), id_publicacion, reposicion)
+        }
+
+        /// Compara una publicación con su estado anterior y, si algún comprador la tiene entre sus favoritas,
+        /// emite los eventos de baja de precio o reposición de stock que correspondan.
+        fn notificar_interesados_publicacion(&self, id_publicacion: u128, anterior: Option<Publicacion>) {
+            let Some(anterior) = anterior
+            else { return; };
+
+            if !self.interesados_publicaciones.contains_key(&id_publicacion) {
+                return;
+            }
+
+            let Some(actual) = self.publicaciones.get(&id_publicacion)
+            else { return; };
+
+            if actual.precio_unitario < anterior.precio_unitario {
+                self.env().emit_event(PrecioPublicacionReducido {
+                    publicacion: id_publicacion,
+                    precio_anterior: anterior.precio_unitario,
+                    precio_nuevo: actual.precio_unitario,
+                });
+            }
+
+            if anterior.cantidad_ofertada == 0 && actual.cantidad_ofertada > 0 {
+                self.env().emit_event(PublicacionConStock {
+                    publicacion: id_publicacion,
+                    cantidad_ofertada: actual.cantidad_ofertada,
+                });
+            }
         }
 
         /// Dada una ID, devuelve la publicación
@@ -266,6 +665,14 @@ pub mod rustaceo_libre {
             self._ver_publicacion(id_publicacion)
         }
 
+        /// Dada una ID, devuelve la publicación junto con la insignia de verificación de su vendedor
+        /// 
+        /// Devolverá None si la publicación no existe
+        #[ink(message)]
+        pub fn ver_detalle_publicacion(&self, id_publicacion: u128) -> Option<DetallePublicacion> {
+            self._ver_detalle_publicacion(id_publicacion)
+        }
+
         /// Devuelve todos los productos publicados por el usuario que lo ejecute
         /// 
         /// Dará error si el usuario no está registrado como vendedor o si no tiene publicaciones.
@@ -281,20 +688,34 @@ pub mod rustaceo_libre {
         /// Registra un producto en la lista de productos
         /// para su posterior uso en publicaciones
         /// 
-        /// Devuelve error si el usuario no está registrado o no es vendedor.
+        /// Si el identificador estándar (GTIN/EAN/ISBN) ya pertenece a otro producto aprobado, no se registra uno nuevo:
+        /// el stock inicial se acredita en el producto existente y se devuelve su ID.
+        /// 
+        /// Devuelve error si el usuario no está registrado, no es vendedor, la categoría no existe,
+        /// los atributos no cumplen el esquema de la categoría, el identificador es inválido
+        /// o pertenece a un producto aún pendiente de moderación.
         #[ink(message)]
-        pub fn registrar_producto(&mut self, nombre: String, descripcion: String, categoria: CategoriaProducto, stock_inicial: u32) -> Result<u128, ErrorRegistrarProducto> {
-            self._registrar_producto(self.env().caller(), nombre, descripcion, categoria, stock_inicial)
+        pub fn registrar_producto(&mut self, nombre: String, descripcion: String, categoria: u128, stock_inicial: u32, atributos: Vec<Atributo>, identificador: Option<IdentificadorProducto>) -> Result<u128, ErrorRegistrarProducto> {
+            let datos = DatosProducto { nombre, descripcion, categoria, atributos, identificador };
+            self._registrar_producto(self.env().block_timestamp(), self.env().caller(), datos, stock_inicial)
+        }
+
+        /// Dado un identificador estándar (GTIN/EAN/ISBN), devuelve la ID del producto que lo tiene asignado.
+        /// 
+        /// Devolverá None si el identificador es inválido o no corresponde a ningún producto.
+        #[ink(message)]
+        pub fn ver_producto_por_identificador(&self, identificador: IdentificadorProducto) -> Option<u128> {
+            self._ver_producto_por_identificador(identificador)
         }
 
         /// Dada la ID de un producto y un stock, incrementa la posesión en stock de ese producto del vendedor.
         /// 
         /// Devolverá la nueva cantidad de stock disponible de ese producto para el vendedor.
         /// Devolverá error si la cantidad ingresada es cero, el usuario no está registrado,
-        /// no es vendedor o el producto no existe.
+        /// no es vendedor o el producto no existe, fue rechazado o fusionado.
         #[ink(message)]
         pub fn ingresar_stock_producto(&mut self, id_producto: u128, cantidad_ingresada: u32) -> Result<u32, ErrorIngresarStockProducto> {
-            self._ingresar_stock_producto(self.env().caller(), id_producto, cantidad_ingresada)
+            self._ingresar_stock_producto(self.env().block_timestamp(), self.env().caller(), id_producto, cantidad_ingresada)
         }
 
         /// Dada la ID de un producto y un stock, decrementa la posesión en stock de ese producto del vendedor.
@@ -304,7 +725,7 @@ pub mod rustaceo_libre {
         /// no es vendedor o el producto no existe.
         #[ink(message)]
         pub fn retirar_stock_producto(&mut self, id_producto: u128, cantidad_retirada: u32) -> Result<u32, ErrorRetirarStockProducto> {
-            self._retirar_stock_producto(self.env().caller(), id_producto, cantidad_retirada)
+            self._retirar_stock_producto(self.env().block_timestamp(), self.env().caller(), id_producto, cantidad_retirada)
         }
         
         /// Dada una ID, devuelve la publicación del producto
@@ -320,7 +741,7 @@ pub mod rustaceo_libre {
         /// Dará error si el usuario no está registrado, no es vendedor o no posee stock de ningún producto
         #[ink(message)]
         pub fn ver_stock_propio(&self) -> Result<StockProductos, ErrorVerStockPropio> {
-            self._ver_stock_propio(self.env().caller())
+            self._ver_stock_propio(self.env().block_timestamp(), self.env().caller())
         }
 
         /// Devuelve la cantidad de ventas que ese producto haya tenido
@@ -328,6 +749,565 @@ pub mod rustaceo_libre {
         pub fn ver_ventas_producto(&self, id: u128) -> Option<u128> {
             self._ver_ventas_producto(id)
         }
+
+        /// Devolverá la lista de productos pendientes de moderación.
+        /// 
+        /// Devolverá None si el usuario no es parte del Staff.
+        #[ink(message)]
+        pub fn staff_ver_productos_pendientes(&self) -> Option<Vec<u128>> {
+            self._staff_ver_productos_pendientes(self.env().caller())
+        }
+
+        /// Aprueba un producto pendiente, habilitándolo para su uso en publicaciones.
+        /// 
+        /// Devolverá error si el usuario no es parte del Staff, el producto no existe o no está pendiente.
+        #[ink(message)]
+        pub fn staff_aprobar_producto(&mut self, id_producto: u128) -> Result<(), ErrorModerarProducto> {
+            self._staff_aprobar_producto(self.env().caller(), id_producto)
+        }
+
+        /// Rechaza un producto pendiente indicando el motivo. El producto no podrá usarse en publicaciones.
+        /// 
+        /// Devolverá error si el usuario no es parte del Staff, el motivo está vacío o excede su longitud máxima,
+        /// el producto no existe o no está pendiente.
+        #[ink(message)]
+        pub fn staff_rechazar_producto(&mut self, id_producto: u128, motivo: String) -> Result<(), ErrorModerarProducto> {
+            self._staff_rechazar_producto(self.env().caller(), id_producto, motivo)
+        }
+
+        /// Fusiona un producto duplicado en otro aprobado: el stock de cada vendedor y sus publicaciones
+        /// pasan a referenciar al producto destino, y el duplicado queda marcado como fusionado.
+        /// 
+        /// Devolverá error si el usuario no es parte del Staff, alguno de los productos no existe,
+        /// son el mismo producto, el duplicado ya fue fusionado, el destino no está aprobado o el duplicado tiene
+        /// publicaciones con unidades ofertadas de vendedores sin el nivel de verificación que exige la categoría del destino.
+        #[ink(message)]
+        pub fn staff_fusionar_productos(&mut self, id_duplicado: u128, id_destino: u128) -> Result<(), ErrorModerarProducto> {
+            self._staff_fusionar_productos(self.env().caller(), id_duplicado, id_destino)
+        }
+
+        //
+        // lote.rs:
+        //
+
+        /// Ejecuta en orden un lote de operaciones de stock y publicaciones del vendedor
+        /// (ingresar stock, retirar stock y realizar publicaciones), con las mismas validaciones
+        /// que sus mensajes individuales.
+        ///
+        /// Si `atomico` es true, ante la primera operación fallida no se aplica ninguna
+        /// y se devuelve su índice y resultado. Si es false, se devuelve el resultado de cada operación.
+        ///
+        /// Devolverá error si el lote está vacío, supera el máximo de operaciones
+        /// o, en modo atómico, alguna operación falla.
+        #[ink(message)]
+        pub fn operaciones_lote(&mut self, operaciones: Vec<OperacionLote>, atomico: bool) -> Result<Vec<ResultadoOperacionLote>, ErrorOperacionesLote> {
+            self._operaciones_lote(self.env().block_timestamp(), self.env().caller(), operaciones, atomico)
+        }
+
+        //
+        // alerta_stock.rs:
+        //
+
+        /// Establece el umbral de stock bajo de un producto en el stock del vendedor.
+        /// Un umbral de 0 elimina la configuración.
+        ///
+        /// Devolverá error si el usuario no está registrado, no es vendedor o no tiene stock registrado del producto.
+        #[ink(message)]
+        pub fn establecer_umbral_stock_producto(&mut self, id_producto: u128, umbral: u32) -> Result<(), ErrorUmbralStock> {
+            self._establecer_umbral_stock_producto(self.env().block_timestamp(), self.env().caller(), id_producto, umbral)
+        }
+
+        /// Establece el umbral de stock bajo de una publicación del vendedor.
+        /// Un umbral de 0 elimina la configuración.
+        ///
+        /// Devolverá error si el usuario no está registrado, la publicación no existe o no es su vendedor.
+        #[ink(message)]
+        pub fn establecer_umbral_stock_publicacion(&mut self, id_publicacion: u128, umbral: u32) -> Result<(), ErrorUmbralStock> {
+            self._establecer_umbral_stock_publicacion(self.env().block_timestamp(), self.env().caller(), id_publicacion, umbral)
+        }
+
+        /// Devuelve las publicaciones y entradas de StockProductos del vendedor
+        /// cuya cantidad se encuentra en o por debajo del umbral configurado.
+        ///
+        /// Devolverá error si el usuario no está registrado o no es vendedor.
+        #[ink(message)]
+        pub fn ver_stock_bajo(&self) -> Result<ReporteStockBajo, ErrorUmbralStock> {
+            self._ver_stock_bajo(self.env().block_timestamp(), self.env().caller())
+        }
+
+        //
+        // movimiento_stock.rs:
+        //
+
+        /// Devuelve una página del registro de movimientos de stock de un vendedor para un producto
+        /// (ingresos, retiros, publicaciones, ventas, cancelaciones y devoluciones), en orden cronológico.
+        /// Sólo puede consultarlo el mismo vendedor o un miembro del Staff.
+        ///
+        /// Devolverá error si caller no es el vendedor ni Staff, o el límite es 0 o supera el máximo por página.
+        #[ink(message)]
+        pub fn ver_movimientos_stock(&self, vendedor: AccountId, id_producto: u128, desde: u32, limite: u32) -> Result<Vec<MovimientoStock>, ErrorVerMovimientosStock> {
+            self._ver_movimientos_stock(self.env().caller(), vendedor, id_producto, desde, limite)
+        }
+
+        //
+        // transferencia_stock.rs:
+        //
+
+        /// Ofrece a otro vendedor una cantidad de un producto del stock propio, opcionalmente a cambio de un precio total.
+        /// Las unidades se descuentan del stock del emisor hasta que la transferencia se acepte, rechace o cancele.
+        ///
+        /// Devuelve la ID de la transferencia.
+        /// Devolverá error si el emisor o el receptor no están registrados o no son vendedores, son el mismo usuario,
+        /// el producto no existe, fue rechazado o fusionado, la cantidad es 0 o el emisor no tiene stock suficiente.
+        #[ink(message)]
+        pub fn ofrecer_transferencia_stock(&mut self, receptor: AccountId, id_producto: u128, cantidad: u32, precio: Balance) -> Result<u128, ErrorTransferenciaStock> {
+            self._ofrecer_transferencia_stock(self.env().block_timestamp(), self.env().caller(), receptor, id_producto, cantidad, precio)
+        }
+
+        /// Acepta una transferencia de stock ofrecida, pagando su precio con el valor transferido.
+        /// Las unidades se acreditan en el stock del receptor y el pago se envía al emisor en la misma transacción.
+        /// El valor transferido sobrante se devuelve.
+        ///
+        /// Devolverá error si el usuario no está registrado o no es vendedor, la transferencia no existe,
+        /// no es su receptor, ya no está ofrecida o el valor transferido no alcanza para pagarla.
+        #[ink(message, payable)]
+        pub fn aceptar_transferencia_stock(&mut self, id_transferencia: u128) -> Result<(), ErrorTransferenciaStock> {
+            let operacion = self._aceptar_transferencia_stock(self.env().block_timestamp(), self.env().caller(), id_transferencia, self.env().transferred_value());
+
+            let Ok(resultado) = operacion
+            else {
+                // fallo: devolver totalidad de los fondos transferidos
+                let _ = self.env().transfer(self.env().caller(), self.env().transferred_value());
+                return Err(operacion.unwrap_err());
+            };
+
+            if resultado.pago > 0 {
+                let _ = self.env().transfer(self.direccion_cobro(resultado.emisor), resultado.pago);
+            }
+
+            if resultado.monto_transferido_sobrante > 0 {
+                let _ = self.env().transfer(self.env().caller(), resultado.monto_transferido_sobrante);
+            }
+
+            Ok(())
+        }
+
+        /// El receptor rechaza o el emisor cancela una transferencia de stock ofrecida.
+        /// Las unidades retenidas vuelven al stock del emisor.
+        ///
+        /// Devolverá error si el usuario no está registrado, la transferencia no existe,
+        /// el usuario no participa en ella o ya no está ofrecida.
+        #[ink(message)]
+        pub fn anular_transferencia_stock(&mut self, id_transferencia: u128) -> Result<(), ErrorTransferenciaStock> {
+            self._anular_transferencia_stock(self.env().block_timestamp(), self.env().caller(), id_transferencia)
+        }
+
+        /// Devuelve las transferencias de stock en las que participa el usuario, como emisor o receptor.
+        #[ink(message)]
+        pub fn ver_transferencias_stock(&self) -> Vec<TransferenciaStock> {
+            self._ver_transferencias_stock(self.env().caller())
+        }
+
+        //
+        // categoria.rs:
+        //
+
+        /// Crea una categoría de productos, como raíz o como subcategoría de otra. Sólo para Staff.
+        ///
+        /// Devuelve la ID de la nueva categoría.
+        /// Devolverá error si caller no es Staff, el nombre está vacío o excede su longitud máxima,
+        /// el padre no existe o se excedería la profundidad máxima del árbol.
+        #[ink(message)]
+        pub fn staff_crear_categoria(&mut self, nombre: String, padre: Option<u128>) -> Result<u128, ErrorCategoria> {
+            self._staff_crear_categoria(self.env().caller(), nombre, padre)
+        }
+
+        /// Cambia el nombre de una categoría. Sólo para Staff.
+        ///
+        /// Devolverá error si caller no es Staff, el nombre está vacío o excede su longitud máxima
+        /// o la categoría no existe.
+        #[ink(message)]
+        pub fn staff_renombrar_categoria(&mut self, id: u128, nombre: String) -> Result<(), ErrorCategoria> {
+            self._staff_renombrar_categoria(self.env().caller(), id, nombre)
+        }
+
+        /// Mueve una categoría, junto con sus subcategorías, bajo otro padre o a la raíz del árbol. Sólo para Staff.
+        ///
+        /// Devolverá error si caller no es Staff, la categoría es predefinida, la categoría o el nuevo padre no existen,
+        /// el nuevo padre es la categoría misma o una de sus subcategorías, se excedería la profundidad máxima del árbol
+        /// o quedarían publicaciones con unidades ofertadas de vendedores sin el nivel de verificación exigido.
+        #[ink(message)]
+        pub fn staff_mover_categoria(&mut self, id: u128, nuevo_padre: Option<u128>) -> Result<(), ErrorCategoria> {
+            self._staff_mover_categoria(self.env().caller(), id, nuevo_padre)
+        }
+
+        /// Elimina una categoría sin subcategorías ni productos, junto con su esquema de atributos. Sólo para Staff.
+        ///
+        /// Devolverá error si caller no es Staff, la categoría es predefinida, no existe, tiene subcategorías o tiene productos.
+        #[ink(message)]
+        pub fn staff_eliminar_categoria(&mut self, id: u128) -> Result<(), ErrorCategoria> {
+            self._staff_eliminar_categoria(self.env().caller(), id)
+        }
+
+        /// Devuelve la categoría con la ID dada, si existe.
+        #[ink(message)]
+        pub fn ver_categoria(&self, id: u128) -> Option<Categoria> {
+            self._ver_categoria(id)
+        }
+
+        /// Devuelve las subcategorías directas de una categoría, o las categorías raíz si no se indica ninguna.
+        #[ink(message)]
+        pub fn ver_subcategorias(&self, id: Option<u128>) -> Vec<u128> {
+            self._ver_subcategorias(id)
+        }
+
+        /// Devuelve las IDs de los productos de una categoría o de cualquiera de sus subcategorías.
+        #[ink(message)]
+        pub fn ver_productos_categoria(&self, id: u128) -> Vec<u128> {
+            self._ver_productos_categoria(id)
+        }
+
+        //
+        // atributo.rs:
+        //
+
+        /// Define (o reemplaza) el esquema de atributos propio de una categoría: las claves admitidas, su tipo
+        /// y si son obligatorias. Las subcategorías lo heredan. Un esquema vacío elimina el de la categoría. Sólo para Staff.
+        ///
+        /// Devolverá error si caller no es Staff, la categoría no existe, el esquema excede la cantidad máxima
+        /// de definiciones o alguna clave está vacía, excede su longitud máxima o está repetida.
+        #[ink(message)]
+        pub fn staff_definir_esquema_atributos(&mut self, categoria: u128, esquema: Vec<DefinicionAtributo>) -> Result<(), ErrorEsquemaAtributos> {
+            self._staff_definir_esquema_atributos(self.env().caller(), categoria, esquema)
+        }
+
+        /// Devuelve el esquema de atributos que rige para una categoría, incluyendo el heredado de sus ancestros.
+        #[ink(message)]
+        pub fn ver_esquema_atributos(&self, categoria: u128) -> Vec<DefinicionAtributo> {
+            self.esquema_efectivo(&categoria)
+        }
+
+        /// Devuelve las IDs de los productos aprobados que cumplen todos los filtros de atributos,
+        /// opcionalmente restringidos a una categoría y sus subcategorías.
+        ///
+        /// Devolverá error si la categoría no existe o se excede la cantidad máxima de filtros.
+        #[ink(message)]
+        pub fn ver_productos_filtrados(&self, categoria: Option<u128>, filtros: Vec<FiltroAtributo>) -> Result<Vec<u128>, ErrorFiltrarProductos> {
+            self._ver_productos_filtrados(categoria, filtros)
+        }
+
+        //
+        // multimedia.rs:
+        //
+
+        /// Agrega un archivo multimedia (imagen, video o documento referenciado por su CID) al final de la lista de un producto.
+        /// Sólo puede hacerlo el vendedor que registró el producto o un miembro del Staff.
+        ///
+        /// Devolverá error si el producto no existe, caller no tiene permiso, el CID es inválido o ya está en la lista,
+        /// el tamaño es 0 o excede el máximo para su tipo, o la lista alcanzó su máximo.
+        #[ink(message)]
+        pub fn agregar_multimedia_producto(&mut self, id_producto: u128, multimedia: Multimedia) -> Result<(), ErrorMultimedia> {
+            self._agregar_multimedia_producto(self.env().caller(), id_producto, multimedia)
+        }
+
+        /// Quita el archivo multimedia en la posición indicada de la lista de un producto.
+        /// Sólo puede hacerlo el vendedor que registró el producto o un miembro del Staff.
+        ///
+        /// Devolverá error si el producto no existe, caller no tiene permiso o la posición no existe.
+        #[ink(message)]
+        pub fn quitar_multimedia_producto(&mut self, id_producto: u128, indice: u32) -> Result<(), ErrorMultimedia> {
+            self._quitar_multimedia_producto(self.env().caller(), id_producto, indice)
+        }
+
+        /// Agrega un archivo multimedia (imagen, video o documento referenciado por su CID) al final de la lista de una publicación.
+        /// Sólo puede hacerlo el vendedor de la publicación.
+        ///
+        /// Devolverá error si la publicación no existe, caller no es su vendedor, el CID es inválido o ya está en la lista,
+        /// el tamaño es 0 o excede el máximo para su tipo, o la lista alcanzó su máximo.
+        #[ink(message)]
+        pub fn agregar_multimedia_publicacion(&mut self, id_publicacion: u128, multimedia: Multimedia) -> Result<(), ErrorMultimedia> {
+            self._agregar_multimedia_publicacion(self.env().block_timestamp(), self.env().caller(), id_publicacion, multimedia)
+        }
+
+        /// Quita el archivo multimedia en la posición indicada de la lista de una publicación.
+        /// Puede hacerlo el vendedor de la publicación o un miembro del Staff.
+        ///
+        /// Devolverá error si la publicación no existe, caller no tiene permiso o la posición no existe.
+        #[ink(message)]
+        pub fn quitar_multimedia_publicacion(&mut self, id_publicacion: u128, indice: u32) -> Result<(), ErrorMultimedia> {
+            self._quitar_multimedia_publicacion(self.env().block_timestamp(), self.env().caller(), id_publicacion, indice)
+        }
+
+        //
+        // perfil.rs:
+        //
+
+        /// Crea o reemplaza el perfil de caller: nombre visible (único, sin distinguir mayúsculas ni acentos), biografía,
+        /// CID del avatar, hash de un dato de contacto y código de país ISO 3166-1 alfa-2. Conserva los datos de la tienda.
+        ///
+        /// Devolverá error si caller no está registrado, el nombre visible es inválido o lo usa otro usuario,
+        /// la biografía excede su longitud máxima, el avatar no es un CID válido o el país es inválido.
+        #[ink(message)]
+        pub fn editar_perfil(&mut self, nombre_visible: String, biografia: String, avatar: Option<String>, hash_contacto: Option<[u8; 32]>, pais: Option<String>) -> Result<(), ErrorPerfil> {
+            self._editar_perfil(self.env().caller(), nombre_visible, biografia, avatar, hash_contacto, pais)
+        }
+
+        /// Crea o reemplaza los datos de la tienda de caller: nombre, políticas y días de preparación de los pedidos.
+        /// Requiere haber creado el perfil.
+        ///
+        /// Devolverá error si caller no está registrado, no es vendedor, no tiene perfil o algún dato es inválido.
+        #[ink(message)]
+        pub fn editar_tienda(&mut self, nombre: String, politicas: String, dias_preparacion: u32) -> Result<(), ErrorPerfil> {
+            self._editar_tienda(self.env().caller(), nombre, politicas, dias_preparacion)
+        }
+
+        /// Devuelve la vista pública de un usuario: perfil, roles, calificaciones promedio como comprador y vendedor
+        /// e insignia de verificación.
+        ///
+        /// Devolverá None si el usuario no está registrado.
+        #[ink(message)]
+        pub fn ver_perfil(&self, id: AccountId) -> Option<PerfilPublico> {
+            self._ver_perfil(id)
+        }
+
+        /// Dado un nombre visible, sin distinguir mayúsculas ni acentos, devuelve la ID del usuario que lo usa.
+        #[ink(message)]
+        pub fn buscar_usuario_por_nombre(&self, nombre_visible: String) -> Option<AccountId> {
+            self._buscar_usuario_por_nombre(nombre_visible)
+        }
+
+        //
+        // cuenta.rs:
+        //
+
+        /// Elimina la cuenta de caller. Sus publicaciones quedan sin unidades ofertadas y se quitan de las favoritas
+        /// de los compradores, su stock se retira y se borran su perfil y sus listas. Los pedidos se conservan
+        /// para que las contrapartes sigan viendo su historial. Las calificaciones se archivan y se restauran
+        /// si la cuenta vuelve a registrarse.
+        ///
+        /// Devolverá error si caller no está registrado o tiene pedidos pendientes o despachados,
+        /// fondos en custodia, disputas en curso o transferencias de stock ofrecidas, o si es una tienda compartida
+        /// que todavía no se disolvió.
+        #[ink(message)]
+        pub fn eliminar_cuenta(&mut self) -> Result<(), ErrorEliminarCuenta> {
+            self._eliminar_cuenta(self.env().block_timestamp(), self.env().caller())
+        }
+
+        //
+        // migracion.rs:
+        //
+
+        /// Inicia la migración de la cuenta de caller a una nueva AccountId, por ejemplo si su clave fue comprometida
+        /// o cambia de billetera. La migración debe confirmarse desde la cuenta nueva una vez cumplido el plazo de migración.
+        ///
+        /// Devolverá error si caller no está registrado, ya tiene una migración en curso, o la cuenta nueva
+        /// es la misma, el owner, un miembro del Staff, ya está registrada, fue eliminada o es destino de otra migración.
+        #[ink(message)]
+        pub fn iniciar_migracion_cuenta(&mut self, nueva: AccountId) -> Result<(), ErrorMigracionCuenta> {
+            self._iniciar_migracion_cuenta(self.env().block_timestamp(), self.env().caller(), nueva)
+        }
+
+        /// Cancela la migración en curso de la cuenta de caller.
+        ///
+        /// Devolverá error si caller no tiene una migración en curso.
+        #[ink(message)]
+        pub fn cancelar_migracion_cuenta(&mut self) -> Result<(), ErrorMigracionCuenta> {
+            self._cancelar_migracion_cuenta(self.env().caller())
+        }
+
+        /// Confirma desde la cuenta nueva (caller) la migración iniciada por la cuenta anterior. Caller pasa a ser
+        /// el titular de los pedidos, publicaciones, stock, perfil y calificaciones de la cuenta anterior.
+        ///
+        /// Devolverá error si no hay una migración en curso desde la cuenta anterior hacia caller,
+        /// no se cumplió el plazo de migración o alguna de las cuentas dejó de ser válida.
+        #[ink(message)]
+        pub fn confirmar_migracion_cuenta(&mut self, anterior: AccountId) -> Result<(), ErrorMigracionCuenta> {
+            self._confirmar_migracion_cuenta(self.env().block_timestamp(), self.env().caller(), anterior)
+        }
+
+        /// Devuelve la migración en curso de una cuenta, si existe.
+        #[ink(message)]
+        pub fn ver_migracion_cuenta(&self, id: AccountId) -> Option<MigracionCuenta> {
+            self._ver_migracion_cuenta(id)
+        }
+
+        //
+        // operador.rs:
+        //
+
+        /// Autoriza a una cuenta como operador de caller con los permisos indicados (gestionar stock, gestionar publicaciones,
+        /// despachar pedidos, responder disputas) y, opcionalmente, un vencimiento. Si ya era su operador, se reemplazan
+        /// sus permisos y vencimiento. Un operador nunca puede retirar fondos.
+        ///
+        /// Devolverá error si caller no está registrado o no es vendedor, el operador es caller, un usuario registrado,
+        /// el owner, un miembro del Staff u operador de otro vendedor, los permisos están vacíos o repetidos,
+        /// el vencimiento ya pasó o caller alcanzó el máximo de operadores.
+        #[ink(message)]
+        pub fn autorizar_operador(&mut self, operador: AccountId, permisos: Vec<PermisoOperador>, vencimiento: Option<u64>) -> Result<(), ErrorOperador> {
+            self._autorizar_operador(self.env().block_timestamp(), self.env().caller(), operador, permisos, vencimiento)
+        }
+
+        /// Revoca la autorización de un operador de caller.
+        ///
+        /// Devolverá error si la cuenta no es operador de caller.
+        #[ink(message)]
+        pub fn revocar_operador(&mut self, operador: AccountId) -> Result<(), ErrorOperador> {
+            self._revocar_operador(self.env().caller(), operador)
+        }
+
+        /// Devuelve los operadores autorizados por caller, incluidos los vencidos.
+        #[ink(message)]
+        pub fn ver_operadores(&self) -> Vec<(AccountId, Operador)> {
+            self._ver_operadores(self.env().caller())
+        }
+
+        //
+        // tienda_compartida.rs:
+        //
+
+        /// Convierte la cuenta de vendedor de caller en una tienda compartida con los socios, el umbral de aprobación
+        /// y la dirección de cobro indicados. Cada socio opera la tienda en el día a día como operador con todos los permisos,
+        /// mientras que los retiros de fondos y los cambios de dirección de cobro, socios o umbral requieren una propuesta
+        /// aprobada por tantos socios como indique el umbral.
+        ///
+        /// Devolverá error si caller no está registrado, no es vendedor o ya es una tienda compartida,
+        /// los socios son inválidos u operan para otro vendedor, o el umbral es 0 o mayor a la cantidad de socios.
+        #[ink(message)]
+        pub fn crear_tienda_compartida(&mut self, socios: Vec<AccountId>, umbral: u32, direccion_cobro: AccountId) -> Result<(), ErrorTiendaCompartida> {
+            self._crear_tienda_compartida(self.env().caller(), socios, umbral, direccion_cobro)
+        }
+
+        /// Caller, socio de la tienda, propone retirar los fondos de un pedido, cambiar la dirección de cobro,
+        /// cambiar los socios y el umbral o disolver la tienda compartida. Devuelve la ID de la propuesta.
+        ///
+        /// Devolverá error si la tienda no existe, caller no es socio, el pedido no es una venta de la tienda
+        /// o los socios o el umbral propuestos son inválidos.
+        #[ink(message)]
+        pub fn proponer_accion_tienda(&mut self, tienda: AccountId, accion: AccionPropuesta) -> Result<u128, ErrorTiendaCompartida> {
+            self._proponer_accion_tienda(self.env().block_timestamp(), self.env().caller(), tienda, accion)
+        }
+
+        /// Caller, socio de la tienda, aprueba una propuesta pendiente. Al alcanzar el umbral la propuesta se ejecuta
+        /// y, si es un retiro, los fondos se transfieren a la dirección de cobro de la tienda.
+        ///
+        /// Devolverá error si la propuesta no existe o no está pendiente, caller no es socio o ya la aprobó,
+        /// o la ejecución falla. En ese caso la aprobación no se registra.
+        #[ink(message)]
+        pub fn aprobar_propuesta_tienda(&mut self, id_propuesta: u128) -> Result<(), ErrorTiendaCompartida> {
+            let operacion = self._aprobar_propuesta_tienda(self.env().block_timestamp(), self.env().caller(), id_propuesta)?;
+
+            if let Some((tienda, valor)) = operacion {
+                self.transferir_fondos_vendedor(tienda, valor);
+            }
+
+            Ok(())
+        }
+
+        /// Caller cancela una propuesta pendiente que haya realizado.
+        ///
+        /// Devolverá error si la propuesta no existe, no está pendiente o caller no es su proponente.
+        #[ink(message)]
+        pub fn cancelar_propuesta_tienda(&mut self, id_propuesta: u128) -> Result<(), ErrorTiendaCompartida> {
+            self._cancelar_propuesta_tienda(self.env().block_timestamp(), self.env().caller(), id_propuesta)
+        }
+
+        /// Devuelve los datos de una tienda compartida, si existe.
+        #[ink(message)]
+        pub fn ver_tienda_compartida(&self, tienda: AccountId) -> Option<TiendaCompartida> {
+            self._ver_tienda_compartida(tienda)
+        }
+
+        /// Devuelve las propuestas de una tienda compartida, de la más antigua a la más reciente.
+        #[ink(message)]
+        pub fn ver_propuestas_tienda(&self, tienda: AccountId) -> Vec<PropuestaTienda> {
+            self._ver_propuestas_tienda(tienda)
+        }
+
+        //
+        // verificacion.rs:
+        //
+
+        /// Caller, vendedor, solicita una verificación del nivel indicado presentando el hash de su documentación.
+        ///
+        /// Devolverá error si caller no está registrado, no es vendedor, ya tiene una solicitud pendiente
+        /// o ya está verificado con ese nivel o uno superior.
+        #[ink(message)]
+        pub fn solicitar_verificacion(&mut self, nivel: NivelVerificacion, hash_documentos: [u8; 32]) -> Result<(), ErrorVerificacion> {
+            self._solicitar_verificacion(self.env().block_timestamp(), self.env().caller(), nivel, hash_documentos)
+        }
+
+        /// Devuelve la última solicitud de verificación de un vendedor.
+        ///
+        /// Devolverá None si no existe o caller no es el vendedor ni parte del Staff.
+        #[ink(message)]
+        pub fn ver_solicitud_verificacion(&self, vendedor: AccountId) -> Option<SolicitudVerificacion> {
+            self._ver_solicitud_verificacion(self.env().caller(), vendedor)
+        }
+
+        /// Devuelve la insignia de verificación de un vendedor, si tiene una.
+        #[ink(message)]
+        pub fn ver_verificacion(&self, vendedor: AccountId) -> Option<Verificacion> {
+            self._ver_verificacion(vendedor)
+        }
+
+        /// Devolverá la lista de vendedores con solicitudes de verificación pendientes.
+        ///
+        /// Devolverá None si caller no es parte del Staff.
+        #[ink(message)]
+        pub fn staff_ver_solicitudes_verificacion(&self) -> Option<Vec<AccountId>> {
+            self._staff_ver_solicitudes_verificacion(self.env().caller())
+        }
+
+        /// Aprueba la solicitud de verificación pendiente de un vendedor, otorgándole la insignia del nivel solicitado.
+        ///
+        /// Devolverá error si caller no es parte del Staff o el vendedor no tiene una solicitud pendiente.
+        #[ink(message)]
+        pub fn staff_aprobar_verificacion(&mut self, vendedor: AccountId) -> Result<(), ErrorVerificacion> {
+            self._staff_aprobar_verificacion(self.env().block_timestamp(), self.env().caller(), vendedor)
+        }
+
+        /// Rechaza la solicitud de verificación pendiente de un vendedor indicando el motivo.
+        ///
+        /// Devolverá error si caller no es parte del Staff, el vendedor no tiene una solicitud pendiente
+        /// o el motivo es inválido.
+        #[ink(message)]
+        pub fn staff_rechazar_verificacion(&mut self, vendedor: AccountId, motivo: String) -> Result<(), ErrorVerificacion> {
+            self._staff_rechazar_verificacion(self.env().block_timestamp(), self.env().caller(), vendedor, motivo)
+        }
+
+        /// Revoca la insignia de verificación de un vendedor indicando el motivo.
+        ///
+        /// Devolverá error si caller no es parte del Staff, el vendedor no está verificado o el motivo es inválido.
+        #[ink(message)]
+        pub fn staff_revocar_verificacion(&mut self, vendedor: AccountId, motivo: String) -> Result<(), ErrorVerificacion> {
+            self._staff_revocar_verificacion(self.env().block_timestamp(), self.env().caller(), vendedor, motivo)
+        }
+
+        /// Establece el nivel de verificación que debe tener un vendedor para publicar productos de una categoría
+        /// o de sus subcategorías. Con None se quita la exigencia.
+        ///
+        /// Devolverá error si caller no es parte del Staff o la categoría no existe.
+        #[ink(message)]
+        pub fn staff_exigir_verificacion_categoria(&mut self, categoria: u128, nivel: Option<NivelVerificacion>) -> Result<(), ErrorVerificacion> {
+            self._staff_exigir_verificacion_categoria(self.env().caller(), categoria, nivel)
+        }
+
+        //
+        // invariantes.rs:
+        //
+
+        /// Verifica, sin modificar nada, la consistencia del stock de cada vendedor (stock propio, publicaciones
+        /// y pedidos abiertos contra su registro de movimientos), los fondos en custodia contra el balance del contrato
+        /// y las referencias cruzadas entre usuarios, pedidos, publicaciones y disputas. Sólo para Staff.
+        ///
+        /// Los ámbitos Stock y Referencias se paginan por usuario y Pedidos por pedido, a partir de la posición `desde`;
+        /// el reporte indica desde dónde continuar. El ámbito General no se pagina.
+        ///
+        /// Devolverá error si caller no es Staff, o el límite es 0 o supera el máximo por consulta.
+        #[ink(message)]
+        pub fn verificar_invariantes(&self, ambito: AmbitoInvariantes, desde: u32, limite: u32) -> Result<ReporteInvariantes, ErrorVerificarInvariantes> {
+            self._verificar_invariantes(self.env().caller(), ambito, desde, limite, self.env().balance())
+        }
         
         //
         // pedido.rs: administrar compras    /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -348,13 +1328,14 @@ pub mod rustaceo_libre {
             tarifa_servicio
         }
 
-        /// Compra una cantidad de un producto
+        /// Compra una cantidad de un producto, adjuntando opcionalmente la dirección de entrega
+        /// cifrada para el vendedor junto con su compromiso.
         /// 
         /// Puede dar error si el usuario no existe, no es comprador, la publicación no existe,
-        /// el stock es insuficiente o el vendedor de la misma no existe.
+        /// el stock es insuficiente, el vendedor de la misma no existe o la dirección no es válida.
         #[ink(message, payable)]
-        pub fn comprar_producto(&mut self, id_publicacion: u128, cantidad: u32) -> Result<u128, ErrorComprarProducto> {
-            let operacion = self._comprar_producto(self.env().block_timestamp(), self.env().caller(), id_publicacion, cantidad, self.env().transferred_value());
+        pub fn comprar_producto(&mut self, id_publicacion: u128, cantidad: u32, direccion_entrega: Option<DireccionEntrega>) -> Result<u128, ErrorComprarProducto> {
+            let operacion = self._comprar_producto_con_direccion(self.env().block_timestamp(), self.env().caller(), id_publicacion, cantidad, self.env().transferred_value(), direccion_entrega);
 
             if let Ok(operacion) = operacion {
                 // devolver fondos sobrantes. el checkeo tal vez es innecesario pero por si acaso
@@ -362,6 +1343,26 @@ pub mod rustaceo_libre {
                     let _ = self.env().transfer(self.env().caller(), operacion.monto_transferido_sobrante);
                 }
 
+                // avisar al vendedor si la publicación cruzó su umbral de stock bajo
+                if let Some(alerta) = operacion.alerta_stock_bajo {
+                    self.env().emit_event(StockBajo {
+                        vendedor: alerta.vendedor,
+                        publicacion: alerta.publicacion,
+                        cantidad_ofertada: alerta.cantidad_ofertada,
+                        umbral: alerta.umbral,
+                    });
+                }
+
+                // ídem para el stock del producto si la reposición automática lo dejó bajo su umbral
+                if let Some(alerta) = operacion.alerta_stock_bajo_producto {
+                    self.env().emit_event(StockProductoBajo {
+                        vendedor: alerta.vendedor,
+                        producto: alerta.producto,
+                        cantidad: alerta.cantidad,
+                        umbral: alerta.umbral,
+                    });
+                }
+
                 Ok(operacion.id_nueva_transaccion)
             } else {
                 // fallo: devolver totalidad de los fondos transferidos
@@ -370,8 +1371,8 @@ pub mod rustaceo_libre {
             }
         }
 
-        /// El vendedor puede retirar los fondos de una compra si fue recibida hace al menos tres días
-        /// y no existe una disputa en curso en contra del vendedor,
+        /// El vendedor puede retirar los fondos de una compra si fue recibida hace al menos tres días,
+        /// o su plazo de devolución si es mayor, y no existe una disputa en curso en contra del vendedor,
         /// o ejecutar la política de reclamo si el comprador no la marca como recibida.
         /// 
         /// Política de reclamo:
@@ -389,18 +1390,39 @@ pub mod rustaceo_libre {
             let Ok(valor) = operacion
             else { return operacion };
 
-            let tarifa_servicio = self._calcular_tarifa_de_servicio(valor);
-            let Some(valor_final) = valor.checked_sub(tarifa_servicio)
-            else { 
-                let _ = self.env().transfer(self.env().caller(), valor);
-                return operacion;
-            };
+            self.transferir_fondos_vendedor(self.env().caller(), valor);
+
+            operacion
+        }
 
-            let _ = self.env().transfer(self.env().caller(), valor_final);
+        /// El vendedor puede retirar los fondos correspondientes a las unidades de un pedido
+        /// que el comprador haya confirmado como recibidas, siempre que la última recepción
+        /// haya sido hace al menos tres días, o su plazo de devolución si es mayor, y no exista una disputa en curso.
+        /// 
+        /// Devuelve el valor liberado antes de descontar la tarifa de servicio.
+        /// Puede dar error si el usuario no está registrado, el pedido no existe, no es el vendedor,
+        /// los fondos ya fueron transferidos, existe una disputa en curso, no pasaron tres días (o el plazo de devolución)
+        /// desde la última recepción o no hay fondos disponibles para retirar.
+        #[ink(message)]
+        pub fn retirar_fondos_parciales(&mut self, id_compra: u128) -> Result<u128, ErrorRetirarFondos> {
+            let operacion = self._retirar_fondos_parciales(self.env().block_timestamp(), self.env().caller(), id_compra);
+
+            let Ok(valor) = operacion
+            else { return operacion };
+
+            self.transferir_fondos_vendedor(self.env().caller(), valor);
 
             operacion
         }
 
+        /// Transfiere al vendedor el valor indicado, descontando la tarifa de servicio.
+        /// Si el vendedor es una tienda compartida, los fondos se envían a su dirección de cobro.
+        fn transferir_fondos_vendedor(&self, vendedor: AccountId, valor: u128) {
+            let tarifa_servicio = self._calcular_tarifa_de_servicio(valor);
+            let valor_final = valor.checked_sub(tarifa_servicio).unwrap_or(valor);
+            let _ = self.env().transfer(self.direccion_cobro(vendedor), valor_final);
+        }
+
         /// Si el pedido indicada está pendiente y el usuario es el vendedor, se establece como recibida.
         /// 
         /// Puede dar error si el usuario no está registrado, el pedido no existe,
@@ -411,6 +1433,35 @@ pub mod rustaceo_libre {
             self._pedido_despachado(self.env().block_timestamp(), self.env().caller(), compra_id)
         }
         
+        /// Ídem pedido_despachado, adjuntando el transportista y el código de seguimiento (o su hash) del envío.
+        /// 
+        /// Puede dar los mismos errores que pedido_despachado o error si la información de seguimiento no es válida.
+        #[ink(message)]
+        pub fn pedido_despachado_con_seguimiento(&mut self, compra_id: u128, transportista: String, codigo_seguimiento: String) -> Result<(), ErrorProductoDespachado> {
+            self._pedido_despachado_con_seguimiento(self.env().block_timestamp(), self.env().caller(), compra_id, transportista, codigo_seguimiento)
+        }
+
+        /// Permite al vendedor actualizar la información de seguimiento de un pedido despachado,
+        /// total o parcialmente. El registro anterior se conserva en el historial de seguimiento del pedido.
+        /// 
+        /// Puede dar error si el usuario no está registrado, el pedido no existe,
+        /// no es el vendedor quien lo actualiza, el pedido no tiene unidades despachadas o la información no es válida.
+        #[ink(message)]
+        pub fn actualizar_seguimiento(&mut self, compra_id: u128, transportista: String, codigo_seguimiento: String) -> Result<(), ErrorActualizarSeguimiento> {
+            self._actualizar_seguimiento(self.env().block_timestamp(), self.env().caller(), compra_id, transportista, codigo_seguimiento)
+        }
+        
+        /// Despacha una parte de las unidades pendientes de despacho de un pedido,
+        /// adjuntando opcionalmente el transportista y el código de seguimiento del envío.
+        /// El pedido se establece como despachado cuando la totalidad de sus unidades vigentes lo fueron.
+        /// 
+        /// Puede dar los mismos errores que pedido_despachado_con_seguimiento
+        /// o error si la cantidad es cero o mayor a la cantidad pendiente de despacho.
+        #[ink(message)]
+        pub fn pedido_despachado_parcial(&mut self, compra_id: u128, cantidad: u32, seguimiento: Option<(String, String)>) -> Result<(), ErrorProductoDespachado> {
+            self._pedido_despachado_parcial(self.env().block_timestamp(), self.env().caller(), compra_id, cantidad, seguimiento)
+        }
+
         /// Si el pedido indicado fue despachado y el usuario es el comprador, se establece como recibido.
         /// 
         /// Puede dar error si el usuario no está registrado, la compra no existe,
@@ -421,6 +1472,16 @@ pub mod rustaceo_libre {
             self._pedido_recibido(self.env().block_timestamp(), self.env().caller(), id_compra) 
         }
 
+        /// El comprador confirma la recepción de una parte de las unidades ya despachadas de un pedido.
+        /// El pedido se establece como recibido cuando la totalidad de sus unidades vigentes lo fueron.
+        /// 
+        /// Puede dar los mismos errores que pedido_recibido
+        /// o error si la cantidad es cero o mayor a la cantidad despachada pendiente de recepción.
+        #[ink(message)]
+        pub fn pedido_recibido_parcial(&mut self, id_compra: u128, cantidad: u32) -> Result<(), ErrorProductoRecibido> {
+            self._pedido_recibido_parcial(self.env().block_timestamp(), self.env().caller(), id_compra, cantidad)
+        }
+
         /// Dada una ID de pedido y una calificación (1..=5), se califica el mismo.
         /// Sólo se puede calificar una vez y sólo pueden calificar el comprador y vendedor de un pedido.
         /// 
@@ -434,11 +1495,15 @@ pub mod rustaceo_libre {
         /// Cancela el pedido si ambos participantes del mismo ejecutan esta misma función
         /// y si éste no fue recibida ni ya cancelada.
         /// Entrega automáticamente los fondos de la compra al comprador y el stock al vendedor.
+        /// Si el comprador cancela por su cuenta un pedido despachado parcialmente, sólo se cancelan
+        /// las unidades aún no despachadas.
         /// 
         /// Devuelve error si el usuario o pedido no existen, si el usuario no participa en el pedido,
         /// si el pedido ya fue cancelado o recibido y si quien solicita la cancelación ya la solicitó antes.
         #[ink(message)]
         pub fn cancelar_pedido(&mut self, id_compra: u128) -> Result<bool, ErrorCancelarPedido> {
+            let id_publicacion = self.pedidos.get(&id_compra).map(|pedido| pedido.publicacion);
+            let anterior = id_publicacion.and_then(|id| self._ver_publicacion(id));
             let operacion = self._cancelar_pedido(self.env().block_timestamp(), self.env().caller(), id_compra);
 
             let Ok(operacion) = operacion
@@ -449,9 +1514,75 @@ pub mod rustaceo_libre {
 
             let _ = self.env().transfer(comprador, valor);
 
+            // las unidades canceladas vuelven a la publicación
+            if let Some(id_publicacion) = id_publicacion {
+                self.notificar_interesados_publicacion(id_publicacion, anterior);
+            }
+
             Ok(true)
         }
 
+        /// Sólo ejecutable por Staff u OWNER.
+        /// Verifica que la dirección en texto plano y la sal brindadas correspondan al compromiso
+        /// de la dirección de entrega del pedido. Debe consultarse sin realizar una transacción
+        /// para que la dirección nunca se haga pública.
+        /// 
+        /// Devolverá error si caller no es Staff, el pedido no existe o no tiene dirección de entrega.
+        #[ink(message)]
+        pub fn staff_verificar_direccion(&self, id_pedido: u128, direccion: Vec<u8>, sal: Vec<u8>) -> Result<bool, ErrorVerificarDireccion> {
+            self._staff_verificar_direccion(self.env().caller(), id_pedido, direccion, sal)
+        }
+
+        /// Cancela las unidades de un pedido que aún no fueron despachadas,
+        /// devolviéndolas a la publicación y reembolsando su valor proporcional al comprador.
+        /// 
+        /// El vendedor puede hacerlo en cualquier momento. El comprador sólo si pasaron al menos
+        /// 14 días desde que realizó el pedido. Si ninguna unidad fue despachada, el pedido se cancela.
+        /// 
+        /// Devuelve el valor reembolsado al comprador.
+        /// Devolverá error si el usuario o pedido no existen, si el usuario no participa en el pedido,
+        /// si el pedido ya fue recibido o cancelado, si existe una disputa en curso,
+        /// si no hay unidades pendientes de despacho o si el comprador no convalida la política de cancelación.
+        #[ink(message)]
+        pub fn cancelar_unidades_restantes(&mut self, id_compra: u128) -> Result<u128, ErrorCancelarUnidadesRestantes> {
+            let id_publicacion = self.pedidos.get(&id_compra).map(|pedido| pedido.publicacion);
+            let anterior = id_publicacion.and_then(|id| self._ver_publicacion(id));
+            let operacion = self._cancelar_unidades_restantes(self.env().block_timestamp(), self.env().caller(), id_compra);
+
+            let Ok((comprador, reembolso)) = operacion
+            else { return Err(operacion.unwrap_err()) };
+
+            if reembolso > 0 {
+                let _ = self.env().transfer(comprador, reembolso);
+            }
+
+            // las unidades canceladas vuelven a la publicación
+            if let Some(id_publicacion) = id_publicacion {
+                self.notificar_interesados_publicacion(id_publicacion, anterior);
+            }
+
+            Ok(reembolso)
+        }
+
+        /// El vendedor reembolsa al comprador un valor arbitrario de un pedido sin cancelarlo.
+        /// El valor se descuenta de los fondos en custodia, reduciendo lo que luego podrá retirar,
+        /// y queda registrado en el historial de reembolsos del pedido.
+        /// 
+        /// Devolverá error si el usuario no está registrado, el pedido no existe, no es el vendedor,
+        /// el pedido fue cancelado, sus fondos ya fueron transferidos, existe una disputa en curso,
+        /// el valor es cero o supera los fondos en custodia o el motivo excede su longitud máxima.
+        #[ink(message)]
+        pub fn reembolso_parcial(&mut self, id_compra: u128, valor: Balance, motivo: String) -> Result<(), ErrorReembolsoParcial> {
+            let operacion = self._reembolso_parcial(self.env().block_timestamp(), self.env().caller(), id_compra, valor, motivo);
+
+            let Ok((comprador, reembolso)) = operacion
+            else { return Err(operacion.unwrap_err()) };
+
+            let _ = self.env().transfer(comprador, reembolso);
+
+            Ok(())
+        }
+
         //
         // pedido.rs: visualizar compras    ///////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
         //
@@ -472,11 +1603,12 @@ pub mod rustaceo_libre {
             self._ver_compras_estado(self.env().caller(), estado)
         }
 
-        /// Devuelve las compras del usuario que lo ejecuta que estén en el estado especificado
+        /// Devuelve las compras del usuario que lo ejecuta cuyo producto pertenezca a la categoría especificada
+        /// o a alguna de sus subcategorías
         /// 
-        /// Dará error si el usuario no está registrado como comprador o no tiene compras en ese estado
+        /// Dará error si el usuario no está registrado como comprador o no tiene compras
         #[ink(message)]
-        pub fn ver_compras_categoria(&self, categoria: CategoriaProducto) -> Result<Vec<Pedido>, ErrorVerCompras> {
+        pub fn ver_compras_categoria(&self, categoria: u128) -> Result<Vec<Pedido>, ErrorVerCompras> {
             self._ver_compras_categoria(self.env().caller(), categoria)
         }
 
@@ -500,11 +1632,12 @@ pub mod rustaceo_libre {
             self._ver_ventas_estado(self.env().caller(), estado)
         }
 
-        /// Devuelve las ventas del usuario que lo ejecuta que estén en el estado especificado
+        /// Devuelve las ventas del usuario que lo ejecuta cuyo producto pertenezca a la categoría especificada
+        /// o a alguna de sus subcategorías
         /// 
-        /// Dará error si el usuario no está registrado como vendedor o no tiene ventas en ese estado
+        /// Dará error si el usuario no está registrado como vendedor o no tiene ventas
         #[ink(message)]
-        pub fn ver_ventas_categoria(&self, categoria: CategoriaProducto) -> Result<Vec<Pedido>, ErrorVerVentas> {
+        pub fn ver_ventas_categoria(&self, categoria: u128) -> Result<Vec<Pedido>, ErrorVerVentas> {
             self._ver_ventas_categoria(self.env().caller(), categoria)
         }
 
@@ -550,11 +1683,12 @@ pub mod rustaceo_libre {
             self._disputar_pedido(self.env().block_timestamp(), self.env().caller(), id_pedido, argumento)
         }
 
-        /// Devolverá los datos de la disputa
+        /// Devolverá los datos de la disputa junto con el pedido disputado, su información de seguimiento
+        /// y los mensajes intercambiados entre comprador y vendedor
         /// 
         /// Devolverá None si el usuario no es parte del Staff ni participa en la disputa o si la misma no existe.
         #[ink(message)]
-        pub fn consultar_disputa(&self, disputa: u128) -> Option<Disputa> {
+        pub fn consultar_disputa(&self, disputa: u128) -> Option<DetalleDisputa> {
             self._consultar_disputa(self.env().caller(), disputa)
         }
 
@@ -587,11 +1721,121 @@ pub mod rustaceo_libre {
             else { return Err(operacion.unwrap_err()) }; // safe unwrap
 
             // transferir fondos al ganador de la disputa
-            let _ = self.env().transfer(id_ganador, valor_total);
+            let _ = self.env().transfer(self.direccion_cobro(id_ganador), valor_total);
 
             Ok(())
         }
 
+        //
+        // mensaje.rs:
+        //
+
+        /// Agrega un mensaje al hilo de mensajes de un pedido. Los mensajes no pueden modificarse ni eliminarse.
+        /// Sólo el comprador y el vendedor del pedido pueden enviar mensajes.
+        ///
+        /// Devuelve la posición del mensaje en el hilo.
+        /// Devolverá error si el usuario no está registrado, el pedido no existe, el usuario no participa en el pedido,
+        /// el mensaje no es válido o el usuario alcanzó la cantidad máxima de mensajes en el pedido.
+        #[ink(message)]
+        pub fn enviar_mensaje(&mut self, id_pedido: u128, contenido: ContenidoMensaje) -> Result<u32, ErrorMensajePedido> {
+            self._enviar_mensaje(self.env().block_timestamp(), self.env().caller(), id_pedido, contenido)
+        }
+
+        /// Devuelve el hilo de mensajes de un pedido.
+        ///
+        /// Devolverá error si el pedido no existe o el usuario no participa en el pedido ni es parte del Staff.
+        #[ink(message)]
+        pub fn ver_mensajes(&self, id_pedido: u128) -> Result<Vec<MensajePedido>, ErrorMensajePedido> {
+            self._ver_mensajes(self.env().caller(), id_pedido)
+        }
+
+        //
+        // devolucion.rs:
+        //
+
+        /// Establece la cantidad de días posteriores a la recepción de un pedido
+        /// en los que el comprador puede solicitar su devolución. Un plazo de 0 días no admite devoluciones.
+        /// Mientras no venza el plazo, el vendedor no puede retirar los fondos de sus pedidos recibidos.
+        ///
+        /// Devolverá error si el usuario no está registrado, no es vendedor o el plazo supera el máximo permitido.
+        #[ink(message)]
+        pub fn establecer_plazo_devolucion(&mut self, dias: u32) -> Result<(), ErrorDevolucion> {
+            self._establecer_plazo_devolucion(self.env().caller(), dias)
+        }
+
+        /// El comprador solicita la devolución de unidades ya recibidas de un pedido, indicando el motivo.
+        /// Sólo puede hacerlo dentro del plazo de devolución establecido por el vendedor
+        /// y mientras los fondos del pedido sigan en custodia del contrato.
+        ///
+        /// Devolverá error si el usuario no está registrado, el pedido no existe, no es el comprador,
+        /// el pedido no fue recibido, el plazo expiró, los fondos ya fueron transferidos,
+        /// hay una disputa en curso, el pedido ya tiene una devolución, la cantidad o el motivo no son válidos.
+        #[ink(message)]
+        pub fn solicitar_devolucion(&mut self, id_pedido: u128, cantidad: u32, motivo: String) -> Result<(), ErrorDevolucion> {
+            self._solicitar_devolucion(self.env().block_timestamp(), self.env().caller(), id_pedido, cantidad, motivo)
+        }
+
+        /// El vendedor aprueba o rechaza la solicitud de devolución de un pedido.
+        ///
+        /// Devolverá error si el usuario no está registrado, el pedido o la devolución no existen,
+        /// no es el vendedor o la devolución no está pendiente de respuesta.
+        #[ink(message)]
+        pub fn responder_devolucion(&mut self, id_pedido: u128, aprobar: bool) -> Result<(), ErrorDevolucion> {
+            self._responder_devolucion(self.env().block_timestamp(), self.env().caller(), id_pedido, aprobar)
+        }
+
+        /// El comprador informa el transportista y el código de seguimiento del envío de una devolución aprobada.
+        /// Debe hacerlo dentro del plazo de despacho de devoluciones.
+        ///
+        /// Devolverá error si el usuario no está registrado, el pedido o la devolución no existen,
+        /// no es el comprador, la devolución no fue aprobada, venció el plazo de despacho
+        /// o la información de seguimiento no es válida.
+        #[ink(message)]
+        pub fn devolucion_despachada(&mut self, id_pedido: u128, transportista: String, codigo_seguimiento: String) -> Result<(), ErrorDevolucion> {
+            self._devolucion_despachada(self.env().block_timestamp(), self.env().caller(), id_pedido, transportista, codigo_seguimiento)
+        }
+
+        /// El vendedor confirma la recepción de las unidades devueltas.
+        /// Las unidades vuelven a su stock y se reembolsa al comprador el valor correspondiente.
+        ///
+        /// Devolverá el valor reembolsado.
+        /// Devolverá error si el usuario no está registrado, el pedido o la devolución no existen,
+        /// no es el vendedor o la devolución no fue despachada.
+        #[ink(message)]
+        pub fn devolucion_recibida(&mut self, id_pedido: u128) -> Result<u128, ErrorDevolucion> {
+            let operacion = self._devolucion_recibida(self.env().block_timestamp(), self.env().caller(), id_pedido);
+
+            let Ok((comprador, reembolso)) = operacion
+            else { return Err(operacion.unwrap_err()) };
+
+            if reembolso > 0 {
+                let _ = self.env().transfer(comprador, reembolso);
+            }
+
+            Ok(reembolso)
+        }
+
+        /// Cierra una devolución cuyo plazo venció sin que la contraparte actuara.
+        /// El vendedor puede cerrar una devolución aprobada que el comprador no despachó a tiempo.
+        /// El comprador puede cerrar una devolución despachada cuya recepción el vendedor no confirmó a tiempo,
+        /// y el pedido pasa a disputa para que el Staff decida a quién corresponden los fondos.
+        ///
+        /// Devolverá error si el usuario no está registrado, el pedido o la devolución no existen,
+        /// el caller no es la contraparte que puede cerrarla, la devolución no está aprobada ni despachada
+        /// o el plazo aún no venció.
+        #[ink(message)]
+        pub fn cerrar_devolucion_vencida(&mut self, id_pedido: u128) -> Result<(), ErrorDevolucion> {
+            self._cerrar_devolucion_vencida(self.env().block_timestamp(), self.env().caller(), id_pedido)
+        }
+
+        /// Devuelve la devolución de un pedido.
+        ///
+        /// Devolverá None si la devolución no existe o el usuario no participa en el pedido ni es parte del Staff.
+        #[ink(message)]
+        pub fn ver_devolucion(&self, id_pedido: u128) -> Option<Devolucion> {
+            self._ver_devolucion(self.env().caller(), id_pedido)
+        }
+
 ////////////////////////////////////////////////////////////////////////////////
 
         /// Devuelve la siguiente ID disponible para pedidos
@@ -661,6 +1905,70 @@ pub mod rustaceo_libre {
             self.publicaciones_siguiente_id = add_res;
             id // devolver
         }
+
+        /// Devuelve la siguiente ID disponible para transferencias de stock
+        /// 
+        /// Si la próxima ID causaría Overflow, devuelve 0 y reinicia la cuenta.
+        pub fn next_id_transferencias_stock(&mut self) -> u128 {
+            let id = self.transferencias_stock_siguiente_id; // obtener actual
+            let add_res = self.transferencias_stock_siguiente_id.checked_add(1); // sumarle 1 al actual para que apunte a un id desocupado
+            
+            let Some(add_res) = add_res
+            else {
+                self.transferencias_stock_siguiente_id = 1;
+                return 0;
+            };
+
+            self.transferencias_stock_siguiente_id = add_res;
+            id // devolver
+        }
+
+        /// Devuelve la siguiente ID disponible para categorías
+        /// 
+        /// Si la próxima ID causaría Overflow, devuelve 0 y reinicia la cuenta.
+        pub fn next_id_categorias(&mut self) -> u128 {
+            let id = self.categorias_siguiente_id; // obtener actual
+            let add_res = self.categorias_siguiente_id.checked_add(1); // sumarle 1 al actual para que apunte a un id desocupado
+            
+            let Some(add_res) = add_res
+            else {
+                self.categorias_siguiente_id = 1;
+                return 0;
+            };
+
+            self.categorias_siguiente_id = add_res;
+            id // devolver
+        }
+
+        /// Devuelve la siguiente ID disponible para propuestas de tiendas compartidas
+        /// 
+        /// Si la próxima ID causaría Overflow, devuelve 0 y reinicia la cuenta.
+        pub fn next_id_propuestas_tiendas(&mut self) -> u128 {
+            let id = self.propuestas_tiendas_siguiente_id; // obtener actual
+            let add_res = self.propuestas_tiendas_siguiente_id.checked_add(1); // sumarle 1 al actual para que apunte a un id desocupado
+            
+            let Some(add_res) = add_res
+            else {
+                self.propuestas_tiendas_siguiente_id = 1;
+                return 0;
+            };
+
+            self.propuestas_tiendas_siguiente_id = add_res;
+            id // devolver
+        }
+
+        /// Devuelve la próxima ID a asignar de cada colección, sin consumirla
+        pub fn siguientes_ids(&self) -> [(ColeccionIds, u128); 7] {
+            [
+                (ColeccionIds::Pedidos, self.pedidos_siguiente_id),
+                (ColeccionIds::Disputas, self.disputas_siguiente_id),
+                (ColeccionIds::Productos, self.productos_siguiente_id),
+                (ColeccionIds::Publicaciones, self.publicaciones_siguiente_id),
+                (ColeccionIds::TransferenciasStock, self.transferencias_stock_siguiente_id),
+                (ColeccionIds::Categorias, self.categorias_siguiente_id),
+                (ColeccionIds::PropuestasTiendas, self.propuestas_tiendas_siguiente_id),
+            ]
+        }
     }
 
     /// Unit tests in Rust are normally defined within such a `#[cfg(test)]`
@@ -730,5 +2038,38 @@ pub mod rustaceo_libre {
             assert_eq!(rustaceo_libre.ver_usuarios_compradores().len(), 2);
             assert_eq!(rustaceo_libre.ver_usuarios_vendedores().len(), 2);
         }
+
+        #[ink::test]
+        fn publicacion_favorita_emite_eventos() {
+            let mut rustaceo_libre = RustaceoLibre::default();
+            let vendedor = AccountId::from([0x1; 32]);
+            let comprador = AccountId::from([0x2; 32]);
+
+            rustaceo_libre._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+            rustaceo_libre._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
+            let id_producto = rustaceo_libre._registrar_producto(0, vendedor, DatosProducto::new("Mate".into(), "Calabaza".into(), 1), 10).unwrap();
+            rustaceo_libre._staff_aprobar_producto(rustaceo_libre.owner, id_producto).unwrap();
+            let id_publicacion = rustaceo_libre._realizar_publicacion(0, vendedor, id_producto, 5, 100).unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(vendedor);
+
+            // sin interesados no se emiten eventos
+            assert_eq!(rustaceo_libre.modificar_precio_publicacion(id_publicacion, 90), Ok(()));
+            assert_eq!(ink::env::test::recorded_events().count(), 0);
+
+            rustaceo_libre._agregar_publicacion_favorita(comprador, id_publicacion).unwrap();
+
+            // una suba de precio no notifica, una baja sí
+            assert_eq!(rustaceo_libre.modificar_precio_publicacion(id_publicacion, 120), Ok(()));
+            assert_eq!(ink::env::test::recorded_events().count(), 0);
+            assert_eq!(rustaceo_libre.modificar_precio_publicacion(id_publicacion, 80), Ok(()));
+            assert_eq!(ink::env::test::recorded_events().count(), 1);
+
+            // reposición de stock
+            assert_eq!(rustaceo_libre.modificar_cantidad_ofertada(id_publicacion, 0), Ok(()));
+            assert_eq!(ink::env::test::recorded_events().count(), 1);
+            assert_eq!(rustaceo_libre.modificar_cantidad_ofertada(id_publicacion, 3), Ok(()));
+            assert_eq!(ink::env::test::recorded_events().count(), 2);
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,267 @@
+use ink::{prelude::vec::Vec, primitives::AccountId};
+
+use crate::{rustaceo_libre::RustaceoLibre, structs::operador::PermisoOperador};
+
+/// Alerta generada cuando una compra deja la cantidad ofertada de una publicación
+/// en o por debajo del umbral configurado por su vendedor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AlertaStockBajo {
+    pub vendedor: AccountId,
+    pub publicacion: u128,
+    pub cantidad_ofertada: u32,
+    pub umbral: u32,
+}
+
+/// Alerta generada cuando la reposición automática de una publicación deja el stock del producto
+/// en o por debajo del umbral configurado por su vendedor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AlertaStockBajoProducto {
+    pub vendedor: AccountId,
+    pub producto: u128,
+    pub cantidad: u32,
+    pub umbral: u32,
+}
+
+/// Entrada del reporte de stock bajo: ID de la publicación o producto, cantidad actual y umbral configurado.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub struct EntradaStockBajo {
+    pub id: u128,
+    pub cantidad: u32,
+    pub umbral: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub struct ReporteStockBajo {
+    pub publicaciones: Vec<EntradaStockBajo>,
+    pub productos: Vec<EntradaStockBajo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ErrorUmbralStock {
+    UsuarioNoRegistrado,
+    NoEsVendedor,
+    ProductoSinStock,
+    PublicacionInexistente,
+    NoEsElVendedor,
+}
+
+impl RustaceoLibre {
+
+    //
+
+    /// Establece el umbral de stock bajo de un producto en el stock del vendedor.
+    /// Un umbral de 0 elimina la configuración.
+    ///
+    /// Devolverá error si el usuario no está registrado, no es vendedor o no tiene stock registrado del producto.
+    pub fn _establecer_umbral_stock_producto(&mut self, timestamp: u64, caller: AccountId, id_producto: u128, umbral: u32) -> Result<(), ErrorUmbralStock> {
+        // un operador autorizado actúa en nombre del vendedor
+        let caller = self.actuar_como_vendedor(timestamp, caller, PermisoOperador::GestionarStock);
+
+        // validar usuario
+        let Some(usuario) = self.usuarios.get(&caller)
+        else { return Err(ErrorUmbralStock::UsuarioNoRegistrado); };
+
+        if !usuario.es_vendedor() {
+            return Err(ErrorUmbralStock::NoEsVendedor);
+        }
+
+        // validar que el vendedor tenga el producto
+        if usuario.obtener_stock_producto(&id_producto).is_none() {
+            return Err(ErrorUmbralStock::ProductoSinStock);
+        }
+
+        if umbral == 0 {
+            self.umbrales_stock_productos.remove(&(caller, id_producto));
+        } else {
+            self.umbrales_stock_productos.insert((caller, id_producto), umbral);
+        }
+
+        Ok(())
+    }
+
+    //
+
+    /// Establece el umbral de stock bajo de una publicación del vendedor.
+    /// Un umbral de 0 elimina la configuración.
+    ///
+    /// Devolverá error si el usuario no está registrado, la publicación no existe o no es su vendedor.
+    pub fn _establecer_umbral_stock_publicacion(&mut self, timestamp: u64, caller: AccountId, id_publicacion: u128, umbral: u32) -> Result<(), ErrorUmbralStock> {
+        // un operador autorizado actúa en nombre del vendedor
+        let caller = self.publicaciones.get(&id_publicacion)
+            .map_or(caller, |p| self.actuar_como_vendedor_de(timestamp, caller, p.vendedor, PermisoOperador::GestionarPublicaciones));
+
+        // validar usuario
+        if !self.usuarios.contains_key(&caller) {
+            return Err(ErrorUmbralStock::UsuarioNoRegistrado);
+        }
+
+        // validar publicación
+        let Some(publicacion) = self.publicaciones.get(&id_publicacion)
+        else { return Err(ErrorUmbralStock::PublicacionInexistente); };
+
+        if publicacion.vendedor != caller {
+            return Err(ErrorUmbralStock::NoEsElVendedor);
+        }
+
+        if umbral == 0 {
+            self.umbrales_stock_publicaciones.remove(&id_publicacion);
+        } else {
+            self.umbrales_stock_publicaciones.insert(id_publicacion, umbral);
+        }
+
+        Ok(())
+    }
+
+    //
+
+    /// Devuelve las publicaciones y entradas de StockProductos del vendedor
+    /// cuya cantidad se encuentra en o por debajo del umbral configurado.
+    ///
+    /// Devolverá error si el usuario no está registrado o no es vendedor.
+    pub fn _ver_stock_bajo(&self, timestamp: u64, caller: AccountId) -> Result<ReporteStockBajo, ErrorUmbralStock> {
+        // un operador autorizado actúa en nombre del vendedor
+        let caller = self.actuar_como_vendedor(timestamp, caller, PermisoOperador::GestionarStock);
+
+        // validar usuario
+        let Some(usuario) = self.usuarios.get(&caller)
+        else { return Err(ErrorUmbralStock::UsuarioNoRegistrado); };
+
+        if !usuario.es_vendedor() {
+            return Err(ErrorUmbralStock::NoEsVendedor);
+        }
+
+        // publicaciones
+        let publicaciones: Vec<EntradaStockBajo> = usuario.obtener_publicaciones().unwrap_or_default().iter().filter_map(|id| {
+            let umbral = *self.umbrales_stock_publicaciones.get(id)?;
+            let publicacion = self.publicaciones.get(id)?;
+
+            if publicacion.cantidad_ofertada > umbral {
+                return None;
+            }
+
+            Some(EntradaStockBajo { id: *id, cantidad: publicacion.cantidad_ofertada, umbral })
+        }).collect();
+
+        // productos en stock del vendedor
+        let productos: Vec<EntradaStockBajo> = self.umbrales_stock_productos.range((caller, 0)..=(caller, u128::MAX)).filter_map(|((_, id), umbral)| {
+            let cantidad = usuario.obtener_stock_producto(id)?;
+            if cantidad > *umbral {
+                return None;
+            }
+
+            Some(EntradaStockBajo { id: *id, cantidad, umbral: *umbral })
+        }).collect();
+
+        Ok(ReporteStockBajo { publicaciones, productos })
+    }
+
+    /// Devuelve la alerta correspondiente si la cantidad ofertada de la publicación
+    /// pasó de estar por encima de su umbral a estar en o por debajo del mismo.
+    pub fn alerta_stock_bajo_publicacion(&self, id_publicacion: u128, cantidad_anterior: u32) -> Option<AlertaStockBajo> {
+        let umbral = *self.umbrales_stock_publicaciones.get(&id_publicacion)?;
+        let publicacion = self.publicaciones.get(&id_publicacion)?;
+
+        if cantidad_anterior <= umbral || publicacion.cantidad_ofertada > umbral {
+            return None;
+        }
+
+        Some(AlertaStockBajo {
+            vendedor: publicacion.vendedor,
+            publicacion: id_publicacion,
+            cantidad_ofertada: publicacion.cantidad_ofertada,
+            umbral,
+        })
+    }
+
+    /// Devuelve la alerta correspondiente si el stock del producto del vendedor
+    /// pasó de estar por encima de su umbral a estar en o por debajo del mismo.
+    pub fn alerta_stock_bajo_producto(&self, vendedor: AccountId, id_producto: u128, cantidad_anterior: u32) -> Option<AlertaStockBajoProducto> {
+        let umbral = *self.umbrales_stock_productos.get(&(vendedor, id_producto))?;
+        let cantidad = self.usuarios.get(&vendedor)?.obtener_stock_producto(&id_producto)?;
+
+        if cantidad_anterior <= umbral || cantidad > umbral {
+            return None;
+        }
+
+        Some(AlertaStockBajoProducto {
+            vendedor,
+            producto: id_producto,
+            cantidad,
+            umbral,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::pruebas::preparar_publicacion;
+
+    #[ink::test]
+    fn compra_que_cruza_umbral_genera_alerta() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (vendedor, comprador, _, id_publicacion) = preparar_publicacion(&mut contrato, 10, 6);
+
+        assert_eq!(contrato._establecer_umbral_stock_publicacion(0, vendedor, id_publicacion, 3), Ok(()));
+
+        // 6 -> 4: sigue por encima del umbral
+        let resultado = contrato._comprar_producto(0, comprador, id_publicacion, 2, 200).unwrap();
+        assert_eq!(resultado.alerta_stock_bajo, None);
+
+        // 4 -> 3: cruza el umbral
+        let resultado = contrato._comprar_producto(0, comprador, id_publicacion, 1, 100).unwrap();
+        assert_eq!(resultado.alerta_stock_bajo, Some(AlertaStockBajo { vendedor, publicacion: id_publicacion, cantidad_ofertada: 3, umbral: 3 }));
+
+        // 3 -> 2: ya estaba por debajo, no se repite la alerta
+        let resultado = contrato._comprar_producto(0, comprador, id_publicacion, 1, 100).unwrap();
+        assert_eq!(resultado.alerta_stock_bajo, None);
+    }
+
+    #[ink::test]
+    fn ver_stock_bajo_lista_publicaciones_y_productos() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (vendedor, _, id_producto, id_publicacion) = preparar_publicacion(&mut contrato, 10, 6);
+
+        // stock del vendedor: 10 - 6 publicadas = 4
+        assert_eq!(contrato._establecer_umbral_stock_producto(0, vendedor, id_producto, 4), Ok(()));
+        assert_eq!(contrato._establecer_umbral_stock_publicacion(0, vendedor, id_publicacion, 5), Ok(()));
+
+        let reporte = contrato._ver_stock_bajo(0, vendedor).unwrap();
+        assert_eq!(reporte.productos, vec![EntradaStockBajo { id: id_producto, cantidad: 4, umbral: 4 }]);
+        assert!(reporte.publicaciones.is_empty());
+
+        assert_eq!(contrato._establecer_umbral_stock_publicacion(0, vendedor, id_publicacion, 6), Ok(()));
+        assert_eq!(contrato._establecer_umbral_stock_producto(0, vendedor, id_producto, 0), Ok(()));
+
+        let reporte = contrato._ver_stock_bajo(0, vendedor).unwrap();
+        assert_eq!(reporte.publicaciones, vec![EntradaStockBajo { id: id_publicacion, cantidad: 6, umbral: 6 }]);
+        assert!(reporte.productos.is_empty());
+    }
+
+    #[ink::test]
+    fn establecer_umbral_validaciones() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (vendedor, comprador, id_producto, id_publicacion) = preparar_publicacion(&mut contrato, 10, 6);
+
+        assert_eq!(contrato._establecer_umbral_stock_producto(0, comprador, id_producto, 1), Err(ErrorUmbralStock::NoEsVendedor));
+        assert_eq!(contrato._establecer_umbral_stock_producto(0, vendedor, 99, 1), Err(ErrorUmbralStock::ProductoSinStock));
+        assert_eq!(contrato._establecer_umbral_stock_publicacion(0, comprador, id_publicacion, 1), Err(ErrorUmbralStock::NoEsElVendedor));
+        assert_eq!(contrato._establecer_umbral_stock_publicacion(0, vendedor, 99, 1), Err(ErrorUmbralStock::PublicacionInexistente));
+        assert_eq!(contrato._ver_stock_bajo(0, comprador), Err(ErrorUmbralStock::NoEsVendedor));
+    }
+}
@@ -0,0 +1,346 @@
+use ink::{prelude::{collections::BTreeSet, string::String, vec::Vec}, primitives::AccountId};
+
+use crate::{rustaceo_libre::RustaceoLibre, structs::producto::{ErrorRegistrarProducto, EstadoProducto}};
+
+/// Cantidad máxima de atributos de un producto, de definiciones del esquema de una categoría y de filtros por consulta
+pub const MAX_ATRIBUTOS_PRODUCTO: usize = 32;
+/// Longitud máxima (en bytes) de la clave de un atributo
+pub const MAX_LARGO_CLAVE_ATRIBUTO: usize = 32;
+/// Longitud máxima (en bytes) de un valor de texto de un atributo
+pub const MAX_LARGO_VALOR_ATRIBUTO: usize = 128;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum TipoAtributo {
+    Texto,
+    Entero,
+    Booleano,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ValorAtributo {
+    Texto(String),
+    Entero(i64),
+    Booleano(bool),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub struct Atributo {
+    pub clave: String,
+    pub valor: ValorAtributo,
+}
+
+/// Definición de un atributo en el esquema de una categoría
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub struct DefinicionAtributo {
+    pub clave: String,
+    pub tipo: TipoAtributo,
+    pub obligatorio: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum CondicionAtributo {
+    Igual(ValorAtributo),
+    MayorOIgual(i64), // sólo para atributos enteros
+    MenorOIgual(i64), // sólo para atributos enteros
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub struct FiltroAtributo {
+    pub clave: String,
+    pub condicion: CondicionAtributo,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ErrorEsquemaAtributos {
+    UsuarioNoStaff,
+    CategoriaInexistente,
+    DemasiadasDefiniciones,
+    ClaveInvalida,
+    ClaveDuplicada,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ErrorFiltrarProductos {
+    CategoriaInexistente,
+    DemasiadosFiltros,
+}
+
+//
+// impl ValorAtributo
+//
+
+impl ValorAtributo {
+    pub fn tipo(&self) -> TipoAtributo {
+        match self {
+            ValorAtributo::Texto(_) => TipoAtributo::Texto,
+            ValorAtributo::Entero(_) => TipoAtributo::Entero,
+            ValorAtributo::Booleano(_) => TipoAtributo::Booleano,
+        }
+    }
+}
+
+//
+// impl CondicionAtributo
+//
+
+impl CondicionAtributo {
+    /// Devuelve true si el valor cumple la condición
+    pub fn se_cumple(&self, valor: &ValorAtributo) -> bool {
+        match (self, valor) {
+            (CondicionAtributo::Igual(esperado), valor) => esperado == valor,
+            (CondicionAtributo::MayorOIgual(minimo), ValorAtributo::Entero(valor)) => valor >= minimo,
+            (CondicionAtributo::MenorOIgual(maximo), ValorAtributo::Entero(valor)) => valor <= maximo,
+            _ => false,
+        }
+    }
+}
+
+fn clave_valida(clave: &str) -> bool {
+    !clave.is_empty() && clave.len() <= MAX_LARGO_CLAVE_ATRIBUTO
+}
+
+impl RustaceoLibre {
+
+    /// Devuelve el esquema de atributos que rige para una categoría: sus propias definiciones
+    /// más las heredadas de sus categorías ancestro. Ante claves repetidas prevalece la definición más cercana.
+    pub fn esquema_efectivo(&self, categoria: &u128) -> Vec<DefinicionAtributo> {
+        let mut esquema: Vec<DefinicionAtributo> = Vec::new();
+        let mut actual = Some(*categoria);
+
+        while let Some(id) = actual {
+            if let Some(definiciones) = self.esquemas_atributos.get(&id) {
+                for definicion in definiciones {
+                    if !esquema.iter().any(|d| d.clave == definicion.clave) {
+                        esquema.push(definicion.clone());
+                    }
+                }
+            }
+
+            actual = self.categorias.get(&id).and_then(|c| c.padre);
+        }
+
+        esquema
+    }
+
+    /// Valida los atributos de un producto contra el esquema de su categoría:
+    /// claves y valores de largo válido, sin claves repetidas, con los atributos obligatorios presentes
+    /// y con el tipo de valor definido. Se admiten atributos que el esquema no define.
+    pub(crate) fn validar_atributos_producto(&self, categoria: &u128, atributos: &[Atributo]) -> Result<(), ErrorRegistrarProducto> {
+        if atributos.len() > MAX_ATRIBUTOS_PRODUCTO {
+            return Err(ErrorRegistrarProducto::AtributosInvalidos);
+        }
+
+        let mut claves = BTreeSet::new();
+        for atributo in atributos {
+            if !clave_valida(&atributo.clave) || !claves.insert(atributo.clave.as_str()) {
+                return Err(ErrorRegistrarProducto::AtributosInvalidos);
+            }
+
+            if let ValorAtributo::Texto(texto) = &atributo.valor {
+                if texto.len() > MAX_LARGO_VALOR_ATRIBUTO {
+                    return Err(ErrorRegistrarProducto::AtributosInvalidos);
+                }
+            }
+        }
+
+        for definicion in self.esquema_efectivo(categoria) {
+            let Some(atributo) = atributos.iter().find(|a| a.clave == definicion.clave)
+            else {
+                if definicion.obligatorio {
+                    return Err(ErrorRegistrarProducto::AtributoObligatorioFaltante);
+                }
+                continue;
+            };
+
+            if atributo.valor.tipo() != definicion.tipo {
+                return Err(ErrorRegistrarProducto::TipoDeAtributoInvalido);
+            }
+        }
+
+        Ok(())
+    }
+
+    //
+
+    /// Define (o reemplaza) el esquema de atributos propio de una categoría, heredado por sus subcategorías.
+    /// Un esquema vacío elimina el de la categoría. Los productos ya registrados no se revalidan.
+    ///
+    /// Devolverá error si el usuario no es parte del Staff, la categoría no existe, el esquema excede
+    /// la cantidad máxima de definiciones o alguna clave está vacía, excede su longitud máxima o está repetida.
+    pub fn _staff_definir_esquema_atributos(&mut self, caller: AccountId, categoria: u128, esquema: Vec<DefinicionAtributo>) -> Result<(), ErrorEsquemaAtributos> {
+        // validar staff
+        if !self.staff.contains(&caller) && caller != self.owner {
+            return Err(ErrorEsquemaAtributos::UsuarioNoStaff);
+        }
+
+        // validar categoría
+        if !self.categorias.contains_key(&categoria) {
+            return Err(ErrorEsquemaAtributos::CategoriaInexistente);
+        }
+
+        // validar esquema
+        if esquema.len() > MAX_ATRIBUTOS_PRODUCTO {
+            return Err(ErrorEsquemaAtributos::DemasiadasDefiniciones);
+        }
+
+        let mut claves = BTreeSet::new();
+        for definicion in esquema.iter() {
+            if !clave_valida(&definicion.clave) {
+                return Err(ErrorEsquemaAtributos::ClaveInvalida);
+            }
+
+            if !claves.insert(definicion.clave.as_str()) {
+                return Err(ErrorEsquemaAtributos::ClaveDuplicada);
+            }
+        }
+
+        if esquema.is_empty() {
+            self.esquemas_atributos.remove(&categoria);
+        } else {
+            self.esquemas_atributos.insert(categoria, esquema);
+        }
+
+        Ok(())
+    }
+
+    //
+
+    /// Devuelve las IDs de los productos aprobados que cumplen todos los filtros de atributos,
+    /// opcionalmente restringidos a una categoría y sus subcategorías.
+    ///
+    /// Devolverá error si la categoría no existe o se excede la cantidad máxima de filtros.
+    pub fn _ver_productos_filtrados(&self, categoria: Option<u128>, filtros: Vec<FiltroAtributo>) -> Result<Vec<u128>, ErrorFiltrarProductos> {
+        // validar categoría
+        if let Some(categoria) = &categoria {
+            if !self.categorias.contains_key(categoria) {
+                return Err(ErrorFiltrarProductos::CategoriaInexistente);
+            }
+        }
+
+        // validar filtros
+        if filtros.len() > MAX_ATRIBUTOS_PRODUCTO {
+            return Err(ErrorFiltrarProductos::DemasiadosFiltros);
+        }
+
+        let productos = self.productos.iter()
+            .filter(|(_, producto)| producto.estado == EstadoProducto::Aprobado)
+            .filter(|(_, producto)| categoria.as_ref().map_or(true, |c| self.pertenece_a_categoria(&producto.categoria, c)))
+            .filter(|(_, producto)| filtros.iter().all(|filtro| {
+                producto.atributos.iter().any(|a| a.clave == filtro.clave && filtro.condicion.se_cumple(&a.valor))
+            }))
+            .map(|(id, _)| *id)
+            .collect();
+
+        Ok(productos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::{producto::DatosProducto, usuario::RolDeSeleccion};
+
+    fn atributo(clave: &str, valor: ValorAtributo) -> Atributo {
+        Atributo { clave: clave.into(), valor }
+    }
+
+    fn definicion(clave: &str, tipo: TipoAtributo, obligatorio: bool) -> DefinicionAtributo {
+        DefinicionAtributo { clave: clave.into(), tipo, obligatorio }
+    }
+
+    #[ink::test]
+    fn esquema_valida_registro_y_se_hereda() {
+        let mut contrato = RustaceoLibre::new(0);
+        let owner = contrato.owner;
+        let vendedor = AccountId::from([0x2; 32]);
+        contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+
+        let esquema = vec![definicion("RAM", TipoAtributo::Entero, true), definicion("Color", TipoAtributo::Texto, false)];
+        assert_eq!(contrato._staff_definir_esquema_atributos(vendedor, 2, esquema.clone()), Err(ErrorEsquemaAtributos::UsuarioNoStaff));
+        assert_eq!(contrato._staff_definir_esquema_atributos(owner, 2, vec![definicion("", TipoAtributo::Texto, false)]), Err(ErrorEsquemaAtributos::ClaveInvalida));
+        assert_eq!(contrato._staff_definir_esquema_atributos(owner, 2, vec![definicion("A", TipoAtributo::Texto, false), definicion("A", TipoAtributo::Entero, false)]), Err(ErrorEsquemaAtributos::ClaveDuplicada));
+        assert_eq!(contrato._staff_definir_esquema_atributos(owner, 2, esquema), Ok(()));
+
+        // las subcategorías heredan el esquema
+        let notebooks = contrato._staff_crear_categoria(owner, "Notebooks".into(), Some(2)).unwrap();
+        assert_eq!(contrato.esquema_efectivo(&notebooks).len(), 2);
+
+        assert_eq!(contrato._registrar_producto(0, vendedor, DatosProducto::new("A".into(), "B".into(), notebooks), 1), Err(ErrorRegistrarProducto::AtributoObligatorioFaltante));
+        assert_eq!(contrato._registrar_producto(0, vendedor, DatosProducto { atributos: vec![atributo("RAM", ValorAtributo::Texto("16".into()))], ..DatosProducto::new("A".into(), "B".into(), notebooks) }, 1), Err(ErrorRegistrarProducto::TipoDeAtributoInvalido));
+        assert_eq!(contrato._registrar_producto(0, vendedor, DatosProducto { atributos: vec![atributo("RAM", ValorAtributo::Entero(16)), atributo("RAM", ValorAtributo::Entero(8))], ..DatosProducto::new("A".into(), "B".into(), notebooks) }, 1), Err(ErrorRegistrarProducto::AtributosInvalidos));
+        assert!(contrato._registrar_producto(0, vendedor, DatosProducto { atributos: vec![atributo("RAM", ValorAtributo::Entero(16)), atributo("Peso", ValorAtributo::Entero(2))], ..DatosProducto::new("A".into(), "B".into(), notebooks) }, 1).is_ok());
+
+        // otras categorías no se ven afectadas
+        assert!(contrato._registrar_producto(0, vendedor, DatosProducto::new("A".into(), "B".into(), 1), 1).is_ok());
+    }
+
+    #[ink::test]
+    fn filtrar_productos_por_atributos() {
+        let mut contrato = RustaceoLibre::new(0);
+        let owner = contrato.owner;
+        let vendedor = AccountId::from([0x2; 32]);
+        contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+
+        let mut registrar = |categoria: u128, ram: i64| {
+            let id = contrato._registrar_producto(0, vendedor, DatosProducto { atributos: vec![atributo("RAM", ValorAtributo::Entero(ram))], ..DatosProducto::new("A".into(), "B".into(), categoria) }, 1).unwrap();
+            contrato._staff_aprobar_producto(owner, id).unwrap();
+            id
+        };
+        let chico = registrar(2, 8);
+        let grande = registrar(2, 32);
+        let otro = registrar(1, 64);
+
+        let ram_minima = vec![FiltroAtributo { clave: "RAM".into(), condicion: CondicionAtributo::MayorOIgual(16) }];
+        assert_eq!(contrato._ver_productos_filtrados(Some(2), ram_minima.clone()), Ok(vec![grande]));
+        assert_eq!(contrato._ver_productos_filtrados(None, ram_minima), Ok(vec![grande, otro]));
+
+        let ram_exacta = vec![FiltroAtributo { clave: "RAM".into(), condicion: CondicionAtributo::Igual(ValorAtributo::Entero(8)) }];
+        assert_eq!(contrato._ver_productos_filtrados(None, ram_exacta), Ok(vec![chico]));
+        assert_eq!(contrato._ver_productos_filtrados(Some(2), Vec::new()), Ok(vec![chico, grande]));
+        assert_eq!(contrato._ver_productos_filtrados(Some(99), Vec::new()), Err(ErrorFiltrarProductos::CategoriaInexistente));
+
+        // los productos pendientes de moderación no aparecen
+        let pendiente = contrato._registrar_producto(0, vendedor, DatosProducto::new("A".into(), "B".into(), 2), 1).unwrap();
+        assert!(!contrato._ver_productos_filtrados(None, Vec::new()).unwrap().contains(&pendiente));
+    }
+}
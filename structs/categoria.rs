@@ -0,0 +1,374 @@
+use ink::{prelude::{collections::BTreeMap, string::String, vec::Vec}, primitives::AccountId};
+
+use crate::rustaceo_libre::RustaceoLibre;
+
+/// Longitud máxima (en bytes) del nombre de una categoría
+pub const MAX_LARGO_NOMBRE_CATEGORIA: usize = 64;
+/// Profundidad máxima del árbol de categorías (una categoría raíz tiene profundidad 1)
+pub const MAX_PROFUNDIDAD_CATEGORIAS: u32 = 8;
+
+/// Categorías raíz creadas al instanciar el contrato. No pueden eliminarse ni moverse:
+/// la ID 0 (Ninguna) es la categoría por defecto.
+pub const CATEGORIAS_INICIALES: [(u128, &str); 5] = [
+    (0, "Ninguna"),
+    (1, "Hogar"),
+    (2, "Tecnologia"),
+    (3, "Indumentaria"),
+    (4, "Ferreteria"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub struct Categoria {
+    pub nombre: String,
+    pub padre: Option<u128>, // None si es una categoría raíz
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ErrorCategoria {
+    UsuarioNoStaff,
+    NombreInvalido,
+    CategoriaInexistente,
+    PadreInexistente,
+    PadreInvalido, // la categoría misma o una de sus subcategorías
+    ProfundidadMaximaAlcanzada,
+    TieneSubcategorias,
+    TieneProductos,
+    CategoriaPredefinida,
+    VerificacionRequerida, // quedarían publicaciones activas de vendedores sin el nivel de verificación exigido
+}
+
+impl RustaceoLibre {
+
+    /// Devuelve el árbol inicial de categorías, formado por las categorías raíz predefinidas
+    pub(crate) fn categorias_iniciales() -> BTreeMap<u128, Categoria> {
+        CATEGORIAS_INICIALES.iter()
+            .map(|(id, nombre)| (*id, Categoria { nombre: String::from(*nombre), padre: None }))
+            .collect()
+    }
+
+    /// Devuelve true si la categoría es una de las categorías raíz predefinidas
+    fn es_categoria_predefinida(id: u128) -> bool {
+        CATEGORIAS_INICIALES.iter().any(|(predefinida, _)| *predefinida == id)
+    }
+
+    /// Devuelve true si `categoria` es `ancestro` o alguna de sus subcategorías
+    pub fn pertenece_a_categoria(&self, categoria: &u128, ancestro: &u128) -> bool {
+        let mut actual = Some(*categoria);
+
+        // la profundidad del árbol está acotada
+        for _ in 0..MAX_PROFUNDIDAD_CATEGORIAS {
+            let Some(id) = actual
+            else { return false; };
+
+            if id == *ancestro {
+                return true;
+            }
+
+            actual = self.categorias.get(&id).and_then(|c| c.padre);
+        }
+
+        false
+    }
+
+    /// Devuelve la profundidad de una categoría: 1 para las categorías raíz
+    fn profundidad_categoria(&self, id: &u128) -> u32 {
+        let mut profundidad = 1;
+        let mut actual = self.categorias.get(id).and_then(|c| c.padre);
+
+        while let Some(padre) = actual {
+            if profundidad >= MAX_PROFUNDIDAD_CATEGORIAS {
+                break;
+            }
+
+            profundidad += 1;
+            actual = self.categorias.get(&padre).and_then(|c| c.padre);
+        }
+
+        profundidad
+    }
+
+    /// Devuelve la altura del subárbol que cuelga de una categoría: 1 si no tiene subcategorías
+    fn altura_categoria(&self, id: &u128) -> u32 {
+        let profundidad = self.profundidad_categoria(id);
+
+        self.categorias.keys()
+            .filter(|otra| self.pertenece_a_categoria(otra, id))
+            .map(|otra| self.profundidad_categoria(otra).saturating_sub(profundidad).saturating_add(1))
+            .max()
+            .unwrap_or(1)
+    }
+
+    //
+
+    /// Crea una categoría, como raíz o como subcategoría de `padre`.
+    ///
+    /// Devuelve la ID de la nueva categoría.
+    /// Devolverá error si el usuario no es parte del Staff, el nombre está vacío o excede su longitud máxima,
+    /// el padre no existe o se excedería la profundidad máxima del árbol.
+    pub fn _staff_crear_categoria(&mut self, caller: AccountId, nombre: String, padre: Option<u128>) -> Result<u128, ErrorCategoria> {
+        // validar staff
+        if !self.staff.contains(&caller) && caller != self.owner {
+            return Err(ErrorCategoria::UsuarioNoStaff);
+        }
+
+        // validar nombre
+        if nombre.is_empty() || nombre.len() > MAX_LARGO_NOMBRE_CATEGORIA {
+            return Err(ErrorCategoria::NombreInvalido);
+        }
+
+        // validar padre
+        if let Some(padre) = &padre {
+            if !self.categorias.contains_key(padre) {
+                return Err(ErrorCategoria::PadreInexistente);
+            }
+
+            if self.profundidad_categoria(padre) >= MAX_PROFUNDIDAD_CATEGORIAS {
+                return Err(ErrorCategoria::ProfundidadMaximaAlcanzada);
+            }
+        }
+
+        let id = self.next_id_categorias();
+        self.categorias.insert(id, Categoria { nombre, padre });
+
+        Ok(id)
+    }
+
+    //
+
+    /// Cambia el nombre de una categoría.
+    ///
+    /// Devolverá error si el usuario no es parte del Staff, el nombre está vacío o excede su longitud máxima
+    /// o la categoría no existe.
+    pub fn _staff_renombrar_categoria(&mut self, caller: AccountId, id: u128, nombre: String) -> Result<(), ErrorCategoria> {
+        // validar staff
+        if !self.staff.contains(&caller) && caller != self.owner {
+            return Err(ErrorCategoria::UsuarioNoStaff);
+        }
+
+        // validar nombre
+        if nombre.is_empty() || nombre.len() > MAX_LARGO_NOMBRE_CATEGORIA {
+            return Err(ErrorCategoria::NombreInvalido);
+        }
+
+        // validar categoría
+        let Some(mut categoria) = self.categorias.get(&id).cloned()
+        else { return Err(ErrorCategoria::CategoriaInexistente); };
+
+        categoria.nombre = nombre;
+        self.categorias.insert(id, categoria);
+
+        Ok(())
+    }
+
+    //
+
+    /// Mueve una categoría, junto con sus subcategorías, bajo otro padre o a la raíz del árbol.
+    ///
+    /// Devolverá error si el usuario no es parte del Staff, la categoría es predefinida, la categoría o el nuevo padre no existen,
+    /// el nuevo padre es la categoría misma o una de sus subcategorías, se excedería la profundidad máxima del árbol
+    /// o quedarían publicaciones con unidades ofertadas de vendedores sin el nivel de verificación exigido en su nueva ubicación.
+    pub fn _staff_mover_categoria(&mut self, caller: AccountId, id: u128, nuevo_padre: Option<u128>) -> Result<(), ErrorCategoria> {
+        // validar staff
+        if !self.staff.contains(&caller) && caller != self.owner {
+            return Err(ErrorCategoria::UsuarioNoStaff);
+        }
+
+        // las categorías predefinidas siempre son raíz
+        if Self::es_categoria_predefinida(id) {
+            return Err(ErrorCategoria::CategoriaPredefinida);
+        }
+
+        // validar categoría
+        let Some(mut categoria) = self.categorias.get(&id).cloned()
+        else { return Err(ErrorCategoria::CategoriaInexistente); };
+
+        // validar nuevo padre
+        if let Some(padre) = &nuevo_padre {
+            if !self.categorias.contains_key(padre) {
+                return Err(ErrorCategoria::PadreInexistente);
+            }
+
+            // evitar ciclos
+            if self.pertenece_a_categoria(padre, &id) {
+                return Err(ErrorCategoria::PadreInvalido);
+            }
+
+            if self.profundidad_categoria(padre).saturating_add(self.altura_categoria(&id)) > MAX_PROFUNDIDAD_CATEGORIAS {
+                return Err(ErrorCategoria::ProfundidadMaximaAlcanzada);
+            }
+        }
+
+        // las publicaciones activas del subárbol deben cumplir la verificación exigida en su nueva ubicación
+        let nivel_nuevo_padre = nuevo_padre.and_then(|padre| self.nivel_verificacion_exigido(&padre));
+        let incumplen = self.publicaciones.values()
+            .filter(|publicacion| publicacion.cantidad_ofertada > 0)
+            .filter_map(|publicacion| self.productos.get(&publicacion.producto).map(|producto| (publicacion.vendedor, producto.categoria)))
+            .filter(|(_, categoria_producto)| self.pertenece_a_categoria(categoria_producto, &id))
+            .any(|(vendedor, categoria_producto)| {
+                // lo exigido dentro del subárbol se conserva; lo exigido por sus ancestros pasa a ser lo del nuevo padre
+                let nivel_subarbol = self.verificacion_exigida_categorias.iter()
+                    .filter(|(ancestro, _)| self.pertenece_a_categoria(&categoria_producto, ancestro) && self.pertenece_a_categoria(ancestro, &id))
+                    .map(|(_, nivel)| *nivel)
+                    .max();
+                !self.cumple_verificacion(vendedor, nivel_subarbol.max(nivel_nuevo_padre))
+            });
+        if incumplen {
+            return Err(ErrorCategoria::VerificacionRequerida);
+        }
+
+        categoria.padre = nuevo_padre;
+        self.categorias.insert(id, categoria);
+
+        Ok(())
+    }
+
+    //
+
+    /// Elimina una categoría sin subcategorías ni productos, junto con su esquema de atributos.
+    ///
+    /// Devolverá error si el usuario no es parte del Staff, la categoría es predefinida, no existe
+    /// o tiene subcategorías o productos.
+    pub fn _staff_eliminar_categoria(&mut self, caller: AccountId, id: u128) -> Result<(), ErrorCategoria> {
+        // validar staff
+        if !self.staff.contains(&caller) && caller != self.owner {
+            return Err(ErrorCategoria::UsuarioNoStaff);
+        }
+
+        // las categorías predefinidas no se eliminan
+        if Self::es_categoria_predefinida(id) {
+            return Err(ErrorCategoria::CategoriaPredefinida);
+        }
+
+        // validar categoría
+        if !self.categorias.contains_key(&id) {
+            return Err(ErrorCategoria::CategoriaInexistente);
+        }
+
+        if self.categorias.values().any(|categoria| categoria.padre.as_ref() == Some(&id)) {
+            return Err(ErrorCategoria::TieneSubcategorias);
+        }
+
+        if self.productos.values().any(|producto| producto.categoria == id) {
+            return Err(ErrorCategoria::TieneProductos);
+        }
+
+        self.categorias.remove(&id);
+        self.categorias_seguimiento_obligatorio.retain(|categoria| *categoria != id);
+        self.verificacion_exigida_categorias.remove(&id);
+        self.esquemas_atributos.remove(&id);
+
+        Ok(())
+    }
+
+    //
+
+    /// Devuelve la categoría correspondiente a la ID, si existe.
+    pub fn _ver_categoria(&self, id: u128) -> Option<Categoria> {
+        self.categorias.get(&id).cloned()
+    }
+
+    /// Devuelve las subcategorías directas de una categoría, o las categorías raíz si no se indica ninguna.
+    pub fn _ver_subcategorias(&self, id: Option<u128>) -> Vec<u128> {
+        self.categorias.iter()
+            .filter(|(_, categoria)| categoria.padre == id)
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    /// Devuelve las IDs de los productos de una categoría o de alguna de sus subcategorías.
+    pub fn _ver_productos_categoria(&self, id: u128) -> Vec<u128> {
+        self.productos.iter()
+            .filter(|(_, producto)| self.pertenece_a_categoria(&producto.categoria, &id))
+            .map(|(id, _)| *id)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::{atributo::{DefinicionAtributo, TipoAtributo}, producto::DatosProducto, usuario::RolDeSeleccion};
+
+    #[ink::test]
+    fn arbol_de_categorias_y_subarboles() {
+        let mut contrato = RustaceoLibre::new(0);
+        let owner = contrato.owner;
+        let vendedor = AccountId::from([0x2; 32]);
+        contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+
+        // Tecnologia > Celulares > Android
+        let celulares = contrato._staff_crear_categoria(owner, "Celulares".into(), Some(2)).unwrap();
+        let android = contrato._staff_crear_categoria(owner, "Android".into(), Some(celulares)).unwrap();
+        assert_eq!(contrato._ver_subcategorias(Some(2)), vec![celulares]);
+        assert_eq!(contrato._ver_subcategorias(None).len(), CATEGORIAS_INICIALES.len());
+
+        assert!(contrato.pertenece_a_categoria(&android, &2));
+        assert!(contrato.pertenece_a_categoria(&android, &android));
+        assert!(!contrato.pertenece_a_categoria(&celulares, &android));
+        assert!(!contrato.pertenece_a_categoria(&android, &1));
+
+        let id_producto = contrato._registrar_producto(0, vendedor, DatosProducto::new("Celular".into(), "Android".into(), android), 1).unwrap();
+        assert_eq!(contrato._ver_productos_categoria(2), vec![id_producto]);
+        assert_eq!(contrato._ver_productos_categoria(1), Vec::<u128>::new());
+
+        // mover Celulares a Hogar
+        assert_eq!(contrato._staff_mover_categoria(owner, celulares, Some(1)), Ok(()));
+        assert_eq!(contrato._ver_productos_categoria(1), vec![id_producto]);
+        assert_eq!(contrato._ver_productos_categoria(2), Vec::<u128>::new());
+    }
+
+    #[ink::test]
+    fn categorias_validaciones() {
+        let mut contrato = RustaceoLibre::new(0);
+        let owner = contrato.owner;
+        let vendedor = AccountId::from([0x2; 32]);
+        contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+
+        assert_eq!(contrato._staff_crear_categoria(vendedor, "X".into(), None), Err(ErrorCategoria::UsuarioNoStaff));
+        assert_eq!(contrato._staff_crear_categoria(owner, String::new(), None), Err(ErrorCategoria::NombreInvalido));
+        assert_eq!(contrato._staff_crear_categoria(owner, "X".into(), Some(99)), Err(ErrorCategoria::PadreInexistente));
+
+        let padre = contrato._staff_crear_categoria(owner, "Padre".into(), None).unwrap();
+        let hija = contrato._staff_crear_categoria(owner, "Hija".into(), Some(padre)).unwrap();
+
+        // ciclos
+        assert_eq!(contrato._staff_mover_categoria(owner, padre, Some(hija)), Err(ErrorCategoria::PadreInvalido));
+        assert_eq!(contrato._staff_mover_categoria(owner, padre, Some(padre)), Err(ErrorCategoria::PadreInvalido));
+
+        // profundidad máxima
+        let mut ultima = hija;
+        for _ in 2..MAX_PROFUNDIDAD_CATEGORIAS {
+            ultima = contrato._staff_crear_categoria(owner, "Nivel".into(), Some(ultima)).unwrap();
+        }
+        assert_eq!(contrato._staff_crear_categoria(owner, "Nivel".into(), Some(ultima)), Err(ErrorCategoria::ProfundidadMaximaAlcanzada));
+        assert_eq!(contrato._staff_mover_categoria(owner, padre, Some(1)), Err(ErrorCategoria::ProfundidadMaximaAlcanzada));
+
+        // eliminar
+        assert_eq!(contrato._staff_eliminar_categoria(owner, padre), Err(ErrorCategoria::TieneSubcategorias));
+        contrato._registrar_producto(0, vendedor, DatosProducto::new("A".into(), "B".into(), ultima), 1).unwrap();
+        assert_eq!(contrato._staff_eliminar_categoria(owner, ultima), Err(ErrorCategoria::TieneProductos));
+        let vacia = contrato._staff_crear_categoria(owner, "Vacia".into(), None).unwrap();
+        let esquema = vec![DefinicionAtributo { clave: "Color".into(), tipo: TipoAtributo::Texto, obligatorio: false }];
+        contrato._staff_definir_esquema_atributos(owner, vacia, esquema).unwrap();
+        assert_eq!(contrato._staff_eliminar_categoria(owner, vacia), Ok(()));
+        assert_eq!(contrato._ver_categoria(vacia), None);
+        assert!(!contrato.esquemas_atributos.contains_key(&vacia));
+        assert_eq!(contrato._registrar_producto(0, vendedor, DatosProducto::new("A".into(), "B".into(), vacia), 1), Err(crate::structs::producto::ErrorRegistrarProducto::CategoriaInexistente));
+
+        // las categorías predefinidas (incluida la categoría por defecto) no se eliminan ni se mueven
+        assert_eq!(contrato._staff_eliminar_categoria(owner, 0), Err(ErrorCategoria::CategoriaPredefinida));
+        assert_eq!(contrato._staff_eliminar_categoria(owner, 3), Err(ErrorCategoria::CategoriaPredefinida));
+        assert_eq!(contrato._staff_mover_categoria(owner, 1, Some(2)), Err(ErrorCategoria::CategoriaPredefinida));
+        assert_eq!(contrato._ver_subcategorias(None).len(), CATEGORIAS_INICIALES.len() + 1);
+    }
+}
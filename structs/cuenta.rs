@@ -0,0 +1,276 @@
+use ink::{prelude::vec::Vec, primitives::AccountId};
+
+use crate::{rustaceo_libre::RustaceoLibre, structs::{movimiento_stock::TipoMovimientoStock, pedido::EstadoPedido, perfil::clave_nombre_visible, transferencia_stock::EstadoTransferenciaStock, usuario::{Rol, Usuario}}};
+
+/// Registro de una cuenta eliminada. Conserva sus calificaciones para restaurarlas si la cuenta vuelve a registrarse,
+/// de modo que eliminarla no sirva para reiniciar la reputación.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub struct CuentaEliminada {
+    pub timestamp: u64,
+    pub total_calificaciones_comprador: u64,
+    pub cant_calificaciones_comprador: u32,
+    pub total_calificaciones_vendedor: u64,
+    pub cant_calificaciones_vendedor: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ErrorEliminarCuenta {
+    UsuarioNoRegistrado,
+    PedidosPendientes,       // pendientes o despachados, como comprador o vendedor
+    FondosEnCustodia,        // pedidos cuyos fondos aún no se liquidaron
+    DisputasEnCurso,
+    TransferenciasPendientes,
+    TiendaCompartida,        // debe disolverse antes con una propuesta aprobada por los socios
+}
+
+impl RustaceoLibre {
+
+    /// Elimina la cuenta del usuario.
+    ///
+    /// Las publicaciones del vendedor quedan sin unidades ofertadas y se dejan de seguir: se quitan de las favoritas
+    /// de los compradores, junto con sus umbrales y reposiciones automáticas. Las unidades vuelven al stock
+    /// del vendedor y se registran como retiradas. También se quitan las favoritas del comprador, el vendedor
+    /// de las listas de seguidos, el perfil, el plazo de devolución, la migración de cuenta en curso,
+    /// los operadores autorizados y la verificación, que debe volver a solicitarse. La membresía en el Staff
+    /// no depende de la cuenta de usuario y se conserva: sólo el owner puede quitarla.
+    /// Los pedidos, sus mensajes y el registro de movimientos de stock se conservan, por lo que las contrapartes
+    /// siguen viendo su historial. Las calificaciones se archivan y se restauran si la cuenta vuelve a registrarse.
+    ///
+    /// Devolverá error si el usuario no está registrado o tiene pedidos pendientes o despachados,
+    /// fondos en custodia, disputas en curso o transferencias de stock ofrecidas, o si es una tienda compartida
+    /// que todavía no se disolvió.
+    pub fn _eliminar_cuenta(&mut self, timestamp: u64, caller: AccountId) -> Result<(), ErrorEliminarCuenta> {
+        // validar usuario
+        let Some(usuario) = self.usuarios.get(&caller).cloned()
+        else { return Err(ErrorEliminarCuenta::UsuarioNoRegistrado); };
+
+        if self.tiendas_compartidas.contains_key(&caller) {
+            return Err(ErrorEliminarCuenta::TiendaCompartida);
+        }
+
+        // validar obligaciones: pedidos como comprador y como vendedor, incluidos los del rol archivado
+        let ids_pedidos = usuario.obtener_compras().unwrap_or_default().into_iter()
+            .chain(usuario.obtener_ventas().unwrap_or_default())
+            .chain(self.datos_comprador_archivados.get(&caller).map(|data| data.compras.clone()).unwrap_or_default())
+            .chain(self.datos_vendedor_archivados.get(&caller).map(|data| data.ventas.clone()).unwrap_or_default());
+        for pedido in ids_pedidos.filter_map(|id_pedido| self.pedidos.get(&id_pedido)) {
+            if matches!(pedido.estado, EstadoPedido::Pendiente(_) | EstadoPedido::Despachado(_)) {
+                return Err(ErrorEliminarCuenta::PedidosPendientes);
+            }
+
+            if pedido.disputa.is_some_and(|id_disputa| self.disputas_en_curso.contains_key(&id_disputa)) {
+                return Err(ErrorEliminarCuenta::DisputasEnCurso);
+            }
+
+            if !pedido.fondos_fueron_transferidos && pedido.fondos_en_custodia() > 0 {
+                return Err(ErrorEliminarCuenta::FondosEnCustodia);
+            }
+        }
+
+        let transferencias_pendientes = self.transferencias_stock.values()
+            .any(|t| (t.emisor == caller || t.receptor == caller) && matches!(t.estado, EstadoTransferenciaStock::Ofrecida(_)));
+        if transferencias_pendientes {
+            return Err(ErrorEliminarCuenta::TransferenciasPendientes);
+        }
+
+        // cerrar publicaciones y retirar el stock
+        if let Some(mut stock) = usuario.obtener_stock_productos() {
+            let publicaciones = usuario.obtener_publicaciones().unwrap_or_default();
+            for &id_publicacion in publicaciones.iter() {
+                let Some(mut publicacion) = self.publicaciones.get(&id_publicacion).cloned()
+                else { continue; };
+
+                let cantidad = publicacion.cantidad_ofertada;
+                let id_producto = publicacion.producto;
+                publicacion.cantidad_ofertada = 0;
+                self.publicaciones.insert(id_publicacion, publicacion);
+                self.registrar_movimiento_stock(timestamp, caller, id_producto, TipoMovimientoStock::DesdePublicacion(id_publicacion), cantidad);
+                stock.insert(id_producto, stock.get(&id_producto).unwrap_or(0).saturating_add(cantidad));
+
+                self.umbrales_stock_publicaciones.remove(&id_publicacion);
+                self.reposiciones_automaticas.remove(&id_publicacion);
+                self.interesados_publicaciones.remove(&id_publicacion);
+            }
+            self.quitar_publicaciones_de_favoritas(&publicaciones);
+
+            for (id_producto, cantidad) in stock.productos.iter().zip(stock.stock.iter()) {
+                self.registrar_movimiento_stock(timestamp, caller, *id_producto, TipoMovimientoStock::Retiro, *cantidad);
+                self.umbrales_stock_productos.remove(&(caller, *id_producto));
+            }
+
+            // dejar de ser seguido
+            let seguidores: Vec<AccountId> = self.usuarios.iter()
+                .filter(|(_, u)| u.obtener_vendedores_seguidos().is_some_and(|seguidos| seguidos.contains(&caller)))
+                .map(|(id, _)| *id)
+                .collect();
+
+            for id_seguidor in seguidores {
+                let Some(mut seguidor) = self.usuarios.get(&id_seguidor).cloned()
+                else { continue; };
+                let Some(mut data_comprador) = seguidor.obtener_data_comprador()
+                else { continue; };
+
+                data_comprador.vendedores_seguidos.retain(|id| *id != caller);
+                seguidor.establecer_data_comprador(data_comprador);
+                self.usuarios.insert(id_seguidor, seguidor);
+            }
+        }
+
+        // quitar al comprador de los interesados de sus favoritas
+        for id_publicacion in usuario.obtener_publicaciones_favoritas().unwrap_or_default() {
+            self.restar_interesado_publicacion(id_publicacion);
+        }
+
+        // perfil, plazo de devolución, migración en curso, operadores y verificación
+        if let Some(perfil) = self.perfiles.remove(&caller) {
+            self.nombres_visibles.remove(&clave_nombre_visible(&perfil.nombre_visible));
+        }
+        self.plazos_devolucion.remove(&caller);
+        self.migraciones_pendientes.remove(&caller);
+        self.verificaciones.remove(&caller);
+        self.solicitudes_verificacion.remove(&caller);
+        self.operadores.retain(|_, operador| operador.vendedor != caller);
+
+        // archivar calificaciones, incluidas las de un rol que haya dejado o que ya no tenga
+        let archivado_comprador = self.datos_comprador_archivados.remove(&caller);
+        let archivado_vendedor = self.datos_vendedor_archivados.remove(&caller);
+        let data_comprador = usuario.obtener_data_comprador().or(archivado_comprador);
+        let data_vendedor = usuario.obtener_data_vendedor().or(archivado_vendedor);
+
+        let mut archivo = self.cuentas_eliminadas.get(&caller).cloned().unwrap_or_default();
+        archivo.timestamp = timestamp;
+        if let Some(data_comprador) = data_comprador {
+            archivo.total_calificaciones_comprador = data_comprador.total_calificaciones;
+            archivo.cant_calificaciones_comprador = data_comprador.cant_calificaciones;
+        }
+        if let Some(data_vendedor) = data_vendedor {
+            archivo.total_calificaciones_vendedor = data_vendedor.total_calificaciones;
+            archivo.cant_calificaciones_vendedor = data_vendedor.cant_calificaciones;
+        }
+
+        self.cuentas_eliminadas.insert(caller, archivo);
+        self.usuarios.remove(&caller);
+
+        Ok(())
+    }
+
+    /// Quita las publicaciones indicadas de las favoritas de todos los compradores
+    fn quitar_publicaciones_de_favoritas(&mut self, ids_publicaciones: &[u128]) {
+        let compradores: Vec<AccountId> = self.usuarios.iter()
+            .filter(|(_, u)| u.obtener_publicaciones_favoritas().is_some_and(|favoritas| favoritas.iter().any(|id| ids_publicaciones.contains(id))))
+            .map(|(id, _)| *id)
+            .collect();
+
+        for id_comprador in compradores {
+            let Some(mut comprador) = self.usuarios.get(&id_comprador).cloned()
+            else { continue; };
+            let Some(mut data_comprador) = comprador.obtener_data_comprador()
+            else { continue; };
+
+            data_comprador.publicaciones_favoritas.retain(|id| !ids_publicaciones.contains(id));
+            comprador.establecer_data_comprador(data_comprador);
+            self.usuarios.insert(id_comprador, comprador);
+        }
+    }
+
+    /// Si la cuenta fue eliminada anteriormente, restaura en el usuario las calificaciones archivadas
+    /// de los roles indicados.
+    pub(crate) fn restaurar_calificaciones(&self, usuario: &mut Usuario, comprador: bool, vendedor: bool) {
+        let Some(archivo) = self.cuentas_eliminadas.get(&usuario.id)
+        else { return; };
+
+        let (data_comprador, data_vendedor) = match &mut usuario.rol {
+            Rol::Comprador(data_comprador) => (Some(data_comprador), None),
+            Rol::Vendedor(data_vendedor) => (None, Some(data_vendedor)),
+            Rol::Ambos(data_comprador, data_vendedor) => (Some(data_comprador), Some(data_vendedor)),
+        };
+
+        if let Some(data_comprador) = data_comprador.filter(|_| comprador) {
+            data_comprador.total_calificaciones = archivo.total_calificaciones_comprador;
+            data_comprador.cant_calificaciones = archivo.cant_calificaciones_comprador;
+        }
+
+        if let Some(data_vendedor) = data_vendedor.filter(|_| vendedor) {
+            data_vendedor.total_calificaciones = archivo.total_calificaciones_vendedor;
+            data_vendedor.cant_calificaciones = archivo.cant_calificaciones_vendedor;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::{producto::DatosProducto, usuario::RolDeSeleccion};
+
+    #[ink::test]
+    fn eliminar_cuenta_con_obligaciones_falla() {
+        let mut contrato = RustaceoLibre::new(0);
+        let vendedor = AccountId::from([0x1; 32]);
+        let comprador = AccountId::from([0x2; 32]);
+        contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+        contrato._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
+
+        let id_producto = contrato._registrar_producto(0, vendedor, DatosProducto::new("A".into(), "B".into(), 1), 10).unwrap();
+        contrato._staff_aprobar_producto(contrato.owner, id_producto).unwrap();
+        let id_publicacion = contrato._realizar_publicacion(0, vendedor, id_producto, 5, 100).unwrap();
+        contrato._comprar_producto(0, comprador, id_publicacion, 1, 100).unwrap();
+
+        assert_eq!(contrato._eliminar_cuenta(0, AccountId::from([0x9; 32])), Err(ErrorEliminarCuenta::UsuarioNoRegistrado));
+        assert_eq!(contrato._eliminar_cuenta(0, vendedor), Err(ErrorEliminarCuenta::PedidosPendientes));
+        assert_eq!(contrato._eliminar_cuenta(0, comprador), Err(ErrorEliminarCuenta::PedidosPendientes));
+        assert!(contrato.usuarios.contains_key(&vendedor));
+    }
+
+    #[ink::test]
+    fn eliminar_cuenta_cierra_publicaciones_y_limpia_listas() {
+        let mut contrato = RustaceoLibre::new(0);
+        let vendedor = AccountId::from([0x1; 32]);
+        let comprador = AccountId::from([0x2; 32]);
+        contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+        contrato._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
+        contrato._editar_perfil(vendedor, "Vendedor".into(), "".into(), None, None, None).unwrap();
+
+        let id_producto = contrato._registrar_producto(0, vendedor, DatosProducto::new("A".into(), "B".into(), 1), 10).unwrap();
+        contrato._staff_aprobar_producto(contrato.owner, id_producto).unwrap();
+        let id_publicacion = contrato._realizar_publicacion(0, vendedor, id_producto, 5, 100).unwrap();
+        contrato._agregar_publicacion_favorita(comprador, id_publicacion).unwrap();
+        contrato._seguir_vendedor(comprador, vendedor).unwrap();
+
+        // la calificación del vendedor se archiva
+        let mut usuario = contrato.usuarios.get(&vendedor).cloned().unwrap();
+        usuario.calificar_como_vendedor(2);
+        contrato.usuarios.insert(vendedor, usuario);
+
+        assert_eq!(contrato._eliminar_cuenta(10, vendedor), Ok(()));
+        assert!(!contrato.usuarios.contains_key(&vendedor));
+        assert_eq!(contrato.publicaciones.get(&id_publicacion).unwrap().cantidad_ofertada, 0);
+        assert_eq!(contrato._ver_publicaciones_favoritas(comprador), Ok(Vec::new()));
+        assert_eq!(contrato._ver_vendedores_seguidos(comprador), Ok(Vec::new()));
+        assert!(!contrato.interesados_publicaciones.contains_key(&id_publicacion));
+        assert_eq!(contrato._buscar_usuario_por_nombre("Vendedor".into()), None);
+
+        let movimientos = contrato.movimientos_stock.get(&(vendedor, id_producto)).unwrap();
+        assert_eq!(movimientos.last().map(|m| (m.tipo.clone(), m.cantidad)), Some((TipoMovimientoStock::Retiro, 10)));
+
+        // volver a registrarse no reinicia las calificaciones
+        contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+        let data_vendedor = contrato.usuarios.get(&vendedor).unwrap().obtener_data_vendedor().unwrap();
+        assert_eq!((data_vendedor.total_calificaciones, data_vendedor.cant_calificaciones), (2, 1));
+
+        // el comprador también puede irse. si es Staff, lo sigue siendo
+        contrato.staff.push(comprador);
+        assert_eq!(contrato._eliminar_cuenta(11, comprador), Ok(()));
+        assert!(contrato.cuentas_eliminadas.contains_key(&comprador));
+        assert!(contrato.staff.contains(&comprador));
+    }
+}
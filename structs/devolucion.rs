@@ -0,0 +1,599 @@
+use ink::{prelude::string::String, primitives::AccountId};
+
+use crate::{rustaceo_libre::RustaceoLibre, structs::{movimiento_stock::TipoMovimientoStock, pedido::{EstadoPedido, RegistroSeguimiento}}};
+
+/// Largo máximo del motivo de una devolución
+pub const MAX_LARGO_MOTIVO_DEVOLUCION: usize = 256;
+
+/// Plazo de devolución máximo que puede establecer un vendedor, en días
+pub const MAX_DIAS_PLAZO_DEVOLUCION: u32 = 90;
+
+/// Tiempo (en milisegundos) que tiene el comprador para despachar una devolución aprobada: 7 días.
+/// Vencido, el vendedor puede cerrar la devolución y retirar los fondos del pedido.
+pub const PLAZO_DESPACHO_DEVOLUCION: u64 = 604_800_000; // 1000*60*60*24*7
+
+/// Tiempo (en milisegundos) que tiene el vendedor para confirmar la recepción de una devolución despachada: 30 días.
+/// Vencido, el comprador puede cerrar la devolución y llevar el pedido a disputa.
+pub const PLAZO_RECEPCION_DEVOLUCION: u64 = 2_592_000_000; // 1000*60*60*24*30
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum EstadoDevolucion {
+    Solicitada(u64),
+    Aprobada(u64),
+    Rechazada(u64),
+    Despachada(u64),
+    Recibida(u64),
+    Vencida(u64), // cerrada por la contraparte al vencer el plazo de despacho o, con disputa, el de recepción
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub struct Devolucion {
+    pub pedido: u128,
+    pub timestamp: u64,
+    pub cantidad: u32,
+    pub motivo: String,
+    pub estado: EstadoDevolucion,
+    pub seguimiento: Option<RegistroSeguimiento>,
+    pub reembolso: u128,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ErrorDevolucion {
+    UsuarioNoRegistrado,
+    NoEsVendedor,
+    PlazoInvalido,
+    PedidoInexistente,
+    SoloCompradorPuede,
+    SoloVendedorPuede,
+    PedidoNoRecibido,
+    PlazoDeDevolucionExpirado,
+    FondosYaTransferidos,
+    DisputaEnCurso,
+    DevolucionExistente,
+    DevolucionInexistente,
+    EstadoInvalido,
+    CantidadInvalida,
+    MotivoInvalido,
+    SeguimientoInvalido,
+    PlazoDeDespachoVencido,
+    PlazoNoVencido,
+}
+
+impl Devolucion {
+    /// Devuelve true si la devolución todavía no fue rechazada ni concluida.
+    /// Mientras esté en curso, los fondos del pedido no pueden retirarse.
+    pub fn en_curso(&self) -> bool {
+        matches!(self.estado, EstadoDevolucion::Solicitada(_) | EstadoDevolucion::Aprobada(_) | EstadoDevolucion::Despachada(_))
+    }
+}
+
+impl RustaceoLibre {
+
+    //
+
+    /// Establece la cantidad de días posteriores a la recepción de un pedido
+    /// en los que el comprador puede solicitar su devolución. Un plazo de 0 días no admite devoluciones.
+    /// Mientras no venza el plazo, el vendedor no puede retirar los fondos de sus pedidos recibidos.
+    ///
+    /// Devolverá error si el usuario no está registrado, no es vendedor o el plazo supera el máximo permitido.
+    pub fn _establecer_plazo_devolucion(&mut self, caller: AccountId, dias: u32) -> Result<(), ErrorDevolucion> {
+        // validar usuario
+        let Some(usuario) = self.usuarios.get(&caller)
+        else { return Err(ErrorDevolucion::UsuarioNoRegistrado); };
+
+        if !usuario.es_vendedor() {
+            return Err(ErrorDevolucion::NoEsVendedor);
+        }
+
+        // validar plazo
+        if dias > MAX_DIAS_PLAZO_DEVOLUCION {
+            return Err(ErrorDevolucion::PlazoInvalido);
+        }
+
+        self.plazos_devolucion.insert(caller, dias);
+        Ok(())
+    }
+
+    //
+
+    /// El comprador solicita la devolución de unidades ya recibidas de un pedido, indicando el motivo.
+    /// Sólo puede hacerlo dentro del plazo de devolución establecido por el vendedor
+    /// y mientras los fondos del pedido sigan en custodia del contrato.
+    ///
+    /// Devolverá error si el usuario no está registrado, el pedido no existe, no es el comprador,
+    /// el pedido no fue recibido, el plazo expiró, los fondos ya fueron transferidos,
+    /// hay una disputa en curso, el pedido ya tiene una devolución, la cantidad o el motivo no son válidos.
+    pub fn _solicitar_devolucion(&mut self, timestamp: u64, caller: AccountId, id_pedido: u128, cantidad: u32, motivo: String) -> Result<(), ErrorDevolucion> {
+        // validar usuario
+        if !self.usuarios.contains_key(&caller) {
+            return Err(ErrorDevolucion::UsuarioNoRegistrado);
+        }
+
+        // validar pedido
+        let Some(pedido) = self.pedidos.get(&id_pedido)
+        else { return Err(ErrorDevolucion::PedidoInexistente); };
+
+        // validar caller == comprador
+        if pedido.comprador != caller {
+            return Err(ErrorDevolucion::SoloCompradorPuede);
+        }
+
+        // validar pedido recibido
+        let EstadoPedido::Recibido(timestamp_recibido) = pedido.estado
+        else { return Err(ErrorDevolucion::PedidoNoRecibido); };
+
+        // validar plazo de devolución del vendedor
+        let plazo = self.plazo_devolucion(pedido.vendedor);
+        if timestamp.saturating_sub(timestamp_recibido) > plazo || plazo == 0 {
+            return Err(ErrorDevolucion::PlazoDeDevolucionExpirado);
+        }
+
+        // validar fondos en custodia
+        if pedido.fondos_fueron_transferidos || pedido.fondos_en_custodia() == 0 {
+            return Err(ErrorDevolucion::FondosYaTransferidos);
+        }
+
+        // validar que no exista disputa en curso
+        if let Some(id_disputa) = pedido.disputa {
+            if self.disputas_en_curso.contains_key(&id_disputa) {
+                return Err(ErrorDevolucion::DisputaEnCurso);
+            }
+        }
+
+        // validar devolución existente
+        if self.devoluciones.contains_key(&id_pedido) {
+            return Err(ErrorDevolucion::DevolucionExistente);
+        }
+
+        // validar cantidad
+        if cantidad == 0 || cantidad > pedido.cantidad_recibida {
+            return Err(ErrorDevolucion::CantidadInvalida);
+        }
+
+        // validar motivo
+        if motivo.is_empty() || motivo.len() > MAX_LARGO_MOTIVO_DEVOLUCION {
+            return Err(ErrorDevolucion::MotivoInvalido);
+        }
+
+        // crear y guardar devolución
+        let devolucion = Devolucion {
+            pedido: id_pedido,
+            timestamp,
+            cantidad,
+            motivo,
+            estado: EstadoDevolucion::Solicitada(timestamp),
+            seguimiento: None,
+            reembolso: 0,
+        };
+        self.devoluciones.insert(id_pedido, devolucion);
+
+        Ok(())
+    }
+
+    //
+
+    /// El vendedor aprueba o rechaza la solicitud de devolución de un pedido.
+    /// Una devolución rechazada libera los fondos del pedido, que podrá disputarse normalmente.
+    ///
+    /// Devolverá error si el usuario no está registrado, el pedido o la devolución no existen,
+    /// no es el vendedor o la devolución no está pendiente de respuesta.
+    pub fn _responder_devolucion(&mut self, timestamp: u64, caller: AccountId, id_pedido: u128, aprobar: bool) -> Result<(), ErrorDevolucion> {
+        // validar usuario
+        if !self.usuarios.contains_key(&caller) {
+            return Err(ErrorDevolucion::UsuarioNoRegistrado);
+        }
+
+        // validar pedido
+        let Some(pedido) = self.pedidos.get(&id_pedido)
+        else { return Err(ErrorDevolucion::PedidoInexistente); };
+
+        // validar caller == vendedor
+        if pedido.vendedor != caller {
+            return Err(ErrorDevolucion::SoloVendedorPuede);
+        }
+
+        // validar devolución
+        let Some(mut devolucion) = self.devoluciones.get(&id_pedido).cloned()
+        else { return Err(ErrorDevolucion::DevolucionInexistente); };
+
+        if !matches!(devolucion.estado, EstadoDevolucion::Solicitada(_)) {
+            return Err(ErrorDevolucion::EstadoInvalido);
+        }
+
+        // guardar
+        devolucion.estado = if aprobar { EstadoDevolucion::Aprobada(timestamp) } else { EstadoDevolucion::Rechazada(timestamp) };
+        self.devoluciones.insert(id_pedido, devolucion);
+
+        Ok(())
+    }
+
+    //
+
+    /// El comprador informa el transportista y el código de seguimiento del envío de una devolución aprobada.
+    /// Debe hacerlo dentro del plazo de despacho de devoluciones.
+    ///
+    /// Devolverá error si el usuario no está registrado, el pedido o la devolución no existen,
+    /// no es el comprador, la devolución no fue aprobada, venció el plazo de despacho
+    /// o la información de seguimiento no es válida.
+    pub fn _devolucion_despachada(&mut self, timestamp: u64, caller: AccountId, id_pedido: u128, transportista: String, codigo_seguimiento: String) -> Result<(), ErrorDevolucion> {
+        // validar usuario
+        if !self.usuarios.contains_key(&caller) {
+            return Err(ErrorDevolucion::UsuarioNoRegistrado);
+        }
+
+        // validar pedido
+        let Some(pedido) = self.pedidos.get(&id_pedido)
+        else { return Err(ErrorDevolucion::PedidoInexistente); };
+
+        // validar caller == comprador
+        if pedido.comprador != caller {
+            return Err(ErrorDevolucion::SoloCompradorPuede);
+        }
+
+        // validar devolución
+        let Some(mut devolucion) = self.devoluciones.get(&id_pedido).cloned()
+        else { return Err(ErrorDevolucion::DevolucionInexistente); };
+
+        let EstadoDevolucion::Aprobada(timestamp_aprobada) = devolucion.estado
+        else { return Err(ErrorDevolucion::EstadoInvalido); };
+
+        // validar plazo de despacho
+        if timestamp >= timestamp_aprobada.saturating_add(PLAZO_DESPACHO_DEVOLUCION) {
+            return Err(ErrorDevolucion::PlazoDeDespachoVencido);
+        }
+
+        // validar seguimiento
+        let registro = RegistroSeguimiento::new(timestamp, transportista, codigo_seguimiento);
+        if !registro.es_valido() {
+            return Err(ErrorDevolucion::SeguimientoInvalido);
+        }
+
+        // guardar
+        devolucion.seguimiento = Some(registro);
+        devolucion.estado = EstadoDevolucion::Despachada(timestamp);
+        self.devoluciones.insert(id_pedido, devolucion);
+
+        Ok(())
+    }
+
+    //
+
+    /// El vendedor confirma la recepción de las unidades devueltas.
+    /// Las unidades vuelven a su stock y se reembolsa al comprador el valor correspondiente,
+    /// descontado de los fondos del pedido en custodia.
+    ///
+    /// Devolverá la ID del comprador y el valor a reembolsar. La transferencia se realiza en lib.rs.
+    /// Devolverá error si el usuario no está registrado, el pedido o la devolución no existen,
+    /// no es el vendedor o la devolución no fue despachada.
+    pub fn _devolucion_recibida(&mut self, timestamp: u64, caller: AccountId, id_pedido: u128) -> Result<(AccountId, u128), ErrorDevolucion> {
+        // validar usuario
+        let Some(mut vendedor) = self.usuarios.get(&caller).cloned()
+        else { return Err(ErrorDevolucion::UsuarioNoRegistrado); };
+
+        // validar pedido
+        let Some(pedido) = self.pedidos.get(&id_pedido)
+        else { return Err(ErrorDevolucion::PedidoInexistente); };
+
+        // validar caller == vendedor
+        if pedido.vendedor != caller {
+            return Err(ErrorDevolucion::SoloVendedorPuede);
+        }
+
+        // validar devolución
+        let Some(mut devolucion) = self.devoluciones.get(&id_pedido).cloned()
+        else { return Err(ErrorDevolucion::DevolucionInexistente); };
+
+        if !matches!(devolucion.estado, EstadoDevolucion::Despachada(_)) {
+            return Err(ErrorDevolucion::EstadoInvalido);
+        }
+
+        // todo bien
+        let mut pedido = pedido.clone();
+        let reembolso = pedido.valor_unitario()
+            .saturating_mul(u128::from(devolucion.cantidad)) // safe cast: u32 -> u128
+            .min(pedido.fondos_en_custodia());
+
+        // devolver unidades al stock del vendedor
+        if let Some(id_producto) = self.publicaciones.get(&pedido.publicacion).map(|publicacion| publicacion.producto) {
+            let stock_actual = vendedor.obtener_stock_producto(&id_producto).unwrap_or(0);
+            let nuevo_stock = stock_actual.saturating_add(devolucion.cantidad);
+            if vendedor.establecer_stock_producto(&id_producto, &nuevo_stock) {
+                self.usuarios.insert(vendedor.id, vendedor);
+                self.registrar_movimiento_stock(timestamp, caller, id_producto, TipoMovimientoStock::Devolucion(id_pedido), devolucion.cantidad);
+            }
+        }
+
+        // actualizar pedido
+        pedido.fondos_reembolsados = pedido.fondos_reembolsados.saturating_add(reembolso);
+        if pedido.fondos_en_custodia() == 0 {
+            pedido.fondos_fueron_transferidos = true;
+        }
+        let id_comprador = pedido.comprador;
+        self.pedidos.insert(pedido.id, pedido);
+
+        // actualizar devolución
+        devolucion.reembolso = reembolso;
+        devolucion.estado = EstadoDevolucion::Recibida(timestamp);
+        self.devoluciones.insert(id_pedido, devolucion);
+
+        // fin. se devolverán fondos en lib.rs
+        Ok((id_comprador, reembolso))
+    }
+
+    //
+
+    /// Cierra una devolución cuyo plazo venció sin que la contraparte actuara:
+    /// 
+    /// - Si el comprador no despachó una devolución aprobada dentro del plazo de despacho,
+    ///   el vendedor puede cerrarla y los fondos del pedido vuelven a poder retirarse.
+    /// - Si el vendedor no confirmó la recepción de una devolución despachada dentro del plazo de recepción,
+    ///   el comprador puede cerrarla y el pedido pasa a disputa: el despacho sólo lo informa el comprador,
+    ///   por lo que el Staff decide a quién corresponden los fondos en custodia.
+    ///
+    /// Devolverá error si el usuario no está registrado, el pedido o la devolución no existen,
+    /// el caller no es la contraparte que puede cerrarla, la devolución no está aprobada ni despachada
+    /// o el plazo aún no venció.
+    pub fn _cerrar_devolucion_vencida(&mut self, timestamp: u64, caller: AccountId, id_pedido: u128) -> Result<(), ErrorDevolucion> {
+        // validar usuario
+        if !self.usuarios.contains_key(&caller) {
+            return Err(ErrorDevolucion::UsuarioNoRegistrado);
+        }
+
+        // validar pedido
+        let Some(pedido) = self.pedidos.get(&id_pedido)
+        else { return Err(ErrorDevolucion::PedidoInexistente); };
+
+        // validar devolución
+        let Some(mut devolucion) = self.devoluciones.get(&id_pedido).cloned()
+        else { return Err(ErrorDevolucion::DevolucionInexistente); };
+
+        match devolucion.estado {
+            // el comprador no despachó: cierra el vendedor
+            EstadoDevolucion::Aprobada(timestamp_aprobada) => {
+                if pedido.vendedor != caller {
+                    return Err(ErrorDevolucion::SoloVendedorPuede);
+                }
+
+                if timestamp < timestamp_aprobada.saturating_add(PLAZO_DESPACHO_DEVOLUCION) {
+                    return Err(ErrorDevolucion::PlazoNoVencido);
+                }
+
+                devolucion.estado = EstadoDevolucion::Vencida(timestamp);
+                self.devoluciones.insert(id_pedido, devolucion);
+
+                Ok(())
+            },
+            // el vendedor no confirmó la recepción: cierra el comprador y resuelve el Staff
+            EstadoDevolucion::Despachada(timestamp_despachada) => {
+                if pedido.comprador != caller {
+                    return Err(ErrorDevolucion::SoloCompradorPuede);
+                }
+
+                if timestamp < timestamp_despachada.saturating_add(PLAZO_RECEPCION_DEVOLUCION) {
+                    return Err(ErrorDevolucion::PlazoNoVencido);
+                }
+
+                // abrir disputa, salvo que el pedido ya tenga una
+                if pedido.disputa.is_none() && !pedido.fondos_fueron_transferidos {
+                    let argumento = String::from("Devolución despachada sin recepción confirmada: ") + &devolucion.motivo;
+                    self.abrir_disputa(timestamp, pedido.clone(), argumento);
+                }
+
+                devolucion.estado = EstadoDevolucion::Vencida(timestamp);
+                self.devoluciones.insert(id_pedido, devolucion);
+
+                Ok(())
+            },
+            _ => Err(ErrorDevolucion::EstadoInvalido),
+        }
+    }
+
+    //
+
+    /// Devuelve la devolución de un pedido.
+    ///
+    /// Devolverá None si la devolución no existe o el usuario no participa en el pedido ni es parte del Staff.
+    pub fn _ver_devolucion(&self, caller: AccountId, id_pedido: u128) -> Option<Devolucion> {
+        let pedido = self.pedidos.get(&id_pedido)?;
+        let es_staff = self.owner == caller || self.staff.contains(&caller);
+
+        if pedido.comprador != caller && pedido.vendedor != caller && !es_staff {
+            return None;
+        }
+
+        self.devoluciones.get(&id_pedido).cloned()
+    }
+
+    /// Devuelve el plazo de devolución del vendedor, en milisegundos
+    pub fn plazo_devolucion(&self, vendedor: AccountId) -> u64 {
+        let dias = self.plazos_devolucion.get(&vendedor).copied().unwrap_or(0);
+        u64::from(dias).saturating_mul(86_400_000) // 1000*60*60*24 = 1 día
+    }
+
+    /// Devuelve el tiempo, en milisegundos, que debe pasar desde la recepción de un pedido para que el vendedor
+    /// pueda retirar sus fondos: tres días o su plazo de devolución, si es mayor, de modo que las devoluciones
+    /// puedan solicitarse mientras los fondos sigan en custodia.
+    pub fn plazo_retiro_fondos(&self, vendedor: AccountId) -> u64 {
+        let tres_dias = 259_200_000u64; // 1000*60*60*24*3 = 3 días
+        self.plazo_devolucion(vendedor).max(tres_dias)
+    }
+
+    /// Devuelve true si el pedido tiene una devolución en curso
+    pub fn pedido_con_devolucion_en_curso(&self, id_pedido: u128) -> bool {
+        self.devoluciones.get(&id_pedido).is_some_and(|devolucion| devolucion.en_curso())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::{
+        pruebas::preparar_pedido,
+        usuario::RolDeSeleccion,
+        pedido::ErrorRetirarFondos,
+        disputa::DisputaResuelta,
+    };
+
+    const UN_DIA: u64 = 86_400_000;
+
+    /// Registra vendedor y comprador y completa un pedido de 4 unidades a 100 cada una,
+    /// recibido en el timestamp 10. El vendedor admite devoluciones por 7 días.
+    fn preparar_pedido_recibido(contrato: &mut RustaceoLibre) -> (AccountId, AccountId, u128, u128) {
+        let (vendedor, comprador, id_producto, _, id_pedido) = preparar_pedido(contrato, 10, 5, 4);
+
+        contrato._pedido_despachado(5, vendedor, id_pedido).unwrap();
+        contrato._pedido_recibido(10, comprador, id_pedido).unwrap();
+        contrato._establecer_plazo_devolucion(vendedor, 7).unwrap();
+
+        (vendedor, comprador, id_producto, id_pedido)
+    }
+
+    #[ink::test]
+    fn devolucion_completa_reembolsa_y_repone_stock() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (vendedor, comprador, id_producto, id_pedido) = preparar_pedido_recibido(&mut contrato);
+        let stock_inicial = contrato.usuarios.get(&vendedor).unwrap().obtener_stock_producto(&id_producto).unwrap();
+
+        assert_eq!(contrato._solicitar_devolucion(UN_DIA, comprador, id_pedido, 3, "Llegó roto".into()), Ok(()));
+
+        // mientras la devolución está en curso no se pueden retirar los fondos
+        assert_eq!(contrato._retirar_fondos(10 * UN_DIA, vendedor, id_pedido), Err(ErrorRetirarFondos::DevolucionEnCurso));
+
+        assert_eq!(contrato._responder_devolucion(2 * UN_DIA, vendedor, id_pedido, true), Ok(()));
+        assert_eq!(contrato._devolucion_despachada(3 * UN_DIA, comprador, id_pedido, "Correo".into(), "XY1".into()), Ok(()));
+        assert_eq!(contrato._devolucion_recibida(4 * UN_DIA, vendedor, id_pedido), Ok((comprador, 300)));
+
+        let devolucion = contrato._ver_devolucion(comprador, id_pedido).unwrap();
+        assert_eq!(devolucion.estado, EstadoDevolucion::Recibida(4 * UN_DIA));
+        assert_eq!(devolucion.reembolso, 300);
+
+        let stock_final = contrato.usuarios.get(&vendedor).unwrap().obtener_stock_producto(&id_producto).unwrap();
+        assert_eq!(stock_final, stock_inicial + 3);
+
+        // el vendedor retira sólo lo que quedó en custodia
+        assert_eq!(contrato._retirar_fondos(10 * UN_DIA, vendedor, id_pedido), Ok(100));
+    }
+
+    #[ink::test]
+    fn devolucion_rechazada_libera_fondos() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (vendedor, comprador, _, id_pedido) = preparar_pedido_recibido(&mut contrato);
+
+        contrato._solicitar_devolucion(UN_DIA, comprador, id_pedido, 1, "No me gustó".into()).unwrap();
+        assert_eq!(contrato._devolucion_despachada(UN_DIA, comprador, id_pedido, "Correo".into(), "XY1".into()), Err(ErrorDevolucion::EstadoInvalido));
+        assert_eq!(contrato._responder_devolucion(UN_DIA, comprador, id_pedido, false), Err(ErrorDevolucion::SoloVendedorPuede));
+        assert_eq!(contrato._responder_devolucion(UN_DIA, vendedor, id_pedido, false), Ok(()));
+
+        assert!(!contrato.pedido_con_devolucion_en_curso(id_pedido));
+        assert_eq!(contrato._retirar_fondos(10 * UN_DIA, vendedor, id_pedido), Ok(400));
+    }
+
+    #[ink::test]
+    fn solicitar_devolucion_validaciones() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (vendedor, comprador, _, id_pedido) = preparar_pedido_recibido(&mut contrato);
+
+        assert_eq!(contrato._solicitar_devolucion(UN_DIA, vendedor, id_pedido, 1, "x".into()), Err(ErrorDevolucion::SoloCompradorPuede));
+        assert_eq!(contrato._solicitar_devolucion(UN_DIA, comprador, id_pedido, 5, "x".into()), Err(ErrorDevolucion::CantidadInvalida));
+        assert_eq!(contrato._solicitar_devolucion(UN_DIA, comprador, id_pedido, 1, "".into()), Err(ErrorDevolucion::MotivoInvalido));
+        assert_eq!(contrato._solicitar_devolucion(10 + 7 * UN_DIA + 1, comprador, id_pedido, 1, "x".into()), Err(ErrorDevolucion::PlazoDeDevolucionExpirado));
+
+        // sin plazo de devolución no se admiten devoluciones
+        contrato._establecer_plazo_devolucion(vendedor, 0).unwrap();
+        assert_eq!(contrato._solicitar_devolucion(UN_DIA, comprador, id_pedido, 1, "x".into()), Err(ErrorDevolucion::PlazoDeDevolucionExpirado));
+    }
+
+    #[ink::test]
+    fn plazo_de_devolucion_retiene_los_fondos() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (vendedor, comprador, _, id_pedido) = preparar_pedido_recibido(&mut contrato);
+
+        // pasados tres días, el plazo de devolución de 7 días sigue reteniendo los fondos
+        assert_eq!(contrato.plazo_retiro_fondos(vendedor), 7 * UN_DIA);
+        assert!(contrato._retirar_fondos(10 + 4 * UN_DIA, vendedor, id_pedido).is_err());
+        assert_eq!(contrato._solicitar_devolucion(10 + 6 * UN_DIA, comprador, id_pedido, 1, "Llegó roto".into()), Ok(()));
+        assert_eq!(contrato._responder_devolucion(10 + 6 * UN_DIA, vendedor, id_pedido, false), Ok(()));
+        assert_eq!(contrato._retirar_fondos(10 + 7 * UN_DIA + 1, vendedor, id_pedido), Ok(400));
+
+        // un plazo menor a tres días no acorta el de retiro
+        contrato._establecer_plazo_devolucion(vendedor, 1).unwrap();
+        assert_eq!(contrato.plazo_retiro_fondos(vendedor), 3 * UN_DIA);
+    }
+
+    #[ink::test]
+    fn establecer_plazo_devolucion_solo_vendedor() {
+        let mut contrato = RustaceoLibre::new(0);
+        let comprador = AccountId::from([0x2; 32]);
+        contrato._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
+
+        assert_eq!(contrato._establecer_plazo_devolucion(comprador, 7), Err(ErrorDevolucion::NoEsVendedor));
+        assert_eq!(contrato._establecer_plazo_devolucion(AccountId::from([0x3; 32]), 7), Err(ErrorDevolucion::UsuarioNoRegistrado));
+    }
+
+    #[ink::test]
+    fn devolucion_aprobada_no_despachada_vence() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (vendedor, comprador, _, id_pedido) = preparar_pedido_recibido(&mut contrato);
+        let aprobada = UN_DIA;
+
+        contrato._solicitar_devolucion(UN_DIA, comprador, id_pedido, 2, "Talle incorrecto".into()).unwrap();
+        contrato._responder_devolucion(aprobada, vendedor, id_pedido, true).unwrap();
+
+        // antes del plazo nadie puede cerrarla
+        let vencimiento = aprobada + PLAZO_DESPACHO_DEVOLUCION;
+        assert_eq!(contrato._cerrar_devolucion_vencida(vencimiento - 1, vendedor, id_pedido), Err(ErrorDevolucion::PlazoNoVencido));
+        assert_eq!(contrato._cerrar_devolucion_vencida(vencimiento, comprador, id_pedido), Err(ErrorDevolucion::SoloVendedorPuede));
+
+        // el comprador ya no puede despacharla y el vendedor la cierra
+        assert_eq!(contrato._devolucion_despachada(vencimiento, comprador, id_pedido, "Correo".into(), "XY1".into()), Err(ErrorDevolucion::PlazoDeDespachoVencido));
+        assert_eq!(contrato._cerrar_devolucion_vencida(vencimiento, vendedor, id_pedido), Ok(()));
+        assert_eq!(contrato._ver_devolucion(vendedor, id_pedido).unwrap().estado, EstadoDevolucion::Vencida(vencimiento));
+
+        assert!(!contrato.pedido_con_devolucion_en_curso(id_pedido));
+        assert_eq!(contrato._retirar_fondos(vencimiento, vendedor, id_pedido), Ok(400));
+    }
+
+    #[ink::test]
+    fn devolucion_despachada_no_recibida_vence_y_pasa_a_disputa() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (vendedor, comprador, id_producto, id_pedido) = preparar_pedido_recibido(&mut contrato);
+        let stock_inicial = contrato.usuarios.get(&vendedor).unwrap().obtener_stock_producto(&id_producto).unwrap();
+        let despachada = 2 * UN_DIA;
+
+        contrato._solicitar_devolucion(UN_DIA, comprador, id_pedido, 1, "Llegó roto".into()).unwrap();
+        contrato._responder_devolucion(UN_DIA, vendedor, id_pedido, true).unwrap();
+        contrato._devolucion_despachada(despachada, comprador, id_pedido, "Correo".into(), "XY1".into()).unwrap();
+
+        let vencimiento = despachada + PLAZO_RECEPCION_DEVOLUCION;
+        assert_eq!(contrato._cerrar_devolucion_vencida(vencimiento - 1, comprador, id_pedido), Err(ErrorDevolucion::PlazoNoVencido));
+        assert_eq!(contrato._cerrar_devolucion_vencida(vencimiento, vendedor, id_pedido), Err(ErrorDevolucion::SoloCompradorPuede));
+        assert_eq!(contrato._cerrar_devolucion_vencida(vencimiento, comprador, id_pedido), Ok(()));
+
+        // sin recepción confirmada, las unidades no vuelven al stock ni se reembolsa automáticamente
+        let stock_final = contrato.usuarios.get(&vendedor).unwrap().obtener_stock_producto(&id_producto).unwrap();
+        assert_eq!(stock_final, stock_inicial);
+        assert_eq!(contrato._devolucion_recibida(vencimiento, vendedor, id_pedido), Err(ErrorDevolucion::EstadoInvalido));
+
+        // el pedido queda en disputa hasta que la resuelva el Staff
+        let id_disputa = contrato.pedidos.get(&id_pedido).unwrap().disputa.unwrap();
+        assert_eq!(contrato._retirar_fondos(vencimiento, vendedor, id_pedido), Err(ErrorRetirarFondos::DisputaEnCurso));
+
+        let resultado = DisputaResuelta::FavorVendedor { argumento_interventor: "Recepción no acreditada".into() };
+        assert_eq!(contrato._staff_resolver_disputa(contrato.owner, id_disputa, resultado), Ok((vendedor, 400)));
+    }
+}
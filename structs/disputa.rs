@@ -1,6 +1,6 @@
 use ink::{prelude::{string::String}, primitives::AccountId, prelude::vec::Vec};
 
-use crate::{rustaceo_libre::RustaceoLibre};
+use crate::{rustaceo_libre::RustaceoLibre, structs::{mensaje::MensajePedido, operador::PermisoOperador, pedido::Pedido}};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[ink::scale_derive(Encode, Decode, TypeInfo)]
@@ -51,6 +51,20 @@ pub struct Disputa {
     pub interventor: Option<AccountId>
 }
 
+/// Vista de una disputa junto con el pedido disputado,
+/// incluyendo la información de seguimiento de su envío y el hilo de mensajes entre las partes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub struct DetalleDisputa {
+    pub disputa: Disputa,
+    pub pedido: Pedido,
+    pub mensajes: Vec<MensajePedido>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[ink::scale_derive(Encode, Decode, TypeInfo)]
 #[cfg_attr(
@@ -145,6 +159,10 @@ impl RustaceoLibre {
     /// Devolverá error si el usuario no existe, la compra no existe, no es el comprador o vendedor,
     /// es el vendedor y no hay una disputa, es el comprador y ya realizó la disputa o la disputa ya concluyó.
     pub fn _disputar_pedido(&mut self, timestamp: u64, caller: AccountId, id_pedido: u128, argumento: String) -> Result<(), ErrorDisputarPedido> {
+        // un operador autorizado actúa en nombre del vendedor
+        let caller = self.pedidos.get(&id_pedido)
+            .map_or(caller, |p| self.actuar_como_vendedor_de(timestamp, caller, p.vendedor, PermisoOperador::ResponderDisputas));
+
         // validar usuario
         if !self.usuarios.contains_key(&caller) {
             return Err(ErrorDisputarPedido::UsuarioNoRegistrado);
@@ -200,14 +218,20 @@ impl RustaceoLibre {
             return Err(ErrorDisputarPedido::PlazoDeDisputaExpirado);
         }
 
-        let mut pedido = pedido.clone();
+        self.abrir_disputa(timestamp, pedido.clone(), argumento);
 
+        Ok(())
+    }
+
+    /// Crea una disputa sobre el pedido con el argumento del comprador,
+    /// registrándola en el pedido y en las disputas en curso de comprador y vendedor.
+    pub(crate) fn abrir_disputa(&mut self, timestamp: u64, mut pedido: Pedido, argumento: String) {
         // crear nueva disputa
         let id_nueva_disputa = self.next_id_disputas();
         let nueva_disputa = Disputa {
             id: id_nueva_disputa,
             timestamp,
-            pedido: id_pedido,
+            pedido: pedido.id,
             estado: EstadoDisputa::EnCurso(DisputaEnCurso::PendienteContraargumentacion),
             argumento_comprador: argumento,
             argumento_vendedor: None,
@@ -232,16 +256,15 @@ impl RustaceoLibre {
         // actualizar y guardar pedido para reflejar disputa
         pedido.disputa = Some(id_nueva_disputa);
         self.pedidos.insert(pedido.id, pedido);
-
-        Ok(())
     }
 
     //
 
-    /// Devolverá los datos de la disputa
+    /// Devolverá los datos de la disputa junto con el pedido disputado, su información de seguimiento
+    /// y los mensajes intercambiados entre comprador y vendedor
     /// 
     /// Devolverá None si el usuario no es parte del Staff ni participa en la disputa o si la misma no existe.
-    pub fn _consultar_disputa(&self, caller: AccountId, disputa: u128) -> Option<Disputa> {
+    pub fn _consultar_disputa(&self, caller: AccountId, disputa: u128) -> Option<DetalleDisputa> {
         let disputa = // buscar en curso. si no existe, buscar en resueltas. si no existe, devolver None 
             if let Some(d) = self.disputas_en_curso.get(&disputa) { d }
             else {
@@ -264,7 +287,11 @@ impl RustaceoLibre {
             return None;
         }
 
-        Some(disputa.clone())
+        Some(DetalleDisputa {
+            disputa: disputa.clone(),
+            pedido: pedido.clone(),
+            mensajes: self.mensajes_pedidos.get(&disputa.pedido).cloned().unwrap_or_default(),
+        })
     }
 
     //
@@ -350,21 +377,28 @@ impl RustaceoLibre {
         self.disputas_resueltas.insert(id_disputa, disputa.clone());
 
         // almacenar datos para después
+        // sólo se disputan los fondos que el contrato aún retiene
         let id_comprador = pedido.comprador;
         let id_vendedor = pedido.vendedor;
-        let valor_total = pedido.valor_total;
+        let valor_total = pedido.fondos_en_custodia();
 
         // disputa: finalizar devolviendo fondos
         // Se deben devolver fondos en lib.rs. Actualizar pedido marcando los fondos como entregados.
 
-        pedido.fondos_fueron_transferidos = true;
-        self.pedidos.insert(disputa.pedido, pedido);
-
         let id_ganador: AccountId = match resultado {
-            DisputaResuelta::FavorComprador{ argumento_interventor: _ } => id_comprador,
-            DisputaResuelta::FavorVendedor{ argumento_interventor: _ } => id_vendedor
+            DisputaResuelta::FavorComprador{ argumento_interventor: _ } => {
+                pedido.fondos_reembolsados = pedido.fondos_reembolsados.saturating_add(valor_total);
+                id_comprador
+            },
+            DisputaResuelta::FavorVendedor{ argumento_interventor: _ } => {
+                pedido.fondos_liberados = pedido.fondos_liberados.saturating_add(valor_total);
+                id_vendedor
+            }
         };
 
+        pedido.fondos_fueron_transferidos = true;
+        self.pedidos.insert(disputa.pedido, pedido);
+
         Ok((id_ganador, valor_total))
     }
 }
@@ -403,6 +437,15 @@ mod tests_disputas {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         }
     }
 
@@ -933,9 +976,10 @@ mod tests_disputas {
         );
 
         let d = c._consultar_disputa(comprador, id_disputa).expect("debe poder consultar");
-        assert_eq!(d.id, id_disputa);
-        assert_eq!(d.pedido, id_pedido);
-        assert!(d.en_curso());
+        assert_eq!(d.disputa.id, id_disputa);
+        assert_eq!(d.disputa.pedido, id_pedido);
+        assert_eq!(d.pedido.id, id_pedido);
+        assert!(d.disputa.en_curso());
     }
 
     #[ink::test]
@@ -1016,7 +1060,7 @@ mod tests_disputas {
         );
 
         let d = c._consultar_disputa(vendedor, id_disputa).expect("vendedor participa");
-        assert!(d.resuelta());
+        assert!(d.disputa.resuelta());
     }
 
     #[ink::test]
@@ -1111,4 +1155,43 @@ mod tests_disputas {
             .expect("staff puede ver");
         assert_eq!(ids, vec![3]);
     }
+
+
+    #[ink::test]
+    fn consultar_disputa_incluye_mensajes_del_pedido() {
+        use crate::structs::mensaje::ContenidoMensaje;
+
+        let mut c = RustaceoLibre::new(0);
+        let comprador = acc(1);
+        let vendedor = acc(2);
+        let id_pedido = 1;
+        let id_disputa = 10;
+
+        c._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
+        c._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+
+        let mut p = pedido_base(id_pedido, comprador, vendedor);
+        p.disputa = Some(id_disputa);
+        c.pedidos.insert(id_pedido, p);
+
+        c._enviar_mensaje(1, comprador, id_pedido, ContenidoMensaje::Texto("No llegó".into())).unwrap();
+        c._enviar_mensaje(2, vendedor, id_pedido, ContenidoMensaje::Texto("Ya fue despachado".into())).unwrap();
+
+        c.disputas_en_curso.insert(
+            id_disputa,
+            Disputa {
+                id: id_disputa,
+                timestamp: 3,
+                pedido: id_pedido,
+                estado: EstadoDisputa::EnCurso(DisputaEnCurso::PendienteContraargumentacion),
+                argumento_comprador: "x".into(),
+                argumento_vendedor: None,
+                interventor: None,
+            },
+        );
+
+        let d = c._consultar_disputa(vendedor, id_disputa).expect("debe poder consultar");
+        assert_eq!(d.mensajes.len(), 2);
+        assert_eq!(d.mensajes[0].autor, comprador);
+    }
 }
@@ -0,0 +1,181 @@
+use ink::prelude::{string::String, vec::Vec};
+
+use crate::rustaceo_libre::RustaceoLibre;
+
+/// Identificador estándar de un producto
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum IdentificadorProducto {
+    Gtin(String), // GTIN-8, GTIN-12 (UPC-A), GTIN-13 (EAN-13) o GTIN-14; sólo dígitos
+    Isbn(String), // ISBN-10 o ISBN-13; admite guiones y espacios
+}
+
+/// Devuelve los dígitos de la cadena, o None si contiene algún otro carácter
+fn digitos(texto: &str) -> Option<Vec<u32>> {
+    texto.chars().map(|c| c.to_digit(10)).collect()
+}
+
+/// Calcula el dígito verificador GTIN (módulo 10, ponderando 3 y 1 desde la derecha) de los dígitos dados
+fn digito_verificador_gtin(digitos: &[u32]) -> u32 {
+    let suma: u32 = digitos.iter().rev().enumerate()
+        .map(|(i, d)| if i % 2 == 0 { d * 3 } else { *d })
+        .sum();
+
+    (10 - suma % 10) % 10
+}
+
+/// Valida un GTIN de 8, 12, 13 o 14 dígitos y lo devuelve completado con ceros a la izquierda hasta 14 dígitos
+fn normalizar_gtin(gtin: &str) -> Option<String> {
+    let digitos = digitos(gtin)?;
+    if ![8, 12, 13, 14].contains(&digitos.len()) {
+        return None;
+    }
+
+    let (verificador, cuerpo) = digitos.split_last()?;
+    if digito_verificador_gtin(cuerpo) != *verificador {
+        return None;
+    }
+
+    let mut normalizado = String::new();
+    for _ in digitos.len()..14 {
+        normalizado.push('0');
+    }
+    normalizado.push_str(gtin);
+
+    Some(normalizado)
+}
+
+/// Valida un ISBN-10 o ISBN-13 y lo devuelve como GTIN de 14 dígitos (los ISBN-10 se convierten a ISBN-13 con prefijo 978)
+fn normalizar_isbn(isbn: &str) -> Option<String> {
+    let limpio: String = isbn.chars().filter(|c| *c != '-' && *c != ' ').collect();
+    if !limpio.is_ascii() {
+        return None;
+    }
+
+    if limpio.len() == 13 {
+        if !limpio.starts_with("978") && !limpio.starts_with("979") {
+            return None;
+        }
+        return normalizar_gtin(&limpio);
+    }
+
+    if limpio.len() != 10 {
+        return None;
+    }
+
+    // ISBN-10: el verificador puede ser 'X' (10) y la suma ponderada 10..1 debe ser múltiplo de 11
+    let cuerpo = digitos(&limpio[..9])?;
+    let verificador = match limpio.chars().last()? {
+        'X' | 'x' => 10,
+        c => c.to_digit(10)?,
+    };
+
+    let suma: u32 = cuerpo.iter().enumerate()
+        .map(|(i, d)| d * (10 - i as u32))
+        .sum::<u32>() + verificador;
+    if suma % 11 != 0 {
+        return None;
+    }
+
+    let mut gtin: Vec<u32> = [9, 7, 8].into_iter().chain(cuerpo).collect();
+    gtin.push(digito_verificador_gtin(&gtin));
+
+    let mut normalizado = String::from("0");
+    normalizado.extend(gtin.iter().filter_map(|d| char::from_digit(*d, 10)));
+
+    Some(normalizado)
+}
+
+//
+// impl IdentificadorProducto
+//
+
+impl IdentificadorProducto {
+    /// Valida el formato y el dígito verificador del identificador y devuelve su forma normalizada:
+    /// un GTIN de 14 dígitos, común a todos los tipos, que se usa como clave del índice de unicidad.
+    ///
+    /// Devolverá None si el identificador es inválido.
+    pub fn normalizar(&self) -> Option<String> {
+        match self {
+            IdentificadorProducto::Gtin(gtin) => normalizar_gtin(gtin),
+            IdentificadorProducto::Isbn(isbn) => normalizar_isbn(isbn),
+        }
+    }
+}
+
+impl RustaceoLibre {
+
+    /// Dado un identificador estándar, devuelve la ID del producto que lo tiene asignado, si existe.
+    ///
+    /// Devolverá None si el identificador es inválido o no corresponde a ningún producto.
+    pub fn _ver_producto_por_identificador(&self, identificador: IdentificadorProducto) -> Option<u128> {
+        let clave = identificador.normalizar()?;
+        self.productos_por_identificador.get(&clave).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ink::primitives::AccountId;
+    use crate::structs::{producto::{DatosProducto, ErrorRegistrarProducto}, usuario::RolDeSeleccion};
+
+    #[test]
+    fn validar_y_normalizar_identificadores() {
+        // EAN-13, UPC-A y EAN-8
+        assert_eq!(IdentificadorProducto::Gtin("4006381333931".into()).normalizar(), Some("04006381333931".into()));
+        assert_eq!(IdentificadorProducto::Gtin("036000291452".into()).normalizar(), Some("00036000291452".into()));
+        assert_eq!(IdentificadorProducto::Gtin("96385074".into()).normalizar(), Some("00000096385074".into()));
+        assert_eq!(IdentificadorProducto::Gtin("4006381333932".into()).normalizar(), None); // verificador incorrecto
+        assert_eq!(IdentificadorProducto::Gtin("40063813339".into()).normalizar(), None); // largo incorrecto
+
+        // ISBN-10 y su equivalente ISBN-13
+        let isbn13 = IdentificadorProducto::Isbn("978-0-306-40615-7".into()).normalizar();
+        assert_eq!(isbn13, Some("09780306406157".into()));
+        assert_eq!(IdentificadorProducto::Isbn("0-306-40615-2".into()).normalizar(), isbn13);
+        assert_eq!(IdentificadorProducto::Isbn("080442957X".into()).normalizar(), Some("09780804429573".into()));
+        assert_eq!(IdentificadorProducto::Isbn("0-306-40615-3".into()).normalizar(), None);
+        assert_eq!(IdentificadorProducto::Isbn("4006381333931".into()).normalizar(), None); // EAN-13 sin prefijo ISBN
+    }
+
+    #[ink::test]
+    fn identificador_duplicado_devuelve_producto_existente() {
+        let mut contrato = RustaceoLibre::new(0);
+        let owner = contrato.owner;
+        let vendedor_a = AccountId::from([0x2; 32]);
+        let vendedor_b = AccountId::from([0x3; 32]);
+        contrato._registrar_usuario(vendedor_a, RolDeSeleccion::Vendedor).unwrap();
+        contrato._registrar_usuario(vendedor_b, RolDeSeleccion::Vendedor).unwrap();
+
+        let ean = IdentificadorProducto::Gtin("4006381333931".into());
+        assert_eq!(contrato._registrar_producto(0, vendedor_a, DatosProducto { identificador: Some(IdentificadorProducto::Gtin("123".into())), ..DatosProducto::new("A".into(), "B".into(), 1) }, 1), Err(ErrorRegistrarProducto::IdentificadorInvalido));
+
+        let id_producto = contrato._registrar_producto(0, vendedor_a, DatosProducto { identificador: Some(ean.clone()), ..DatosProducto::new("Lápiz".into(), "B".into(), 1) }, 5).unwrap();
+        assert_eq!(contrato._ver_producto_por_identificador(ean.clone()), Some(id_producto));
+
+        // mientras el producto está pendiente de moderación, su identificador no puede reutilizarse
+        let otro_formato = IdentificadorProducto::Gtin("04006381333931".into());
+        let duplicado = contrato._registrar_producto(0, vendedor_b, DatosProducto { identificador: Some(otro_formato.clone()), ..DatosProducto::new("Lapiz".into(), "C".into(), 1) }, 3);
+        assert_eq!(duplicado, Err(ErrorRegistrarProducto::IdentificadorEnUso));
+
+        // una vez aprobado, el mismo identificador, con otro formato, devuelve el producto existente y acredita el stock
+        contrato._staff_aprobar_producto(owner, id_producto).unwrap();
+        let duplicado = contrato._registrar_producto(0, vendedor_b, DatosProducto { identificador: Some(otro_formato), ..DatosProducto::new("Lapiz".into(), "C".into(), 1) }, 3);
+        assert_eq!(duplicado, Ok(id_producto));
+        assert_eq!(contrato.productos.len(), 1);
+        assert_eq!(contrato.usuarios.get(&vendedor_b).unwrap().obtener_stock_producto(&id_producto), Some(3));
+
+        // un producto rechazado libera su identificador
+        let isbn = IdentificadorProducto::Isbn("080442957X".into());
+        let rechazado = contrato._registrar_producto(0, vendedor_a, DatosProducto { identificador: Some(isbn.clone()), ..DatosProducto::new("Libro".into(), "B".into(), 1) }, 1).unwrap();
+        contrato._staff_rechazar_producto(owner, rechazado, "Datos incorrectos".into()).unwrap();
+        assert_eq!(contrato._ver_producto_por_identificador(isbn.clone()), None);
+        let nuevo = contrato._registrar_producto(0, vendedor_b, DatosProducto { identificador: Some(isbn.clone()), ..DatosProducto::new("Libro".into(), "B".into(), 1) }, 1).unwrap();
+        assert_ne!(nuevo, rechazado);
+        assert_eq!(contrato._ver_producto_por_identificador(isbn), Some(nuevo));
+    }
+}
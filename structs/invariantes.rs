@@ -0,0 +1,396 @@
+use ink::{prelude::{collections::BTreeSet, vec::Vec}, primitives::AccountId};
+
+use crate::{rustaceo_libre::RustaceoLibre, structs::{movimiento_stock::TipoMovimientoStock, usuario::Usuario}};
+
+/// Cantidad máxima de elementos (usuarios o pedidos) verificados por consulta
+pub const MAX_ELEMENTOS_VERIFICACION: u32 = 50;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum AmbitoInvariantes {
+    Stock,       // paginado por usuario: unidades de cada producto de cada vendedor
+    Referencias, // paginado por usuario: compras, ventas, publicaciones y disputas del usuario
+    Pedidos,     // paginado por pedido: referencias del pedido a sus usuarios, publicación y disputa
+    General,     // sin paginar: fondos en custodia y próximas IDs
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ColeccionIds {
+    Pedidos,
+    Disputas,
+    Productos,
+    Publicaciones,
+    TransferenciasStock,
+    Categorias,
+    PropuestasTiendas,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ReferenciaUsuario {
+    Compra(u128),
+    Venta(u128),
+    Publicacion(u128),
+    DisputaComprador(u128),
+    DisputaVendedor(u128),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ViolacionInvariante {
+    /// Las unidades según el registro de movimientos (ingresos, retiros, devoluciones y transferencias,
+    /// menos las recibidas por compradores) no coinciden con las que hay en stock, publicaciones y pedidos abiertos.
+    StockInconsistente { vendedor: AccountId, producto: u128, esperado: i128, en_stock: u32, en_publicaciones: u32, en_pedidos: u32 },
+    /// El usuario referencia un elemento inexistente o que no le corresponde.
+    ReferenciaInvalida { usuario: AccountId, referencia: ReferenciaUsuario },
+    /// El pedido no figura entre las compras o ventas de uno de sus usuarios.
+    PedidoNoReferenciado { pedido: u128, usuario: AccountId },
+    PedidoSinPublicacion { pedido: u128 },
+    /// La disputa del pedido no existe o refiere a otro pedido.
+    DisputaInconsistente { pedido: u128, disputa: u128 },
+    /// Los fondos en custodia de pedidos sin liquidar superan el balance del contrato.
+    CustodiaInsuficiente { fondos_en_custodia: u128, balance: u128 },
+    /// La próxima ID a asignar ya está en uso (por ejemplo, luego de reiniciarse la cuenta).
+    IdSiguienteOcupada { coleccion: ColeccionIds, id: u128 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub struct ReporteInvariantes {
+    pub violaciones: Vec<ViolacionInvariante>,
+    pub siguiente: Option<u32>, // posición desde la cual continuar, si quedan elementos por verificar
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ErrorVerificarInvariantes {
+    SinPermiso,
+    LimiteInvalido,
+}
+
+impl RustaceoLibre {
+
+    //
+
+    /// Verifica la consistencia del stock, los fondos en custodia y las referencias cruzadas entre
+    /// usuarios, pedidos, publicaciones y disputas, sin modificar nada.
+    /// Los ámbitos Stock y Referencias se paginan por usuario y Pedidos por pedido, a partir de la posición `desde`.
+    /// El ámbito General no se pagina.
+    ///
+    /// Devolverá error si caller no es Staff, o el límite es 0 o supera el máximo por consulta.
+    pub fn _verificar_invariantes(&self, caller: AccountId, ambito: AmbitoInvariantes, desde: u32, limite: u32, balance: u128) -> Result<ReporteInvariantes, ErrorVerificarInvariantes> {
+        // validar staff
+        if self.owner != caller && !self.staff.contains(&caller) {
+            return Err(ErrorVerificarInvariantes::SinPermiso);
+        }
+
+        // validar límite
+        if limite == 0 || limite > MAX_ELEMENTOS_VERIFICACION {
+            return Err(ErrorVerificarInvariantes::LimiteInvalido);
+        }
+
+        let mut violaciones: Vec<ViolacionInvariante> = Vec::new();
+        let total = match ambito {
+            AmbitoInvariantes::Stock | AmbitoInvariantes::Referencias => self.usuarios.len(),
+            AmbitoInvariantes::Pedidos => self.pedidos.len(),
+            AmbitoInvariantes::General => 0,
+        };
+
+        match ambito {
+            AmbitoInvariantes::Stock => {
+                for usuario in self.usuarios.values().skip(desde as usize).take(limite as usize) {
+                    self.verificar_stock_usuario(usuario, &mut violaciones);
+                }
+            },
+            AmbitoInvariantes::Referencias => {
+                for usuario in self.usuarios.values().skip(desde as usize).take(limite as usize) {
+                    self.verificar_referencias_usuario(usuario, &mut violaciones);
+                }
+            },
+            AmbitoInvariantes::Pedidos => {
+                for id_pedido in self.pedidos.keys().skip(desde as usize).take(limite as usize) {
+                    self.verificar_referencias_pedido(*id_pedido, &mut violaciones);
+                }
+            },
+            AmbitoInvariantes::General => self.verificar_general(balance, &mut violaciones),
+        }
+
+        // hay más elementos si la página no llegó al final
+        let fin_pagina = desde.saturating_add(limite);
+        let siguiente = if (fin_pagina as usize) < total { Some(fin_pagina) } else { None };
+
+        Ok(ReporteInvariantes { violaciones, siguiente })
+    }
+
+    /// Verifica, para cada producto del vendedor, que las unidades registradas en su historial de movimientos
+    /// coincidan con las que hay en su stock, en sus publicaciones y en sus pedidos abiertos.
+    fn verificar_stock_usuario(&self, usuario: &Usuario, violaciones: &mut Vec<ViolacionInvariante>) {
+        let Some(data_vendedor) = usuario.obtener_data_vendedor()
+        else { return; };
+
+        // productos: los del stock, los publicados y los que tienen movimientos registrados
+        let mut productos: BTreeSet<u128> = data_vendedor.stock_productos.productos.iter().copied().collect();
+        productos.extend(data_vendedor.publicaciones.iter().filter_map(|id| self.publicaciones.get(id)).map(|p| p.producto));
+        productos.extend(self.movimientos_stock.range((usuario.id, 0)..=(usuario.id, u128::MAX)).map(|((_, id), _)| *id));
+
+        for id_producto in productos {
+            let en_stock = data_vendedor.stock_productos.get(&id_producto).unwrap_or(0);
+
+            let en_publicaciones = data_vendedor.publicaciones.iter()
+                .filter_map(|id| self.publicaciones.get(id))
+                .filter(|publicacion| publicacion.producto == id_producto)
+                .fold(0u32, |total, publicacion| total.saturating_add(publicacion.cantidad_ofertada));
+
+            // pedidos del producto: unidades aún no recibidas y unidades ya recibidas por el comprador
+            let (en_pedidos, recibidas) = data_vendedor.ventas.iter()
+                .filter_map(|id| self.pedidos.get(id))
+                .filter(|pedido| self.publicaciones.get(&pedido.publicacion).is_some_and(|p| p.producto == id_producto))
+                .fold((0u32, 0u32), |(en_pedidos, recibidas), pedido| (
+                    en_pedidos.saturating_add(pedido.cantidad_vigente().saturating_sub(pedido.cantidad_recibida)),
+                    recibidas.saturating_add(pedido.cantidad_recibida),
+                ));
+
+            let movimientos = self.movimientos_stock.get(&(usuario.id, id_producto)).cloned().unwrap_or_default();
+            let registradas = movimientos.iter().fold(0i128, |total, movimiento| {
+                let cantidad = i128::from(movimiento.cantidad);
+                match movimiento.tipo {
+                    TipoMovimientoStock::Ingreso
+                    | TipoMovimientoStock::Devolucion(_)
+                    | TipoMovimientoStock::TransferenciaRecibida(_)
+                    | TipoMovimientoStock::TransferenciaDevuelta(_) => total.saturating_add(cantidad),
+                    TipoMovimientoStock::Retiro
+                    | TipoMovimientoStock::CanceladaEnTransito(_)
+                    | TipoMovimientoStock::TransferenciaEnviada(_) => total.saturating_sub(cantidad),
+                    // movimientos internos entre stock, publicaciones y pedidos
+                    _ => total,
+                }
+            });
+
+            let esperado = registradas.saturating_sub(i128::from(recibidas));
+            let actual = i128::from(en_stock)
+                .saturating_add(i128::from(en_publicaciones))
+                .saturating_add(i128::from(en_pedidos));
+
+            if esperado != actual {
+                violaciones.push(ViolacionInvariante::StockInconsistente {
+                    vendedor: usuario.id,
+                    producto: id_producto,
+                    esperado,
+                    en_stock,
+                    en_publicaciones,
+                    en_pedidos,
+                });
+            }
+        }
+    }
+
+    /// Verifica que las compras, ventas, publicaciones y disputas en curso del usuario existan y le correspondan.
+    fn verificar_referencias_usuario(&self, usuario: &Usuario, violaciones: &mut Vec<ViolacionInvariante>) {
+        let mut invalida = |referencia: ReferenciaUsuario| violaciones.push(ViolacionInvariante::ReferenciaInvalida { usuario: usuario.id, referencia });
+
+        for id in usuario.obtener_compras().unwrap_or_default() {
+            if !self.pedidos.get(&id).is_some_and(|pedido| pedido.comprador == usuario.id) {
+                invalida(ReferenciaUsuario::Compra(id));
+            }
+        }
+
+        for id in usuario.obtener_ventas().unwrap_or_default() {
+            if !self.pedidos.get(&id).is_some_and(|pedido| pedido.vendedor == usuario.id) {
+                invalida(ReferenciaUsuario::Venta(id));
+            }
+        }
+
+        for id in usuario.obtener_publicaciones().unwrap_or_default() {
+            if !self.publicaciones.get(&id).is_some_and(|publicacion| publicacion.vendedor == usuario.id) {
+                invalida(ReferenciaUsuario::Publicacion(id));
+            }
+        }
+
+        for id in usuario.obtener_disputas_en_curso_comprador().unwrap_or_default() {
+            let valida = self.disputas_en_curso.get(&id)
+                .and_then(|disputa| self.pedidos.get(&disputa.pedido))
+                .is_some_and(|pedido| pedido.comprador == usuario.id);
+
+            if !valida {
+                invalida(ReferenciaUsuario::DisputaComprador(id));
+            }
+        }
+
+        for id in usuario.obtener_disputas_en_curso_vendedor().unwrap_or_default() {
+            let valida = self.disputas_en_curso.get(&id)
+                .and_then(|disputa| self.pedidos.get(&disputa.pedido))
+                .is_some_and(|pedido| pedido.vendedor == usuario.id);
+
+            if !valida {
+                invalida(ReferenciaUsuario::DisputaVendedor(id));
+            }
+        }
+    }
+
+    /// Verifica que el pedido figure entre las compras de su comprador y las ventas de su vendedor,
+    /// que su publicación exista y que su disputa, si tiene, refiera al mismo pedido.
+    fn verificar_referencias_pedido(&self, id_pedido: u128, violaciones: &mut Vec<ViolacionInvariante>) {
+        let Some(pedido) = self.pedidos.get(&id_pedido)
+        else { return; };
+
+        // los pedidos de una cuenta eliminada ya no figuran en sus listas
+        let cuenta_eliminada = |id: &AccountId| self.cuentas_eliminadas.get(id).is_some_and(|cuenta| pedido.timestamp <= cuenta.timestamp);
+
+        // también en los datos archivados del rol que el usuario haya dejado
+        let comprador_ok = self.usuarios.get(&pedido.comprador)
+            .and_then(|usuario| usuario.obtener_compras())
+            .is_some_and(|compras| compras.contains(&id_pedido))
+            || self.datos_comprador_archivados.get(&pedido.comprador).is_some_and(|data| data.compras.contains(&id_pedido));
+        if !comprador_ok && !cuenta_eliminada(&pedido.comprador) {
+            violaciones.push(ViolacionInvariante::PedidoNoReferenciado { pedido: id_pedido, usuario: pedido.comprador });
+        }
+
+        let vendedor_ok = self.usuarios.get(&pedido.vendedor)
+            .and_then(|usuario| usuario.obtener_ventas())
+            .is_some_and(|ventas| ventas.contains(&id_pedido))
+            || self.datos_vendedor_archivados.get(&pedido.vendedor).is_some_and(|data| data.ventas.contains(&id_pedido));
+        if !vendedor_ok && !cuenta_eliminada(&pedido.vendedor) {
+            violaciones.push(ViolacionInvariante::PedidoNoReferenciado { pedido: id_pedido, usuario: pedido.vendedor });
+        }
+
+        if !self.publicaciones.contains_key(&pedido.publicacion) {
+            violaciones.push(ViolacionInvariante::PedidoSinPublicacion { pedido: id_pedido });
+        }
+
+        if let Some(id_disputa) = pedido.disputa {
+            let disputa = self.disputas_en_curso.get(&id_disputa).or(self.disputas_resueltas.get(&id_disputa));
+            if !disputa.is_some_and(|disputa| disputa.pedido == id_pedido) {
+                violaciones.push(ViolacionInvariante::DisputaInconsistente { pedido: id_pedido, disputa: id_disputa });
+            }
+        }
+    }
+
+    /// Verifica que el balance del contrato cubra los fondos en custodia de los pedidos sin liquidar
+    /// y que ninguna de las próximas IDs a asignar esté en uso.
+    fn verificar_general(&self, balance: u128, violaciones: &mut Vec<ViolacionInvariante>) {
+        let fondos_en_custodia = self.pedidos.values()
+            .filter(|pedido| !pedido.fondos_fueron_transferidos)
+            .fold(0u128, |total, pedido| total.saturating_add(pedido.fondos_en_custodia()));
+
+        if fondos_en_custodia > balance {
+            violaciones.push(ViolacionInvariante::CustodiaInsuficiente { fondos_en_custodia, balance });
+        }
+
+        for (coleccion, id) in self.siguientes_ids() {
+            let ocupada = match coleccion {
+                ColeccionIds::Pedidos => self.pedidos.contains_key(&id),
+                ColeccionIds::Disputas => self.disputas_en_curso.contains_key(&id) || self.disputas_resueltas.contains_key(&id),
+                ColeccionIds::Productos => self.productos.contains_key(&id),
+                ColeccionIds::Publicaciones => self.publicaciones.contains_key(&id),
+                ColeccionIds::TransferenciasStock => self.transferencias_stock.contains_key(&id),
+                ColeccionIds::Categorias => self.categorias.contains_key(&id),
+                ColeccionIds::PropuestasTiendas => self.propuestas_tiendas.contains_key(&id),
+            };
+
+            if ocupada {
+                violaciones.push(ViolacionInvariante::IdSiguienteOcupada { coleccion, id });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::pruebas::preparar_pedido;
+
+    fn verificar(contrato: &RustaceoLibre, ambito: AmbitoInvariantes, balance: u128) -> Vec<ViolacionInvariante> {
+        let owner = contrato.owner;
+        contrato._verificar_invariantes(owner, ambito, 0, MAX_ELEMENTOS_VERIFICACION, balance).unwrap().violaciones
+    }
+
+    #[ink::test]
+    fn contrato_consistente_no_reporta_violaciones() {
+        let mut contrato = RustaceoLibre::new(0);
+        preparar_pedido(&mut contrato, 10, 6, 2);
+
+        assert!(verificar(&contrato, AmbitoInvariantes::Stock, 200).is_empty());
+        assert!(verificar(&contrato, AmbitoInvariantes::Referencias, 200).is_empty());
+        assert!(verificar(&contrato, AmbitoInvariantes::Pedidos, 200).is_empty());
+        assert!(verificar(&contrato, AmbitoInvariantes::General, 200).is_empty());
+    }
+
+    #[ink::test]
+    fn cancelacion_sin_publicacion_pierde_stock() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (vendedor, comprador, id_producto, id_publicacion, id_pedido) = preparar_pedido(&mut contrato, 10, 6, 2);
+
+        // la publicación desaparece y la cancelación no puede devolverle las unidades
+        contrato.publicaciones.remove(&id_publicacion);
+        contrato._cancelar_pedido(1, comprador, id_pedido).unwrap();
+
+        let violaciones = verificar(&contrato, AmbitoInvariantes::Stock, 0);
+        assert_eq!(violaciones, vec![ViolacionInvariante::StockInconsistente {
+            vendedor,
+            producto: id_producto,
+            esperado: 10,
+            en_stock: 4,
+            en_publicaciones: 0,
+            en_pedidos: 0,
+        }]);
+
+        assert!(verificar(&contrato, AmbitoInvariantes::Pedidos, 0).contains(&ViolacionInvariante::PedidoSinPublicacion { pedido: id_pedido }));
+        assert!(verificar(&contrato, AmbitoInvariantes::Referencias, 0).contains(&ViolacionInvariante::ReferenciaInvalida {
+            usuario: vendedor,
+            referencia: ReferenciaUsuario::Publicacion(id_publicacion),
+        }));
+    }
+
+    #[ink::test]
+    fn custodia_e_ids_ocupadas() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (_, _, _, _, id_pedido) = preparar_pedido(&mut contrato, 10, 6, 2);
+
+        // simular el reinicio de la cuenta de IDs de pedidos
+        let pedido = contrato.pedidos.get(&id_pedido).cloned().unwrap();
+        contrato.pedidos.insert(id_pedido + 1, pedido);
+
+        let violaciones = verificar(&contrato, AmbitoInvariantes::General, 100);
+        assert!(violaciones.contains(&ViolacionInvariante::CustodiaInsuficiente { fondos_en_custodia: 400, balance: 100 }));
+        assert!(violaciones.contains(&ViolacionInvariante::IdSiguienteOcupada { coleccion: ColeccionIds::Pedidos, id: id_pedido + 1 }));
+    }
+
+    #[ink::test]
+    fn verificar_invariantes_paginado_y_permisos() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (vendedor, _, _, _, _) = preparar_pedido(&mut contrato, 10, 6, 2);
+        let owner = contrato.owner;
+
+        assert_eq!(contrato._verificar_invariantes(vendedor, AmbitoInvariantes::Stock, 0, 1, 0), Err(ErrorVerificarInvariantes::SinPermiso));
+        assert_eq!(contrato._verificar_invariantes(owner, AmbitoInvariantes::Stock, 0, 0, 0), Err(ErrorVerificarInvariantes::LimiteInvalido));
+
+        // dos usuarios registrados
+        assert_eq!(contrato._verificar_invariantes(owner, AmbitoInvariantes::Referencias, 0, 1, 0).map(|r| r.siguiente), Ok(Some(1)));
+        assert_eq!(contrato._verificar_invariantes(owner, AmbitoInvariantes::Referencias, 1, 1, 0).map(|r| r.siguiente), Ok(None));
+    }
+}
@@ -0,0 +1,231 @@
+use ink::{prelude::{collections::BTreeMap, vec::Vec}, primitives::AccountId};
+
+use crate::{
+    rustaceo_libre::RustaceoLibre,
+    structs::{
+        operador::PermisoOperador,
+        producto::{ErrorIngresarStockProducto, ErrorRetirarStockProducto},
+        publicacion::ErrorRealizarPublicacion,
+        usuario::Usuario,
+    },
+};
+
+/// Cantidad máxima de operaciones por lote
+pub const MAX_OPERACIONES_LOTE: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum OperacionLote {
+    IngresarStock { id_producto: u128, cantidad: u32 },
+    RetirarStock { id_producto: u128, cantidad: u32 },
+    RealizarPublicacion { id_producto: u128, cantidad_ofertada: u32, precio: u128 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ResultadoOperacionLote {
+    IngresarStock(Result<u32, ErrorIngresarStockProducto>),
+    RetirarStock(Result<u32, ErrorRetirarStockProducto>),
+    RealizarPublicacion(Result<u128, ErrorRealizarPublicacion>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ErrorOperacionesLote {
+    LoteVacio,
+    LoteDemasiadoGrande,
+    OperacionFallida { indice: u32, resultado: ResultadoOperacionLote },
+}
+
+impl OperacionLote {
+    /// Devuelve la ID del producto sobre el que opera
+    pub fn id_producto(&self) -> u128 {
+        match self {
+            OperacionLote::IngresarStock { id_producto, .. } => *id_producto,
+            OperacionLote::RetirarStock { id_producto, .. } => *id_producto,
+            OperacionLote::RealizarPublicacion { id_producto, .. } => *id_producto,
+        }
+    }
+}
+
+impl ResultadoOperacionLote {
+    pub fn es_exitoso(&self) -> bool {
+        match self {
+            ResultadoOperacionLote::IngresarStock(resultado) => resultado.is_ok(),
+            ResultadoOperacionLote::RetirarStock(resultado) => resultado.is_ok(),
+            ResultadoOperacionLote::RealizarPublicacion(resultado) => resultado.is_ok(),
+        }
+    }
+}
+
+impl RustaceoLibre {
+
+    //
+
+    /// Ejecuta en orden un lote de operaciones de stock y publicaciones del vendedor,
+    /// reutilizando las validaciones de _ingresar_stock_producto, _retirar_stock_producto y _realizar_publicacion.
+    ///
+    /// Si `atomico` es true, se valida el lote completo antes de ejecutarlo y, ante la primera
+    /// operación que fallaría, no se ejecuta ninguna y se devuelve su índice y resultado.
+    /// Si es false, se devuelve el resultado de cada operación por separado.
+    ///
+    /// Devolverá error si el lote está vacío, supera el máximo de operaciones
+    /// o, en modo atómico, alguna operación falla.
+    pub fn _operaciones_lote(&mut self, timestamp: u64, caller: AccountId, operaciones: Vec<OperacionLote>, atomico: bool) -> Result<Vec<ResultadoOperacionLote>, ErrorOperacionesLote> {
+        // validar lote
+        if operaciones.is_empty() {
+            return Err(ErrorOperacionesLote::LoteVacio);
+        }
+
+        if operaciones.len() > MAX_OPERACIONES_LOTE {
+            return Err(ErrorOperacionesLote::LoteDemasiadoGrande);
+        }
+
+        if atomico {
+            self.validar_operaciones_lote(timestamp, caller, &operaciones)?;
+        }
+
+        let mut resultados: Vec<ResultadoOperacionLote> = Vec::with_capacity(operaciones.len());
+        for operacion in operaciones {
+            let resultado = match operacion {
+                OperacionLote::IngresarStock { id_producto, cantidad } =>
+                    ResultadoOperacionLote::IngresarStock(self._ingresar_stock_producto(timestamp, caller, id_producto, cantidad)),
+                OperacionLote::RetirarStock { id_producto, cantidad } =>
+                    ResultadoOperacionLote::RetirarStock(self._retirar_stock_producto(timestamp, caller, id_producto, cantidad)),
+                OperacionLote::RealizarPublicacion { id_producto, cantidad_ofertada, precio } =>
+                    ResultadoOperacionLote::RealizarPublicacion(self._realizar_publicacion(timestamp, caller, id_producto, cantidad_ofertada, precio)),
+            };
+
+            resultados.push(resultado);
+        }
+
+        Ok(resultados)
+    }
+
+    /// Simula en orden las operaciones del lote sobre copias de los usuarios afectados, sin modificar el contrato.
+    ///
+    /// Devolverá el índice y resultado de la primera operación que fallaría.
+    fn validar_operaciones_lote(&self, timestamp: u64, caller: AccountId, operaciones: &[OperacionLote]) -> Result<(), ErrorOperacionesLote> {
+        // si caller es un operador, cada operación actúa en nombre del vendedor sólo si tiene el permiso correspondiente
+        let mut usuarios: BTreeMap<AccountId, Option<Usuario>> = BTreeMap::new();
+
+        for (indice, operacion) in operaciones.iter().enumerate() {
+            let permiso = match operacion {
+                OperacionLote::IngresarStock { .. } | OperacionLote::RetirarStock { .. } => PermisoOperador::GestionarStock,
+                OperacionLote::RealizarPublicacion { .. } => PermisoOperador::GestionarPublicaciones,
+            };
+            let cuenta = self.actuar_como_vendedor(timestamp, caller, permiso);
+            let usuario = usuarios.entry(cuenta).or_insert_with(|| self.usuarios.get(&cuenta).cloned());
+
+            let validacion = match *operacion {
+                OperacionLote::IngresarStock { id_producto, cantidad } =>
+                    self.validar_ingreso_stock(usuario.as_ref(), id_producto, cantidad)
+                        .map(|stock| (id_producto, stock))
+                        .map_err(|error| ResultadoOperacionLote::IngresarStock(Err(error))),
+                OperacionLote::RetirarStock { id_producto, cantidad } =>
+                    Self::validar_retiro_stock(usuario.as_ref(), id_producto, cantidad)
+                        .map(|stock| (id_producto, stock))
+                        .map_err(|error| ResultadoOperacionLote::RetirarStock(Err(error))),
+                OperacionLote::RealizarPublicacion { id_producto, cantidad_ofertada, precio } =>
+                    self.validar_publicacion(usuario.as_ref(), id_producto, cantidad_ofertada, precio)
+                        .map(|stock| (id_producto, stock))
+                        .map_err(|error| ResultadoOperacionLote::RealizarPublicacion(Err(error))),
+            };
+
+            let (id_producto, stock) = validacion.map_err(|resultado| {
+                let indice = indice as u32; // safe cast: indice < MAX_OPERACIONES_LOTE
+                ErrorOperacionesLote::OperacionFallida { indice, resultado }
+            })?;
+
+            // aplicar el stock resultante a la copia para validar las operaciones siguientes
+            if let Some(usuario) = usuario {
+                usuario.establecer_stock_producto(&id_producto, &stock);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::{
+        pruebas::registrar_producto_aprobado,
+        usuario::RolDeSeleccion,
+    };
+
+    #[ink::test]
+    fn lote_por_item_devuelve_cada_resultado() {
+        let mut contrato = RustaceoLibre::new(0);
+        let vendedor = AccountId::from([0x1; 32]);
+        contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+        let id_a = registrar_producto_aprobado(&mut contrato, vendedor, 5);
+        let id_b = registrar_producto_aprobado(&mut contrato, vendedor, 5);
+
+        let resultados = contrato._operaciones_lote(0, vendedor, vec![
+            OperacionLote::IngresarStock { id_producto: id_a, cantidad: 5 },
+            OperacionLote::RetirarStock { id_producto: id_b, cantidad: 10 },
+            OperacionLote::RealizarPublicacion { id_producto: id_a, cantidad_ofertada: 8, precio: 100 },
+        ], false).unwrap();
+
+        assert_eq!(resultados, vec![
+            ResultadoOperacionLote::IngresarStock(Ok(10)),
+            ResultadoOperacionLote::RetirarStock(Err(ErrorRetirarStockProducto::StockInsuficiente)),
+            ResultadoOperacionLote::RealizarPublicacion(Ok(0)),
+        ]);
+
+        let usuario = contrato.usuarios.get(&vendedor).unwrap();
+        assert_eq!(usuario.obtener_stock_producto(&id_a), Some(2));
+        assert_eq!(usuario.obtener_stock_producto(&id_b), Some(5));
+    }
+
+    #[ink::test]
+    fn lote_atomico_no_aplica_nada_ante_error() {
+        let mut contrato = RustaceoLibre::new(0);
+        let vendedor = AccountId::from([0x1; 32]);
+        contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+        let id_a = registrar_producto_aprobado(&mut contrato, vendedor, 5);
+        let id_b = registrar_producto_aprobado(&mut contrato, vendedor, 5);
+        let usuario_original = contrato.usuarios.get(&vendedor).cloned();
+
+        let resultado = contrato._operaciones_lote(0, vendedor, vec![
+            OperacionLote::IngresarStock { id_producto: id_a, cantidad: 5 },
+            OperacionLote::RealizarPublicacion { id_producto: id_a, cantidad_ofertada: 8, precio: 100 },
+            OperacionLote::RetirarStock { id_producto: id_b, cantidad: 10 },
+        ], true);
+
+        assert_eq!(resultado, Err(ErrorOperacionesLote::OperacionFallida {
+            indice: 2,
+            resultado: ResultadoOperacionLote::RetirarStock(Err(ErrorRetirarStockProducto::StockInsuficiente)),
+        }));
+        assert_eq!(contrato.usuarios.get(&vendedor).cloned(), usuario_original);
+        assert!(contrato.publicaciones.is_empty());
+        assert_eq!(contrato._ver_movimientos_stock(vendedor, vendedor, id_a, 0, 50).map(|m| m.len()), Ok(1));
+    }
+
+    #[ink::test]
+    fn lote_vacio_o_demasiado_grande() {
+        let mut contrato = RustaceoLibre::new(0);
+        let vendedor = AccountId::from([0x1; 32]);
+        contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+        let id_a = registrar_producto_aprobado(&mut contrato, vendedor, 5);
+
+        assert_eq!(contrato._operaciones_lote(0, vendedor, Vec::new(), true), Err(ErrorOperacionesLote::LoteVacio));
+
+        let operaciones = vec![OperacionLote::IngresarStock { id_producto: id_a, cantidad: 1 }; MAX_OPERACIONES_LOTE + 1];
+        assert_eq!(contrato._operaciones_lote(0, vendedor, operaciones, false), Err(ErrorOperacionesLote::LoteDemasiadoGrande));
+    }
+}
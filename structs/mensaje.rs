@@ -0,0 +1,172 @@
+use ink::{prelude::{string::String, vec::Vec}, primitives::AccountId};
+
+use crate::rustaceo_libre::RustaceoLibre;
+
+/// Longitud máxima (en bytes) del texto de un mensaje
+pub const MAX_LARGO_MENSAJE: usize = 512;
+/// Cantidad máxima de mensajes que cada participante puede enviar en un pedido.
+/// Es por autor para que ninguna de las partes pueda llenar el hilo e impedir que la otra aporte pruebas.
+pub const MAX_MENSAJES_POR_AUTOR: usize = 50;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ContenidoMensaje {
+    Texto(String),
+    Hash([u8; 32]), // hash de un contenido almacenado fuera de la cadena
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub struct MensajePedido {
+    pub autor: AccountId,
+    pub timestamp: u64,
+    pub contenido: ContenidoMensaje,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ErrorMensajePedido {
+    UsuarioNoRegistrado,
+    PedidoInexistente,
+    UsuarioNoParticipa,
+    MensajeInvalido,
+    MaximoMensajesAlcanzado,
+}
+
+impl ContenidoMensaje {
+    /// Devuelve true si el texto no está vacío ni excede su longitud máxima
+    pub fn es_valido(&self) -> bool {
+        match self {
+            ContenidoMensaje::Texto(texto) => !texto.is_empty() && texto.len() <= MAX_LARGO_MENSAJE,
+            ContenidoMensaje::Hash(_) => true,
+        }
+    }
+}
+
+impl RustaceoLibre {
+
+    //
+
+    /// Agrega un mensaje al hilo de mensajes de un pedido. Los mensajes no pueden modificarse ni eliminarse.
+    /// Sólo el comprador y el vendedor del pedido pueden enviar mensajes.
+    ///
+    /// Devuelve la posición del mensaje en el hilo.
+    /// Devolverá error si el usuario no está registrado, el pedido no existe, el usuario no participa en el pedido,
+    /// el mensaje no es válido o el usuario alcanzó la cantidad máxima de mensajes en el pedido.
+    pub fn _enviar_mensaje(&mut self, timestamp: u64, caller: AccountId, id_pedido: u128, contenido: ContenidoMensaje) -> Result<u32, ErrorMensajePedido> {
+        // validar usuario
+        if !self.usuarios.contains_key(&caller) {
+            return Err(ErrorMensajePedido::UsuarioNoRegistrado);
+        }
+
+        // validar pedido
+        let Some(pedido) = self.pedidos.get(&id_pedido)
+        else { return Err(ErrorMensajePedido::PedidoInexistente); };
+
+        // validar usuario participa en pedido
+        if pedido.comprador != caller && pedido.vendedor != caller {
+            return Err(ErrorMensajePedido::UsuarioNoParticipa);
+        }
+
+        // validar contenido
+        if !contenido.es_valido() {
+            return Err(ErrorMensajePedido::MensajeInvalido);
+        }
+
+        // validar cantidad de mensajes del autor
+        let mut mensajes = self.mensajes_pedidos.get(&id_pedido).cloned().unwrap_or_default();
+        if mensajes.iter().filter(|mensaje| mensaje.autor == caller).count() >= MAX_MENSAJES_POR_AUTOR {
+            return Err(ErrorMensajePedido::MaximoMensajesAlcanzado);
+        }
+
+        // guardar
+        mensajes.push(MensajePedido { autor: caller, timestamp, contenido });
+        let posicion = mensajes.len().saturating_sub(1) as u32; // safe cast: len <= 2 * MAX_MENSAJES_POR_AUTOR
+        self.mensajes_pedidos.insert(id_pedido, mensajes);
+
+        Ok(posicion)
+    }
+
+    //
+
+    /// Devuelve el hilo de mensajes de un pedido.
+    ///
+    /// Devolverá error si el pedido no existe o el usuario no participa en el pedido ni es parte del Staff.
+    pub fn _ver_mensajes(&self, caller: AccountId, id_pedido: u128) -> Result<Vec<MensajePedido>, ErrorMensajePedido> {
+        // validar pedido
+        let Some(pedido) = self.pedidos.get(&id_pedido)
+        else { return Err(ErrorMensajePedido::PedidoInexistente); };
+
+        // validar usuario participa en pedido o es staff
+        let es_staff = self.owner == caller || self.staff.contains(&caller);
+        if pedido.comprador != caller && pedido.vendedor != caller && !es_staff {
+            return Err(ErrorMensajePedido::UsuarioNoParticipa);
+        }
+
+        // un hilo vacío no es un error
+        Ok(self.mensajes_pedidos.get(&id_pedido).cloned().unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::{
+        pruebas::preparar_pedido,
+        usuario::RolDeSeleccion,
+    };
+
+    #[ink::test]
+    fn enviar_y_ver_mensajes() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (vendedor, comprador, _, _, id_pedido) = preparar_pedido(&mut contrato, 10, 5, 1);
+
+        assert_eq!(contrato._enviar_mensaje(1, comprador, id_pedido, ContenidoMensaje::Texto("¿Cuándo llega?".into())), Ok(0));
+        assert_eq!(contrato._enviar_mensaje(2, vendedor, id_pedido, ContenidoMensaje::Hash([7; 32])), Ok(1));
+
+        let mensajes = contrato._ver_mensajes(comprador, id_pedido).unwrap();
+        assert_eq!(mensajes.len(), 2);
+        assert_eq!(mensajes[1], MensajePedido { autor: vendedor, timestamp: 2, contenido: ContenidoMensaje::Hash([7; 32]) });
+
+        // el staff puede leer pero no escribir
+        let owner = contrato.owner;
+        assert_eq!(contrato._ver_mensajes(owner, id_pedido).map(|m| m.len()), Ok(2));
+        assert_eq!(contrato._enviar_mensaje(3, owner, id_pedido, ContenidoMensaje::Texto("hola".into())), Err(ErrorMensajePedido::UsuarioNoRegistrado));
+    }
+
+    #[ink::test]
+    fn enviar_mensaje_validaciones() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (vendedor, comprador, _, _, id_pedido) = preparar_pedido(&mut contrato, 10, 5, 1);
+        let tercero = AccountId::from([0x4; 32]);
+        contrato._registrar_usuario(tercero, RolDeSeleccion::Comprador).unwrap();
+
+        assert_eq!(contrato._enviar_mensaje(1, tercero, id_pedido, ContenidoMensaje::Texto("hola".into())), Err(ErrorMensajePedido::UsuarioNoParticipa));
+        assert_eq!(contrato._ver_mensajes(tercero, id_pedido), Err(ErrorMensajePedido::UsuarioNoParticipa));
+        assert_eq!(contrato._enviar_mensaje(1, comprador, id_pedido, ContenidoMensaje::Texto(String::new())), Err(ErrorMensajePedido::MensajeInvalido));
+
+        let largo = "a".repeat(MAX_LARGO_MENSAJE + 1);
+        assert_eq!(contrato._enviar_mensaje(1, comprador, id_pedido, ContenidoMensaje::Texto(largo)), Err(ErrorMensajePedido::MensajeInvalido));
+
+        for _ in 0..MAX_MENSAJES_POR_AUTOR {
+            contrato._enviar_mensaje(1, comprador, id_pedido, ContenidoMensaje::Hash([0; 32])).unwrap();
+        }
+        assert_eq!(contrato._enviar_mensaje(1, comprador, id_pedido, ContenidoMensaje::Hash([0; 32])), Err(ErrorMensajePedido::MaximoMensajesAlcanzado));
+
+        // el límite es por autor: la otra parte todavía puede responder
+        let posicion = MAX_MENSAJES_POR_AUTOR as u32;
+        assert_eq!(contrato._enviar_mensaje(2, vendedor, id_pedido, ContenidoMensaje::Texto("Adjunto comprobante".into())), Ok(posicion));
+    }
+}
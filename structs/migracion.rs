@@ -0,0 +1,334 @@
+use ink::{prelude::vec::Vec, primitives::AccountId};
+
+use crate::{rustaceo_libre::RustaceoLibre, structs::perfil::clave_nombre_visible};
+
+/// Tiempo (en milisegundos) que debe pasar entre que se inicia una migración de cuenta y que puede confirmarse: 3 días.
+/// Da al dueño de la cuenta la oportunidad de cancelarla si la inició alguien que obtuvo su clave.
+pub const PLAZO_MIGRACION_CUENTA: u64 = 259_200_000; // 1000*60*60*24*3
+
+/// Migración de una cuenta a una nueva AccountId, iniciada desde la cuenta anterior
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub struct MigracionCuenta {
+    pub nueva: AccountId,
+    pub timestamp: u64, // momento en que se inició
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ErrorMigracionCuenta {
+    UsuarioNoRegistrado,
+    CuentaNuevaInvalida,     // es la misma cuenta, el owner, un miembro del Staff o un operador
+    CuentaNuevaEnUso,        // registrada, eliminada o destino de otra migración
+    MigracionEnCurso,
+    MigracionInexistente,
+    NoEsLaCuentaNueva,
+    PlazoNoCumplido,
+}
+
+impl RustaceoLibre {
+
+    /// Inicia la migración de la cuenta del caller a una nueva AccountId.
+    /// La migración debe confirmarse desde la cuenta nueva una vez cumplido el plazo de migración.
+    ///
+    /// Devolverá error si el usuario no está registrado, ya tiene una migración en curso,
+    /// la cuenta nueva es la misma, el owner, un miembro del Staff o un operador, o ya está registrada,
+    /// fue eliminada o es destino de otra migración.
+    pub fn _iniciar_migracion_cuenta(&mut self, timestamp: u64, caller: AccountId, nueva: AccountId) -> Result<(), ErrorMigracionCuenta> {
+        // validar usuario
+        if !self.usuarios.contains_key(&caller) {
+            return Err(ErrorMigracionCuenta::UsuarioNoRegistrado);
+        }
+
+        if self.migraciones_pendientes.contains_key(&caller) {
+            return Err(ErrorMigracionCuenta::MigracionEnCurso);
+        }
+
+        // validar cuenta nueva
+        self.validar_cuenta_nueva(caller, nueva)?;
+        if self.migraciones_pendientes.values().any(|migracion| migracion.nueva == nueva) {
+            return Err(ErrorMigracionCuenta::CuentaNuevaEnUso);
+        }
+
+        self.migraciones_pendientes.insert(caller, MigracionCuenta { nueva, timestamp });
+
+        Ok(())
+    }
+
+    //
+
+    /// Cancela la migración en curso de la cuenta del caller.
+    ///
+    /// Devolverá error si el caller no tiene una migración en curso.
+    pub fn _cancelar_migracion_cuenta(&mut self, caller: AccountId) -> Result<(), ErrorMigracionCuenta> {
+        let Some(_) = self.migraciones_pendientes.remove(&caller)
+        else { return Err(ErrorMigracionCuenta::MigracionInexistente); };
+
+        Ok(())
+    }
+
+    //
+
+    /// Confirma, desde la cuenta nueva, la migración iniciada por la cuenta anterior.
+    ///
+    /// El usuario pasa a identificarse con la cuenta nueva y se reescriben todas las referencias a la anterior:
+    /// pedidos, publicaciones, productos registrados, transferencias de stock, mensajes, listas de favoritas
+    /// y de seguidos, perfil, umbrales, registro de movimientos de stock, datos archivados, calificaciones,
+    /// operadores, tienda compartida y verificación.
+    ///
+    /// Devolverá error si no hay una migración en curso desde la cuenta anterior, el caller no es la cuenta nueva,
+    /// no se cumplió el plazo de migración, la cuenta anterior ya no está registrada
+    /// o la cuenta nueva dejó de ser válida.
+    pub fn _confirmar_migracion_cuenta(&mut self, timestamp: u64, caller: AccountId, anterior: AccountId) -> Result<(), ErrorMigracionCuenta> {
+        // validar migración
+        let Some(migracion) = self.migraciones_pendientes.get(&anterior).cloned()
+        else { return Err(ErrorMigracionCuenta::MigracionInexistente); };
+
+        if migracion.nueva != caller {
+            return Err(ErrorMigracionCuenta::NoEsLaCuentaNueva);
+        }
+
+        if timestamp < migracion.timestamp.saturating_add(PLAZO_MIGRACION_CUENTA) {
+            return Err(ErrorMigracionCuenta::PlazoNoCumplido);
+        }
+
+        // validar que nada haya cambiado durante el plazo
+        if !self.usuarios.contains_key(&anterior) {
+            return Err(ErrorMigracionCuenta::UsuarioNoRegistrado);
+        }
+
+        self.validar_cuenta_nueva(anterior, caller)?;
+
+        self.migraciones_pendientes.remove(&anterior);
+        self.migrar_referencias_cuenta(anterior, caller);
+
+        Ok(())
+    }
+
+    //
+
+    /// Devuelve la migración en curso de una cuenta, si existe.
+    pub fn _ver_migracion_cuenta(&self, id: AccountId) -> Option<MigracionCuenta> {
+        self.migraciones_pendientes.get(&id).cloned()
+    }
+
+    //
+
+    fn validar_cuenta_nueva(&self, anterior: AccountId, nueva: AccountId) -> Result<(), ErrorMigracionCuenta> {
+        if nueva == anterior || nueva == self.owner || self.staff.contains(&nueva) || self.operadores.contains_key(&nueva) {
+            return Err(ErrorMigracionCuenta::CuentaNuevaInvalida);
+        }
+
+        if self.usuarios.contains_key(&nueva) || self.cuentas_eliminadas.contains_key(&nueva) {
+            return Err(ErrorMigracionCuenta::CuentaNuevaEnUso);
+        }
+
+        Ok(())
+    }
+
+    //
+
+    /// Reemplaza todas las referencias a la cuenta anterior por la cuenta nueva
+    fn migrar_referencias_cuenta(&mut self, anterior: AccountId, nueva: AccountId) {
+        let reemplazar = |id: &mut AccountId| {
+            if *id == anterior {
+                *id = nueva;
+            }
+        };
+
+        // usuario
+        if let Some(mut usuario) = self.usuarios.remove(&anterior) {
+            usuario.id = nueva;
+            self.usuarios.insert(nueva, usuario);
+        }
+
+        // vendedores seguidos por los compradores, incluidos los datos archivados
+        for usuario in self.usuarios.values_mut() {
+            let Some(mut data_comprador) = usuario.obtener_data_comprador()
+            else { continue; };
+
+            if data_comprador.vendedores_seguidos.contains(&anterior) {
+                data_comprador.vendedores_seguidos.iter_mut().for_each(reemplazar);
+                usuario.establecer_data_comprador(data_comprador);
+            }
+        }
+
+        for data_comprador in self.datos_comprador_archivados.values_mut() {
+            data_comprador.vendedores_seguidos.iter_mut().for_each(reemplazar);
+        }
+
+        // pedidos y sus mensajes
+        for pedido in self.pedidos.values_mut() {
+            reemplazar(&mut pedido.comprador);
+            reemplazar(&mut pedido.vendedor);
+            if let Some(solicitante) = pedido.primer_solicitud_cancelacion.as_mut() {
+                reemplazar(solicitante);
+            }
+        }
+
+        for mensajes in self.mensajes_pedidos.values_mut() {
+            mensajes.iter_mut().for_each(|mensaje| reemplazar(&mut mensaje.autor));
+        }
+
+        // publicaciones, productos y transferencias
+        for publicacion in self.publicaciones.values_mut() {
+            reemplazar(&mut publicacion.vendedor);
+        }
+
+        for producto in self.productos.values_mut() {
+            reemplazar(&mut producto.registrante);
+        }
+
+        for transferencia in self.transferencias_stock.values_mut() {
+            reemplazar(&mut transferencia.emisor);
+            reemplazar(&mut transferencia.receptor);
+        }
+
+        for operador in self.operadores.values_mut() {
+            reemplazar(&mut operador.vendedor);
+        }
+
+        // tienda compartida y sus propuestas
+        if let Some(mut tienda) = self.tiendas_compartidas.remove(&anterior) {
+            reemplazar(&mut tienda.direccion_cobro);
+            self.tiendas_compartidas.insert(nueva, tienda);
+        }
+
+        for propuesta in self.propuestas_tiendas.values_mut() {
+            reemplazar(&mut propuesta.tienda);
+        }
+
+        // datos por vendedor y producto
+        let claves: Vec<(AccountId, u128)> = self.umbrales_stock_productos.keys()
+            .filter(|(vendedor, _)| *vendedor == anterior)
+            .copied()
+            .collect();
+        for (_, id_producto) in claves {
+            if let Some(umbral) = self.umbrales_stock_productos.remove(&(anterior, id_producto)) {
+                self.umbrales_stock_productos.insert((nueva, id_producto), umbral);
+            }
+        }
+
+        let claves: Vec<(AccountId, u128)> = self.movimientos_stock.keys()
+            .filter(|(vendedor, _)| *vendedor == anterior)
+            .copied()
+            .collect();
+        for (_, id_producto) in claves {
+            if let Some(movimientos) = self.movimientos_stock.remove(&(anterior, id_producto)) {
+                self.movimientos_stock.insert((nueva, id_producto), movimientos);
+            }
+        }
+
+        // datos por usuario
+        if let Some(plazo) = self.plazos_devolucion.remove(&anterior) {
+            self.plazos_devolucion.insert(nueva, plazo);
+        }
+
+        if let Some(perfil) = self.perfiles.remove(&anterior) {
+            self.nombres_visibles.insert(clave_nombre_visible(&perfil.nombre_visible), nueva);
+            self.perfiles.insert(nueva, perfil);
+        }
+
+        if let Some(data_comprador) = self.datos_comprador_archivados.remove(&anterior) {
+            self.datos_comprador_archivados.insert(nueva, data_comprador);
+        }
+
+        if let Some(data_vendedor) = self.datos_vendedor_archivados.remove(&anterior) {
+            self.datos_vendedor_archivados.insert(nueva, data_vendedor);
+        }
+
+        if let Some(verificacion) = self.verificaciones.remove(&anterior) {
+            self.verificaciones.insert(nueva, verificacion);
+        }
+
+        if let Some(solicitud) = self.solicitudes_verificacion.remove(&anterior) {
+            self.solicitudes_verificacion.insert(nueva, solicitud);
+        }
+
+        // el registro de una eliminación anterior acompaña a los pedidos que la cuenta ya no lista
+        if let Some(cuenta_eliminada) = self.cuentas_eliminadas.remove(&anterior) {
+            self.cuentas_eliminadas.insert(nueva, cuenta_eliminada);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::{producto::DatosProducto, usuario::RolDeSeleccion};
+
+    #[ink::test]
+    fn iniciar_migracion_cuenta_valida_cuenta_nueva() {
+        let mut contrato = RustaceoLibre::new(0);
+        let usuario = AccountId::from([0x1; 32]);
+        let otro = AccountId::from([0x2; 32]);
+        let nueva = AccountId::from([0x3; 32]);
+        contrato._registrar_usuario(usuario, RolDeSeleccion::Ambos).unwrap();
+        contrato._registrar_usuario(otro, RolDeSeleccion::Comprador).unwrap();
+
+        assert_eq!(contrato._iniciar_migracion_cuenta(0, nueva, usuario), Err(ErrorMigracionCuenta::UsuarioNoRegistrado));
+        assert_eq!(contrato._iniciar_migracion_cuenta(0, usuario, usuario), Err(ErrorMigracionCuenta::CuentaNuevaInvalida));
+        assert_eq!(contrato._iniciar_migracion_cuenta(0, usuario, contrato.owner), Err(ErrorMigracionCuenta::CuentaNuevaInvalida));
+        assert_eq!(contrato._iniciar_migracion_cuenta(0, usuario, otro), Err(ErrorMigracionCuenta::CuentaNuevaEnUso));
+        assert_eq!(contrato._iniciar_migracion_cuenta(0, usuario, nueva), Ok(()));
+        assert_eq!(contrato._iniciar_migracion_cuenta(0, usuario, nueva), Err(ErrorMigracionCuenta::MigracionEnCurso));
+        assert_eq!(contrato._iniciar_migracion_cuenta(0, otro, nueva), Err(ErrorMigracionCuenta::CuentaNuevaEnUso));
+
+        // confirmar exige la cuenta nueva y el plazo cumplido
+        assert_eq!(contrato._confirmar_migracion_cuenta(PLAZO_MIGRACION_CUENTA, otro, usuario), Err(ErrorMigracionCuenta::NoEsLaCuentaNueva));
+        assert_eq!(contrato._confirmar_migracion_cuenta(PLAZO_MIGRACION_CUENTA - 1, nueva, usuario), Err(ErrorMigracionCuenta::PlazoNoCumplido));
+
+        // una vez cancelada ya no puede confirmarse
+        assert_eq!(contrato._cancelar_migracion_cuenta(usuario), Ok(()));
+        assert_eq!(contrato._cancelar_migracion_cuenta(usuario), Err(ErrorMigracionCuenta::MigracionInexistente));
+        assert_eq!(contrato._confirmar_migracion_cuenta(PLAZO_MIGRACION_CUENTA, nueva, usuario), Err(ErrorMigracionCuenta::MigracionInexistente));
+        assert!(contrato.usuarios.contains_key(&usuario));
+    }
+
+    #[ink::test]
+    fn confirmar_migracion_cuenta_reescribe_referencias() {
+        let mut contrato = RustaceoLibre::new(0);
+        let vendedor = AccountId::from([0x1; 32]);
+        let comprador = AccountId::from([0x2; 32]);
+        let nueva = AccountId::from([0x3; 32]);
+        contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+        contrato._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
+        contrato._editar_perfil(vendedor, "Vendedor".into(), "".into(), None, None, None).unwrap();
+
+        let id_producto = contrato._registrar_producto(0, vendedor, DatosProducto::new("A".into(), "B".into(), 1), 10).unwrap();
+        contrato._staff_aprobar_producto(contrato.owner, id_producto).unwrap();
+        let id_publicacion = contrato._realizar_publicacion(0, vendedor, id_producto, 5, 100).unwrap();
+        contrato._seguir_vendedor(comprador, vendedor).unwrap();
+        let id_pedido = contrato._comprar_producto(0, comprador, id_publicacion, 1, 100).unwrap().id_nueva_transaccion;
+
+        contrato._iniciar_migracion_cuenta(0, vendedor, nueva).unwrap();
+        assert_eq!(contrato._confirmar_migracion_cuenta(PLAZO_MIGRACION_CUENTA, nueva, vendedor), Ok(()));
+        assert_eq!(contrato._ver_migracion_cuenta(vendedor), None);
+
+        // el usuario, su stock y sus ventas pasan a la cuenta nueva
+        assert!(!contrato.usuarios.contains_key(&vendedor));
+        let usuario = contrato.usuarios.get(&nueva).unwrap();
+        assert_eq!(usuario.id, nueva);
+        assert_eq!(usuario.obtener_stock_producto(&id_producto), Some(5));
+        assert!(usuario.obtener_ventas().unwrap().contains(&id_pedido));
+
+        assert_eq!(contrato.pedidos.get(&id_pedido).unwrap().vendedor, nueva);
+        assert_eq!(contrato.publicaciones.get(&id_publicacion).unwrap().vendedor, nueva);
+        assert_eq!(contrato.productos.get(&id_producto).unwrap().registrante, nueva);
+        assert_eq!(contrato.usuarios.get(&comprador).unwrap().obtener_vendedores_seguidos(), Some(Vec::from([nueva])));
+        assert_eq!(contrato._buscar_usuario_por_nombre("vendedor".into()), Some(nueva));
+        assert!(contrato.movimientos_stock.contains_key(&(nueva, id_producto)));
+        assert!(!contrato.movimientos_stock.contains_key(&(vendedor, id_producto)));
+
+        // la cuenta nueva opera sobre la venta
+        assert!(contrato._pedido_despachado(PLAZO_MIGRACION_CUENTA, nueva, id_pedido).is_ok());
+    }
+}
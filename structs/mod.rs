@@ -2,4 +2,24 @@ pub mod usuario;
 pub mod producto;
 pub mod disputa;
 pub mod publicacion;
-pub mod pedido;
\ No newline at end of file
+pub mod pedido;
+pub mod devolucion;
+pub mod mensaje;
+pub mod alerta_stock;
+pub mod lote;
+pub mod movimiento_stock;
+pub mod transferencia_stock;
+pub mod invariantes;
+pub mod categoria;
+pub mod atributo;
+pub mod multimedia;
+pub mod identificador;
+pub mod perfil;
+pub mod cuenta;
+pub mod migracion;
+pub mod operador;
+pub mod tienda_compartida;
+pub mod verificacion;
+
+#[cfg(test)]
+pub(crate) mod pruebas;
@@ -0,0 +1,155 @@
+use ink::{prelude::{collections::BTreeMap, vec::Vec}, primitives::AccountId};
+
+use crate::rustaceo_libre::RustaceoLibre;
+
+/// Cantidad máxima de movimientos devueltos por consulta
+pub const MAX_MOVIMIENTOS_POR_PAGINA: u32 = 50;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum TipoMovimientoStock {
+    Ingreso,                // al stock del vendedor (incluye el stock inicial)
+    Retiro,                 // del stock del vendedor
+    HaciaPublicacion(u128), // del stock del vendedor a la publicación indicada
+    DesdePublicacion(u128), // de la publicación indicada al stock del vendedor
+    Venta(u128),            // unidades vendidas desde una publicación en el pedido indicado
+    Cancelacion(u128),      // unidades devueltas a la publicación por la cancelación del pedido indicado
+    CanceladaEnTransito(u128), // unidades despachadas y no recibidas al cancelarse el pedido indicado. no vuelven a la publicación
+    Devolucion(u128),       // unidades devueltas al stock del vendedor por la devolución del pedido indicado
+    TransferenciaEnviada(u128),  // unidades retenidas del stock del emisor al ofrecer la transferencia indicada
+    TransferenciaRecibida(u128), // unidades acreditadas al receptor al aceptar la transferencia indicada
+    TransferenciaDevuelta(u128), // unidades devueltas al emisor al rechazar o cancelar la transferencia indicada
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub struct MovimientoStock {
+    pub timestamp: u64,
+    pub tipo: TipoMovimientoStock,
+    pub cantidad: u32,
+}
+
+/// Registro de movimientos de stock por (ID del vendedor, ID del producto)
+pub type MovimientosStock = BTreeMap<(AccountId, u128), Vec<MovimientoStock>>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ErrorVerMovimientosStock {
+    SinPermiso,
+    LimiteInvalido,
+}
+
+impl RustaceoLibre {
+
+    /// Agrega un movimiento al registro de stock del vendedor para el producto indicado.
+    /// Los movimientos de cantidad 0 no se registran.
+    pub(crate) fn registrar_movimiento_stock(&mut self, timestamp: u64, vendedor: AccountId, id_producto: u128, tipo: TipoMovimientoStock, cantidad: u32) {
+        if cantidad == 0 {
+            return;
+        }
+
+        let mut movimientos = self.movimientos_stock.get(&(vendedor, id_producto)).cloned().unwrap_or_default();
+        movimientos.push(MovimientoStock { timestamp, tipo, cantidad });
+        self.movimientos_stock.insert((vendedor, id_producto), movimientos);
+    }
+
+    //
+
+    /// Devuelve una página del registro de movimientos de stock de un vendedor para un producto,
+    /// en orden cronológico a partir de la posición `desde`.
+    /// Sólo puede consultarlo el mismo vendedor o un miembro del Staff.
+    ///
+    /// Devolverá error si caller no es el vendedor ni Staff, o el límite es 0 o supera el máximo por página.
+    pub fn _ver_movimientos_stock(&self, caller: AccountId, vendedor: AccountId, id_producto: u128, desde: u32, limite: u32) -> Result<Vec<MovimientoStock>, ErrorVerMovimientosStock> {
+        let es_staff = self.owner == caller || self.staff.contains(&caller);
+        if caller != vendedor && !es_staff {
+            return Err(ErrorVerMovimientosStock::SinPermiso);
+        }
+
+        if limite == 0 || limite > MAX_MOVIMIENTOS_POR_PAGINA {
+            return Err(ErrorVerMovimientosStock::LimiteInvalido);
+        }
+
+        // una página vacía no es un error
+        let Some(movimientos) = self.movimientos_stock.get(&(vendedor, id_producto))
+        else { return Ok(Vec::new()); };
+
+        Ok(movimientos.iter().skip(desde as usize).take(limite as usize).cloned().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::{
+        producto::DatosProducto,
+        usuario::RolDeSeleccion,
+    };
+
+    #[ink::test]
+    fn movimientos_stock_registra_cada_cambio() {
+        let mut contrato = RustaceoLibre::new(0);
+        let vendedor = AccountId::from([0x3; 32]); // [0x1; 32] es el owner
+        let comprador = AccountId::from([0x2; 32]);
+        contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+        contrato._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
+
+        let id_producto = contrato._registrar_producto(0, vendedor, DatosProducto::new("Mate".into(), "Calabaza".into(), 1), 10).unwrap();
+        contrato._staff_aprobar_producto(contrato.owner, id_producto).unwrap();
+        contrato._ingresar_stock_producto(0, vendedor, id_producto, 5).unwrap();
+        contrato._retirar_stock_producto(0, vendedor, id_producto, 3).unwrap();
+        let id_publicacion = contrato._realizar_publicacion(0, vendedor, id_producto, 6, 100).unwrap();
+        contrato._modificar_cantidad_ofertada(0, vendedor, id_publicacion, 4).unwrap();
+        let id_pedido = contrato._comprar_producto(7, comprador, id_publicacion, 2, 200).unwrap().id_nueva_transaccion;
+        contrato._cancelar_pedido(8, comprador, id_pedido).unwrap();
+
+        let tipos: Vec<(TipoMovimientoStock, u32)> = contrato._ver_movimientos_stock(vendedor, vendedor, id_producto, 0, MAX_MOVIMIENTOS_POR_PAGINA).unwrap()
+            .into_iter().map(|m| (m.tipo, m.cantidad)).collect();
+
+        assert_eq!(tipos, vec![
+            (TipoMovimientoStock::Ingreso, 10),
+            (TipoMovimientoStock::Ingreso, 5),
+            (TipoMovimientoStock::Retiro, 3),
+            (TipoMovimientoStock::HaciaPublicacion(id_publicacion), 6),
+            (TipoMovimientoStock::DesdePublicacion(id_publicacion), 2),
+            (TipoMovimientoStock::Venta(id_pedido), 2),
+            (TipoMovimientoStock::Cancelacion(id_pedido), 2),
+        ]);
+    }
+
+    #[ink::test]
+    fn ver_movimientos_stock_paginado_y_permisos() {
+        let mut contrato = RustaceoLibre::new(0);
+        let vendedor = AccountId::from([0x3; 32]); // [0x1; 32] es el owner
+        let tercero = AccountId::from([0x4; 32]);
+        contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+
+        let id_producto = contrato._registrar_producto(0, vendedor, DatosProducto::new("Mate".into(), "Calabaza".into(), 1), 1).unwrap();
+        contrato._staff_aprobar_producto(contrato.owner, id_producto).unwrap();
+        for cantidad in 2..=5 {
+            contrato._ingresar_stock_producto(0, vendedor, id_producto, cantidad).unwrap();
+        }
+
+        let pagina = contrato._ver_movimientos_stock(vendedor, vendedor, id_producto, 2, 2).unwrap();
+        assert_eq!(pagina.iter().map(|m| m.cantidad).collect::<Vec<u32>>(), vec![3, 4]);
+        assert_eq!(contrato._ver_movimientos_stock(vendedor, vendedor, id_producto, 10, 2), Ok(Vec::new()));
+
+        assert_eq!(contrato._ver_movimientos_stock(tercero, vendedor, id_producto, 0, 2), Err(ErrorVerMovimientosStock::SinPermiso));
+        assert_eq!(contrato._ver_movimientos_stock(vendedor, vendedor, id_producto, 0, 0), Err(ErrorVerMovimientosStock::LimiteInvalido));
+
+        let owner = contrato.owner;
+        assert_eq!(contrato._ver_movimientos_stock(owner, vendedor, id_producto, 0, 50).map(|m| m.len()), Ok(5));
+    }
+}
@@ -0,0 +1,292 @@
+use ink::{prelude::{string::String, vec::Vec}, primitives::AccountId};
+
+use crate::{rustaceo_libre::RustaceoLibre, structs::operador::PermisoOperador};
+
+/// Cantidad máxima de archivos multimedia por producto o publicación
+pub const MAX_MULTIMEDIA: usize = 10;
+/// Longitud máxima (en bytes) de un identificador de contenido
+pub const MAX_LARGO_CID: usize = 128;
+
+/// Largo exacto de un CIDv0 (`Qm...` en base58btc)
+const LARGO_CID_V0: usize = 46;
+/// Largo mínimo de un CIDv1 en base32 (`b...`)
+const MIN_LARGO_CID_V1: usize = 50;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum TipoMultimedia {
+    Imagen,
+    Video,
+    Documento, // manuales, fichas técnicas, etc.
+}
+
+/// Referencia a un archivo almacenado fuera de la cadena, direccionado por su contenido (p. ej. un CID de IPFS)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub struct Multimedia {
+    pub cid: String,
+    pub tipo: TipoMultimedia,
+    pub tamano: u64, // en bytes
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ErrorMultimedia {
+    ProductoInexistente,
+    PublicacionInexistente,
+    SinPermiso,
+    CidInvalido,
+    TamanoInvalido,
+    CidDuplicado,
+    LimiteAlcanzado,
+    IndiceInvalido,
+}
+
+//
+// impl TipoMultimedia
+//
+
+impl TipoMultimedia {
+    /// Tamaño máximo (en bytes) admitido para cada tipo de archivo
+    pub fn tamano_maximo(&self) -> u64 {
+        match self {
+            TipoMultimedia::Imagen => 10 * 1024 * 1024,
+            TipoMultimedia::Video => 200 * 1024 * 1024,
+            TipoMultimedia::Documento => 50 * 1024 * 1024,
+        }
+    }
+}
+
+//
+// impl Multimedia
+//
+
+impl Multimedia {
+    /// Devuelve true si el CID tiene un formato válido: CIDv0 (`Qm` seguido de base58btc, 46 caracteres)
+    /// o CIDv1 en base32 (prefijo multibase `b` seguido de base32 en minúsculas).
+    pub fn cid_valido(cid: &str) -> bool {
+        if cid.len() > MAX_LARGO_CID {
+            return false;
+        }
+
+        if cid.len() == LARGO_CID_V0 && cid.starts_with("Qm") {
+            // base58btc: alfanuméricos sin 0, O, I ni l
+            return cid.chars().all(|c| c.is_ascii_alphanumeric() && !matches!(c, '0' | 'O' | 'I' | 'l'));
+        }
+
+        if cid.len() >= MIN_LARGO_CID_V1 && cid.starts_with('b') {
+            return cid.chars().skip(1).all(|c| c.is_ascii_lowercase() || ('2'..='7').contains(&c));
+        }
+
+        false
+    }
+
+    /// Valida el CID y el tamaño del archivo según su tipo
+    fn validar(&self) -> Result<(), ErrorMultimedia> {
+        if !Self::cid_valido(&self.cid) {
+            return Err(ErrorMultimedia::CidInvalido);
+        }
+
+        if self.tamano == 0 || self.tamano > self.tipo.tamano_maximo() {
+            return Err(ErrorMultimedia::TamanoInvalido);
+        }
+
+        Ok(())
+    }
+}
+
+/// Agrega un archivo al final de una lista de multimedia, validándolo
+fn agregar_a_lista(lista: &mut Vec<Multimedia>, multimedia: Multimedia) -> Result<(), ErrorMultimedia> {
+    multimedia.validar()?;
+
+    if lista.iter().any(|m| m.cid == multimedia.cid) {
+        return Err(ErrorMultimedia::CidDuplicado);
+    }
+
+    if lista.len() >= MAX_MULTIMEDIA {
+        return Err(ErrorMultimedia::LimiteAlcanzado);
+    }
+
+    lista.push(multimedia);
+    Ok(())
+}
+
+/// Quita el archivo en la posición indicada de una lista de multimedia, conservando el orden del resto
+fn quitar_de_lista(lista: &mut Vec<Multimedia>, indice: u32) -> Result<(), ErrorMultimedia> {
+    let indice = indice as usize;
+    if indice >= lista.len() {
+        return Err(ErrorMultimedia::IndiceInvalido);
+    }
+
+    lista.remove(indice);
+    Ok(())
+}
+
+impl RustaceoLibre {
+
+    /// Agrega un archivo multimedia al final de la lista de un producto.
+    /// Sólo puede hacerlo el vendedor que registró el producto o un miembro del Staff.
+    ///
+    /// Devolverá error si el producto no existe, el usuario no tiene permiso, el CID es inválido o ya está en la lista,
+    /// el tamaño es 0 o excede el máximo para su tipo, o la lista alcanzó su máximo.
+    pub fn _agregar_multimedia_producto(&mut self, caller: AccountId, id_producto: u128, multimedia: Multimedia) -> Result<(), ErrorMultimedia> {
+        // validar producto
+        let Some(mut producto) = self.productos.get(&id_producto).cloned()
+        else { return Err(ErrorMultimedia::ProductoInexistente); };
+
+        // validar permiso
+        if producto.registrante != caller && !self.staff.contains(&caller) && caller != self.owner {
+            return Err(ErrorMultimedia::SinPermiso);
+        }
+
+        agregar_a_lista(&mut producto.multimedia, multimedia)?;
+        self.productos.insert(id_producto, producto);
+
+        Ok(())
+    }
+
+    //
+
+    /// Quita el archivo multimedia en la posición indicada de la lista de un producto.
+    /// Sólo puede hacerlo el vendedor que registró el producto o un miembro del Staff.
+    ///
+    /// Devolverá error si el producto no existe, el usuario no tiene permiso o la posición no existe.
+    pub fn _quitar_multimedia_producto(&mut self, caller: AccountId, id_producto: u128, indice: u32) -> Result<(), ErrorMultimedia> {
+        // validar producto
+        let Some(mut producto) = self.productos.get(&id_producto).cloned()
+        else { return Err(ErrorMultimedia::ProductoInexistente); };
+
+        // validar permiso
+        if producto.registrante != caller && !self.staff.contains(&caller) && caller != self.owner {
+            return Err(ErrorMultimedia::SinPermiso);
+        }
+
+        quitar_de_lista(&mut producto.multimedia, indice)?;
+        self.productos.insert(id_producto, producto);
+
+        Ok(())
+    }
+
+    //
+
+    /// Agrega un archivo multimedia al final de la lista de una publicación.
+    /// Sólo puede hacerlo el vendedor de la publicación.
+    ///
+    /// Devolverá error si la publicación no existe, el usuario no es su vendedor, el CID es inválido o ya está en la lista,
+    /// el tamaño es 0 o excede el máximo para su tipo, o la lista alcanzó su máximo.
+    pub fn _agregar_multimedia_publicacion(&mut self, timestamp: u64, caller: AccountId, id_publicacion: u128, multimedia: Multimedia) -> Result<(), ErrorMultimedia> {
+        // un operador autorizado actúa en nombre del vendedor
+        let caller = self.publicaciones.get(&id_publicacion)
+            .map_or(caller, |p| self.actuar_como_vendedor_de(timestamp, caller, p.vendedor, PermisoOperador::GestionarPublicaciones));
+
+        // validar publicación
+        let Some(mut publicacion) = self.publicaciones.get(&id_publicacion).cloned()
+        else { return Err(ErrorMultimedia::PublicacionInexistente); };
+
+        // validar permiso
+        if publicacion.vendedor != caller {
+            return Err(ErrorMultimedia::SinPermiso);
+        }
+
+        agregar_a_lista(&mut publicacion.multimedia, multimedia)?;
+        self.publicaciones.insert(id_publicacion, publicacion);
+
+        Ok(())
+    }
+
+    //
+
+    /// Quita el archivo multimedia en la posición indicada de la lista de una publicación.
+    /// Puede hacerlo el vendedor de la publicación o, para moderar contenido, un miembro del Staff.
+    ///
+    /// Devolverá error si la publicación no existe, el usuario no tiene permiso o la posición no existe.
+    pub fn _quitar_multimedia_publicacion(&mut self, timestamp: u64, caller: AccountId, id_publicacion: u128, indice: u32) -> Result<(), ErrorMultimedia> {
+        // un operador autorizado actúa en nombre del vendedor
+        let caller = self.publicaciones.get(&id_publicacion)
+            .map_or(caller, |p| self.actuar_como_vendedor_de(timestamp, caller, p.vendedor, PermisoOperador::GestionarPublicaciones));
+
+        // validar publicación
+        let Some(mut publicacion) = self.publicaciones.get(&id_publicacion).cloned()
+        else { return Err(ErrorMultimedia::PublicacionInexistente); };
+
+        // validar permiso
+        if publicacion.vendedor != caller && !self.staff.contains(&caller) && caller != self.owner {
+            return Err(ErrorMultimedia::SinPermiso);
+        }
+
+        quitar_de_lista(&mut publicacion.multimedia, indice)?;
+        self.publicaciones.insert(id_publicacion, publicacion);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::{producto::DatosProducto, usuario::RolDeSeleccion};
+
+    const CID_V0: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+    const CID_V1: &str = "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi";
+
+    fn imagen(cid: &str) -> Multimedia {
+        Multimedia { cid: cid.into(), tipo: TipoMultimedia::Imagen, tamano: 1024 }
+    }
+
+    #[test]
+    fn formato_cid() {
+        assert!(Multimedia::cid_valido(CID_V0));
+        assert!(Multimedia::cid_valido(CID_V1));
+        assert!(!Multimedia::cid_valido(""));
+        assert!(!Multimedia::cid_valido("QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbd0")); // '0' no es base58
+        assert!(!Multimedia::cid_valido("bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzd1")); // '1' no es base32
+        assert!(!Multimedia::cid_valido("https://ejemplo.com/imagen.png"));
+    }
+
+    #[ink::test]
+    fn multimedia_producto_y_publicacion() {
+        let mut contrato = RustaceoLibre::new(0);
+        let owner = contrato.owner;
+        let vendedor = AccountId::from([0x2; 32]);
+        let otro = AccountId::from([0x3; 32]);
+        contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+        contrato._registrar_usuario(otro, RolDeSeleccion::Vendedor).unwrap();
+
+        let id_producto = contrato._registrar_producto(0, vendedor, DatosProducto::new("A".into(), "B".into(), 1), 10).unwrap();
+        contrato._staff_aprobar_producto(owner, id_producto).unwrap();
+
+        // producto
+        assert_eq!(contrato._agregar_multimedia_producto(otro, id_producto, imagen(CID_V0)), Err(ErrorMultimedia::SinPermiso));
+        assert_eq!(contrato._agregar_multimedia_producto(vendedor, id_producto, imagen("no-es-un-cid")), Err(ErrorMultimedia::CidInvalido));
+        let video_enorme = Multimedia { cid: CID_V0.into(), tipo: TipoMultimedia::Video, tamano: TipoMultimedia::Video.tamano_maximo() + 1 };
+        assert_eq!(contrato._agregar_multimedia_producto(vendedor, id_producto, video_enorme), Err(ErrorMultimedia::TamanoInvalido));
+
+        assert_eq!(contrato._agregar_multimedia_producto(vendedor, id_producto, imagen(CID_V0)), Ok(()));
+        assert_eq!(contrato._agregar_multimedia_producto(owner, id_producto, imagen(CID_V1)), Ok(()));
+        assert_eq!(contrato._agregar_multimedia_producto(vendedor, id_producto, imagen(CID_V1)), Err(ErrorMultimedia::CidDuplicado));
+        assert_eq!(contrato._quitar_multimedia_producto(vendedor, id_producto, 0), Ok(()));
+        assert_eq!(contrato._ver_producto(id_producto).unwrap().multimedia, vec![imagen(CID_V1)]);
+        assert_eq!(contrato._quitar_multimedia_producto(vendedor, id_producto, 1), Err(ErrorMultimedia::IndiceInvalido));
+
+        // publicación
+        let id_publicacion = contrato._realizar_publicacion(0, vendedor, id_producto, 5, 100).unwrap();
+        assert_eq!(contrato._agregar_multimedia_publicacion(0, owner, id_publicacion, imagen(CID_V0)), Err(ErrorMultimedia::SinPermiso));
+        assert_eq!(contrato._agregar_multimedia_publicacion(0, vendedor, id_publicacion, imagen(CID_V0)), Ok(()));
+        assert_eq!(contrato._ver_publicacion(id_publicacion).unwrap().multimedia, vec![imagen(CID_V0)]);
+        assert_eq!(contrato._quitar_multimedia_publicacion(0, otro, id_publicacion, 0), Err(ErrorMultimedia::SinPermiso));
+        assert_eq!(contrato._quitar_multimedia_publicacion(0, owner, id_publicacion, 0), Ok(()));
+        assert!(contrato._ver_publicacion(id_publicacion).unwrap().multimedia.is_empty());
+    }
+}
@@ -0,0 +1,244 @@
+use ink::{prelude::vec::Vec, primitives::AccountId};
+
+use crate::rustaceo_libre::RustaceoLibre;
+
+/// Cantidad máxima de operadores que puede autorizar un vendedor
+pub const MAX_OPERADORES_VENDEDOR: usize = 20;
+
+/// Acciones que un vendedor puede delegar en un operador.
+/// Retirar fondos nunca se delega.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum PermisoOperador {
+    GestionarStock,          // ingresar y retirar stock, umbrales de stock de productos
+    GestionarPublicaciones,  // publicar, cantidad ofertada, precio, reposición, umbrales y multimedia de publicaciones
+    Despachar,               // despachar pedidos y actualizar su seguimiento
+    ResponderDisputas,       // contraargumentar disputas
+}
+
+/// Cuenta autorizada por un vendedor para actuar en su nombre con los permisos indicados
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub struct Operador {
+    pub vendedor: AccountId,
+    pub permisos: Vec<PermisoOperador>,
+    pub vencimiento: Option<u64>, // la autorización deja de valer a partir de este momento
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ErrorOperador {
+    UsuarioNoRegistrado,
+    NoEsVendedor,
+    OperadorInvalido,         // es el mismo vendedor, un usuario registrado, el owner o un miembro del Staff
+    OperadorDeOtroVendedor,
+    PermisosInvalidos,        // vacíos o repetidos
+    VencimientoInvalido,
+    MaximoOperadores,
+    OperadorInexistente,
+    OperadorEsSocio,          // sus permisos sólo cambian con una propuesta aprobada por los socios de la tienda
+}
+
+//
+// impl Operador
+//
+
+impl Operador {
+    /// Devuelve true si la autorización sigue vigente en el momento indicado y otorga el permiso
+    pub fn permite(&self, timestamp: u64, permiso: PermisoOperador) -> bool {
+        self.vencimiento.map_or(true, |vencimiento| timestamp < vencimiento) && self.permisos.contains(&permiso)
+    }
+}
+
+impl RustaceoLibre {
+
+    /// Autoriza a una cuenta como operador del vendedor con los permisos indicados y, opcionalmente, un vencimiento.
+    /// Si la cuenta ya era operador del vendedor, se reemplazan sus permisos y vencimiento.
+    ///
+    /// El operador actúa en nombre del vendedor en las funciones cubiertas por sus permisos,
+    /// pero nunca puede retirar fondos. Una cuenta sólo puede operar para un vendedor y no puede ser un usuario registrado.
+    ///
+    /// Devolverá error si el usuario no está registrado o no es vendedor, el operador es el mismo vendedor,
+    /// un usuario registrado, el owner, un miembro del Staff u operador de otro vendedor, los permisos están vacíos
+    /// o repetidos, el vencimiento ya pasó, el vendedor alcanzó el máximo de operadores
+    /// o el operador es socio de su tienda compartida.
+    pub fn _autorizar_operador(&mut self, timestamp: u64, caller: AccountId, operador: AccountId, permisos: Vec<PermisoOperador>, vencimiento: Option<u64>) -> Result<(), ErrorOperador> {
+        // validar usuario
+        let Some(usuario) = self.usuarios.get(&caller)
+        else { return Err(ErrorOperador::UsuarioNoRegistrado); };
+
+        if !usuario.es_vendedor() {
+            return Err(ErrorOperador::NoEsVendedor);
+        }
+
+        // validar operador
+        if operador == caller || self.usuarios.contains_key(&operador) || operador == self.owner || self.staff.contains(&operador) {
+            return Err(ErrorOperador::OperadorInvalido);
+        }
+
+        if self.es_socio_tienda(caller, operador) {
+            return Err(ErrorOperador::OperadorEsSocio);
+        }
+
+        let ya_autorizado = match self.operadores.get(&operador) {
+            Some(existente) if existente.vendedor != caller => return Err(ErrorOperador::OperadorDeOtroVendedor),
+            Some(_) => true,
+            None => false,
+        };
+
+        // validar permisos
+        if permisos.is_empty() || permisos.iter().enumerate().any(|(i, permiso)| permisos[..i].contains(permiso)) {
+            return Err(ErrorOperador::PermisosInvalidos);
+        }
+
+        // validar vencimiento
+        if vencimiento.is_some_and(|vencimiento| vencimiento <= timestamp) {
+            return Err(ErrorOperador::VencimientoInvalido);
+        }
+
+        if !ya_autorizado && self.operadores.values().filter(|o| o.vendedor == caller).count() >= MAX_OPERADORES_VENDEDOR {
+            return Err(ErrorOperador::MaximoOperadores);
+        }
+
+        self.operadores.insert(operador, Operador { vendedor: caller, permisos, vencimiento });
+
+        Ok(())
+    }
+
+    //
+
+    /// Revoca la autorización de un operador del vendedor.
+    ///
+    /// Devolverá error si la cuenta no es operador del caller o es socio de su tienda compartida.
+    pub fn _revocar_operador(&mut self, caller: AccountId, operador: AccountId) -> Result<(), ErrorOperador> {
+        let Some(existente) = self.operadores.get(&operador)
+        else { return Err(ErrorOperador::OperadorInexistente); };
+
+        if existente.vendedor != caller {
+            return Err(ErrorOperador::OperadorInexistente);
+        }
+
+        if self.es_socio_tienda(caller, operador) {
+            return Err(ErrorOperador::OperadorEsSocio);
+        }
+
+        self.operadores.remove(&operador);
+
+        Ok(())
+    }
+
+    //
+
+    /// Devuelve los operadores autorizados por el vendedor, incluidos los vencidos.
+    pub fn _ver_operadores(&self, caller: AccountId) -> Vec<(AccountId, Operador)> {
+        self.operadores.iter()
+            .filter(|(_, operador)| operador.vendedor == caller)
+            .map(|(id, operador)| (*id, operador.clone()))
+            .collect()
+    }
+
+    //
+
+    /// Devuelve true si la cuenta es socio de la tienda compartida del vendedor
+    fn es_socio_tienda(&self, vendedor: AccountId, cuenta: AccountId) -> bool {
+        self.tiendas_compartidas.get(&vendedor).is_some_and(|tienda| tienda.socios.contains(&cuenta))
+    }
+
+    /// Devuelve la cuenta en cuyo nombre actúa el caller: el vendedor que lo autorizó, si es un operador
+    /// con autorización vigente que incluye el permiso, o el propio caller en caso contrario.
+    pub(crate) fn actuar_como_vendedor(&self, timestamp: u64, caller: AccountId, permiso: PermisoOperador) -> AccountId {
+        if self.usuarios.contains_key(&caller) {
+            return caller;
+        }
+
+        match self.operadores.get(&caller) {
+            Some(operador) if operador.permite(timestamp, permiso) => operador.vendedor,
+            _ => caller,
+        }
+    }
+
+    /// Ídem actuar_como_vendedor, pero sólo si el caller opera para el vendedor indicado
+    /// (el de la publicación o el pedido sobre el que se actúa).
+    pub(crate) fn actuar_como_vendedor_de(&self, timestamp: u64, caller: AccountId, vendedor: AccountId, permiso: PermisoOperador) -> AccountId {
+        let cuenta = self.actuar_como_vendedor(timestamp, caller, permiso);
+        if cuenta == vendedor { cuenta } else { caller }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::{pedido::ErrorRetirarFondos, producto::{DatosProducto, ErrorIngresarStockProducto}, publicacion::ErrorModificarCantidadOfertada, usuario::RolDeSeleccion};
+
+    #[ink::test]
+    fn autorizar_y_revocar_operador() {
+        let mut contrato = RustaceoLibre::new(0);
+        let vendedor = AccountId::from([0x1; 32]);
+        let otro_vendedor = AccountId::from([0x2; 32]);
+        let operador = AccountId::from([0x3; 32]);
+        contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+        contrato._registrar_usuario(otro_vendedor, RolDeSeleccion::Vendedor).unwrap();
+        let permisos = Vec::from([PermisoOperador::Despachar]);
+
+        assert_eq!(contrato._autorizar_operador(0, operador, vendedor, permisos.clone(), None), Err(ErrorOperador::UsuarioNoRegistrado));
+        assert_eq!(contrato._autorizar_operador(0, vendedor, otro_vendedor, permisos.clone(), None), Err(ErrorOperador::OperadorInvalido));
+        assert_eq!(contrato._autorizar_operador(0, vendedor, operador, Vec::new(), None), Err(ErrorOperador::PermisosInvalidos));
+        assert_eq!(contrato._autorizar_operador(0, vendedor, operador, Vec::from([PermisoOperador::Despachar, PermisoOperador::Despachar]), None), Err(ErrorOperador::PermisosInvalidos));
+        assert_eq!(contrato._autorizar_operador(10, vendedor, operador, permisos.clone(), Some(10)), Err(ErrorOperador::VencimientoInvalido));
+        assert_eq!(contrato._autorizar_operador(0, vendedor, operador, permisos.clone(), Some(10)), Ok(()));
+        assert_eq!(contrato._autorizar_operador(0, otro_vendedor, operador, permisos.clone(), None), Err(ErrorOperador::OperadorDeOtroVendedor));
+
+        // la autorización vence
+        assert_eq!(contrato.actuar_como_vendedor(9, operador, PermisoOperador::Despachar), vendedor);
+        assert_eq!(contrato.actuar_como_vendedor(10, operador, PermisoOperador::Despachar), operador);
+        assert_eq!(contrato._ver_operadores(vendedor).len(), 1);
+
+        assert_eq!(contrato._revocar_operador(otro_vendedor, operador), Err(ErrorOperador::OperadorInexistente));
+        assert_eq!(contrato._revocar_operador(vendedor, operador), Ok(()));
+        assert_eq!(contrato._ver_operadores(vendedor), Vec::new());
+    }
+
+    #[ink::test]
+    fn operador_actua_solo_con_sus_permisos() {
+        let mut contrato = RustaceoLibre::new(0);
+        let vendedor = AccountId::from([0x1; 32]);
+        let comprador = AccountId::from([0x2; 32]);
+        let operador = AccountId::from([0x3; 32]);
+        contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+        contrato._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
+
+        let id_producto = contrato._registrar_producto(0, vendedor, DatosProducto::new("A".into(), "B".into(), 1), 10).unwrap();
+        contrato._staff_aprobar_producto(contrato.owner, id_producto).unwrap();
+        let id_publicacion = contrato._realizar_publicacion(0, vendedor, id_producto, 5, 100).unwrap();
+        let id_pedido = contrato._comprar_producto(0, comprador, id_publicacion, 1, 100).unwrap().id_nueva_transaccion;
+
+        contrato._autorizar_operador(0, vendedor, operador, Vec::from([PermisoOperador::GestionarStock, PermisoOperador::Despachar]), None).unwrap();
+
+        // gestiona el stock del vendedor
+        assert_eq!(contrato._ingresar_stock_producto(0, operador, id_producto, 3), Ok(8));
+        assert_eq!(contrato.usuarios.get(&vendedor).unwrap().obtener_stock_producto(&id_producto), Some(8));
+
+        // sin permiso sobre publicaciones
+        assert_eq!(contrato._modificar_cantidad_ofertada(0, operador, id_publicacion, 6), Err(ErrorModificarCantidadOfertada::UsuarioInexistente));
+
+        // despacha, pero no retira fondos
+        assert!(contrato._pedido_despachado(0, operador, id_pedido).is_ok());
+        assert_eq!(contrato._retirar_fondos(0, operador, id_pedido), Err(ErrorRetirarFondos::UsuarioNoRegistrado));
+
+        // revocado, deja de actuar
+        contrato._revocar_operador(vendedor, operador).unwrap();
+        assert_eq!(contrato._ingresar_stock_producto(0, operador, id_producto, 1), Err(ErrorIngresarStockProducto::UsuarioNoRegistrado));
+    }
+}
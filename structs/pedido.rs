@@ -1,6 +1,6 @@
-use ink::{prelude::vec::Vec, primitives::AccountId};
+use ink::{prelude::{string::String, vec::Vec}, primitives::AccountId};
 
-use crate::{rustaceo_libre::RustaceoLibre, structs::{producto::CategoriaProducto}};
+use crate::{rustaceo_libre::RustaceoLibre, structs::{alerta_stock::{AlertaStockBajo, AlertaStockBajoProducto}, movimiento_stock::TipoMovimientoStock, operador::PermisoOperador, usuario::MAX_LARGO_DIRECCION_CIFRADA}};
 
 //
 // estado pedido
@@ -19,6 +19,103 @@ pub enum EstadoPedido { // (u64 -> timestamp)
     Cancelado(u64),
 }
 
+//
+// seguimiento de envío
+//
+
+/// Longitud máxima (en bytes) del identificador del transportista
+pub const MAX_LARGO_TRANSPORTISTA: usize = 64;
+/// Longitud máxima (en bytes) del código de seguimiento o su hash
+pub const MAX_LARGO_CODIGO_SEGUIMIENTO: usize = 128;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub struct RegistroSeguimiento {
+    pub timestamp: u64,
+    pub transportista: String,
+    pub codigo_seguimiento: String, // puede ser el código en sí o su hash
+}
+
+impl RegistroSeguimiento {
+    pub fn new(timestamp: u64, transportista: String, codigo_seguimiento: String) -> Self {
+        Self {
+            timestamp,
+            transportista,
+            codigo_seguimiento,
+        }
+    }
+
+    /// Devuelve true si el transportista y el código no están vacíos ni exceden su longitud máxima
+    pub fn es_valido(&self) -> bool {
+        !self.transportista.is_empty()
+        && !self.codigo_seguimiento.is_empty()
+        && self.transportista.len() <= MAX_LARGO_TRANSPORTISTA
+        && self.codigo_seguimiento.len() <= MAX_LARGO_CODIGO_SEGUIMIENTO
+    }
+}
+
+//
+// dirección de entrega
+//
+
+/// Dirección de entrega adjunta a un pedido.
+/// 
+/// `cifrada` contiene la dirección cifrada para el vendedor, quien es el único capaz de leerla.
+/// `compromiso` es el hash Blake2x256 de la dirección en texto plano concatenada con una sal,
+/// lo que permite al Staff verificar una dirección en una disputa sin que la misma sea pública.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub struct DireccionEntrega {
+    pub cifrada: Vec<u8>,
+    pub compromiso: [u8; 32],
+}
+
+impl DireccionEntrega {
+    /// Devuelve true si la dirección cifrada no está vacía ni excede su tamaño máximo
+    pub fn es_valida(&self) -> bool {
+        !self.cifrada.is_empty() && self.cifrada.len() <= MAX_LARGO_DIRECCION_CIFRADA
+    }
+
+    /// Calcula el compromiso correspondiente a una dirección en texto plano y su sal
+    pub fn calcular_compromiso(direccion: &[u8], sal: &[u8]) -> [u8; 32] {
+        let mut entrada: Vec<u8> = Vec::with_capacity(direccion.len().saturating_add(sal.len()));
+        entrada.extend_from_slice(direccion);
+        entrada.extend_from_slice(sal);
+
+        let mut salida = [0u8; 32];
+        ink::env::hash_bytes::<ink::env::hash::Blake2x256>(&entrada, &mut salida);
+        salida
+    }
+}
+
+//
+// reembolsos
+//
+
+/// Longitud máxima (en bytes) del motivo de un reembolso
+pub const MAX_LARGO_MOTIVO_REEMBOLSO: usize = 256;
+
+/// Reembolso parcial otorgado por el vendedor al comprador sin cancelar el pedido
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub struct RegistroReembolso {
+    pub timestamp: u64,
+    pub valor: u128,
+    pub motivo: String,
+}
+
 //
 // pedido
 //
@@ -43,6 +140,15 @@ pub struct Pedido {
     pub calificacion_vendedor: Option<u8>,  // viceversa
     pub disputa: Option<u128>,
     pub primer_solicitud_cancelacion: Option<AccountId>, // almacena la id de quien solicitó la cancelación para verificar mutualidad
+    pub seguimiento: Vec<RegistroSeguimiento>, // historial de información de envío. el último registro es el vigente
+    pub direccion_entrega: Option<DireccionEntrega>,
+    pub cantidad_despachada: u32,
+    pub cantidad_recibida: u32,
+    pub cantidad_cancelada: u32, // unidades canceladas cuyo valor fue reembolsado al comprador
+    pub fondos_liberados: u128, // fondos ya transferidos al vendedor por unidades recibidas
+    pub fondos_reembolsados: u128, // fondos ya devueltos al comprador
+    pub ultima_recepcion: Option<u64>, // timestamp de la última recepción (total o parcial)
+    pub reembolsos: Vec<RegistroReembolso>, // historial de reembolsos parciales otorgados por el vendedor
 }
 
 //
@@ -64,9 +170,47 @@ impl Pedido {
             calificacion_comprador: None, // la calificación que dió el comprador
             calificacion_vendedor: None,  // ídem pero vendedor
             disputa: None,
-            primer_solicitud_cancelacion: None
+            primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         }
     }
+
+    /// Devuelve el registro de seguimiento vigente del pedido.
+    /// Devolverá None si el vendedor no brindó información de envío.
+    pub fn seguimiento_actual(&self) -> Option<&RegistroSeguimiento> {
+        self.seguimiento.last()
+    }
+
+    /// Devuelve el valor de cada unidad del pedido
+    pub fn valor_unitario(&self) -> u128 {
+        self.valor_total.checked_div(u128::from(self.cantidad_comprada)).unwrap_or(0) // safe cast: u32 -> u128
+    }
+
+    /// Devuelve la cantidad de unidades del pedido que no fueron canceladas
+    pub fn cantidad_vigente(&self) -> u32 {
+        self.cantidad_comprada.saturating_sub(self.cantidad_cancelada)
+    }
+
+    /// Devuelve la cantidad de unidades vigentes que aún no fueron despachadas
+    pub fn cantidad_pendiente_despacho(&self) -> u32 {
+        self.cantidad_vigente().saturating_sub(self.cantidad_despachada)
+    }
+
+    /// Devuelve los fondos del pedido que el contrato aún retiene:
+    /// el valor total menos lo liberado al vendedor y lo reembolsado al comprador.
+    pub fn fondos_en_custodia(&self) -> u128 {
+        self.valor_total
+            .saturating_sub(self.fondos_liberados)
+            .saturating_sub(self.fondos_reembolsados)
+    }
 }
 
 /// Verifica si se cumplen todas las políticas para la cancelación unilateral de un pedido.
@@ -75,6 +219,11 @@ fn politica_cancelacion_unilateral(timestamp: u64, pedido: &Pedido, caller: Acco
     if pedido.comprador != caller {
         return false;
     }
+
+    // con unidades despachadas, sólo se pueden cancelar unilateralmente las que aún no lo fueron
+    if pedido.cantidad_despachada > 0 {
+        return false;
+    }
     
     let EstadoPedido::Pendiente(pendiente_timestamp) = pedido.estado
     else { return false; };
@@ -111,6 +260,7 @@ pub enum ErrorComprarProducto {
     VendedorInexistente,
     StockInsuficiente,
     ValorTransferidoInsuficiente,
+    DireccionInvalida,
     Desconocido
 }
 
@@ -127,6 +277,25 @@ pub enum ErrorProductoDespachado {
     PedidoYaDespachado,
     PedidoCancelado,
     EstadoNoPendiente,
+    SeguimientoRequerido,
+    SeguimientoInvalido,
+    CantidadInvalida,
+}
+
+// actualizar seguimiento
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ErrorActualizarSeguimiento {
+    UsuarioNoRegistrado,
+    PedidoInexistente,
+    SoloVendedorPuede,
+    PedidoNoDespachado,
+    SeguimientoInvalido,
 }
 
 // producto recibido
@@ -144,6 +313,7 @@ pub enum ErrorProductoRecibido {
     PedidoYaRecibido,
     PedidoNoDespachado,
     PedidoCancelado,
+    CantidadInvalida,
 }
 
 // cancelar pedido
@@ -206,6 +376,48 @@ pub enum ErrorRetirarFondos {
     NoConvalidaPoliticaDeReclamo,
     FondosYaTransferidos,
     EstadoNoEsDespachado,
+    PlazoDeRetiroNoCumplido,
+    SinFondosDisponibles,
+    DevolucionEnCurso,
+    RequiereAprobacionSocios, // el vendedor es una tienda compartida
+}
+
+// cancelar unidades restantes
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ErrorCancelarUnidadesRestantes {
+    UsuarioNoRegistrado,
+    PedidoInexistente,
+    UsuarioNoParticipa,
+    PedidoYaRecibido,
+    PedidoYaCancelado,
+    DisputaEnCurso,
+    SinUnidadesPendientes,
+    NoConvalidaPoliticaDeCancelacion,
+}
+
+// reembolso parcial
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ErrorReembolsoParcial {
+    UsuarioNoRegistrado,
+    PedidoInexistente,
+    SoloVendedorPuede,
+    PedidoCancelado,
+    FondosYaTransferidos,
+    DisputaEnCurso,
+    ValorInvalido,
+    MotivoInvalido,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -225,10 +437,26 @@ pub enum ErrorCalificarPedido {
     CompradorInexistente,
 }
 
+// verificar dirección
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ErrorVerificarDireccion {
+    UsuarioNoStaff,
+    PedidoInexistente,
+    PedidoSinDireccion,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Default, Hash)]
 pub struct ResultadoComprarProducto {
     pub id_nueva_transaccion: u128,
-    pub monto_transferido_sobrante: u128
+    pub monto_transferido_sobrante: u128,
+    pub alerta_stock_bajo: Option<AlertaStockBajo>, // si la compra dejó la publicación en o por debajo de su umbral
+    pub alerta_stock_bajo_producto: Option<AlertaStockBajoProducto>, // si la reposición automática dejó el stock del producto en o por debajo de su umbral
 }
 
 impl RustaceoLibre {
@@ -240,6 +468,16 @@ impl RustaceoLibre {
     /// Puede dar error si el usuario no existe, no es comprador, la publicación no existe,
     /// el stock es insuficiente o el vendedor de la misma no existe.
     pub fn _comprar_producto(&mut self, timestamp: u64, caller: AccountId, id_publicacion: u128, cantidad: u32, valor_transferido: u128) -> Result<ResultadoComprarProducto, ErrorComprarProducto> {
+        self._comprar_producto_con_direccion(timestamp, caller, id_publicacion, cantidad, valor_transferido, None)
+    }
+
+    //
+
+    /// Ídem _comprar_producto, adjuntando opcionalmente la dirección de entrega cifrada para el vendedor
+    /// junto con su compromiso.
+    /// 
+    /// Puede dar los mismos errores que _comprar_producto o error si la dirección cifrada no es válida.
+    pub fn _comprar_producto_con_direccion(&mut self, timestamp: u64, caller: AccountId, id_publicacion: u128, cantidad: u32, valor_transferido: u128, direccion_entrega: Option<DireccionEntrega>) -> Result<ResultadoComprarProducto, ErrorComprarProducto> {
         // validar cantidad
         if cantidad == 0 {
             return Err(ErrorComprarProducto::CantidadCero);
@@ -285,6 +523,13 @@ impl RustaceoLibre {
         let Some(monto_transferido_sobrante) = valor_transferido.checked_sub(valor_total_compra)
         else { return Err(ErrorComprarProducto::ValorTransferidoInsuficiente); };
 
+        // validar dirección de entrega
+        if let Some(direccion) = &direccion_entrega {
+            if !direccion.es_valida() {
+                return Err(ErrorComprarProducto::DireccionInvalida);
+            }
+        }
+
         //
         // todo bien
         //
@@ -305,6 +550,8 @@ impl RustaceoLibre {
         //
 
         let mut publicacion = publicacion;
+        let id_producto = publicacion.producto;
+        let cantidad_ofertada_anterior = publicacion.cantidad_ofertada;
         publicacion.cantidad_ofertada = nuevo_stock_publicacion;
         self.publicaciones.insert(id_publicacion,publicacion);
 
@@ -313,10 +560,12 @@ impl RustaceoLibre {
         //
 
         let id_transaccion = self.next_id_pedidos();
-        let transaccion = Pedido::new(id_transaccion, timestamp, id_publicacion, cantidad, valor_total_compra, comprador.id, id_vendedor);
+        let mut transaccion = Pedido::new(id_transaccion, timestamp, id_publicacion, cantidad, valor_total_compra, comprador.id, id_vendedor);
+        transaccion.direccion_entrega = direccion_entrega;
 
         // añadir compra al mapping de compras
         self.pedidos.insert(id_transaccion, transaccion);
+        self.registrar_movimiento_stock(timestamp, id_vendedor, id_producto, TipoMovimientoStock::Venta(id_transaccion), cantidad);
 
         //
         // actualizar compras al comprador
@@ -336,17 +585,30 @@ impl RustaceoLibre {
 
         self.usuarios.insert(vendedor.id,vendedor);
 
+        //
+        // reposición automática y alerta de stock bajo
+        //
+
+        let stock_vendedor_anterior = self.usuarios.get(&id_vendedor).and_then(|v| v.obtener_stock_producto(&id_producto));
+        let alerta_stock_bajo_producto = match stock_vendedor_anterior {
+            Some(anterior) if self.reponer_publicacion(timestamp, id_publicacion) => self.alerta_stock_bajo_producto(id_vendedor, id_producto, anterior),
+            _ => None,
+        };
+        let alerta_stock_bajo = self.alerta_stock_bajo_publicacion(id_publicacion, cantidad_ofertada_anterior);
+
         // fin
         Ok( ResultadoComprarProducto {
             id_nueva_transaccion: id_transaccion,
-            monto_transferido_sobrante
+            monto_transferido_sobrante,
+            alerta_stock_bajo,
+            alerta_stock_bajo_producto,
         })
     }
 
     //
 
-    /// El vendedor puede retirar los fondos de una compra si fue recibida hace al menos tres días
-    /// y no existe una disputa en curso en contra del vendedor,
+    /// El vendedor puede retirar los fondos de una compra si fue recibida hace al menos tres días,
+    /// o su plazo de devolución si es mayor, y no existe una disputa en curso en contra del vendedor,
     /// o ejecutar la política de reclamo si el comprador no la marca como recibida.
     /// 
     /// Política de reclamo:
@@ -356,8 +618,20 @@ impl RustaceoLibre {
     /// sin necesidad de consentimiento ni voluntad del comprador.
     /// 
     /// Puede dar error si el usuario no está registrado, la transacción no existe,
-    /// el usuario no es el vendedor de la publicación o el tiempo pasado no condice con la política de reclamo
+    /// el usuario no es el vendedor de la publicación, el tiempo pasado no condice con la política de reclamo,
+    /// el pedido tiene una devolución en curso o el vendedor es una tienda compartida,
+    /// cuyos retiros requieren la aprobación de sus socios.
     pub fn _retirar_fondos(&mut self, timestamp: u64, caller: AccountId, id_compra: u128) -> Result<u128, ErrorRetirarFondos> {
+        // los retiros de una tienda compartida requieren la aprobación de sus socios
+        if self.tiendas_compartidas.contains_key(&caller) {
+            return Err(ErrorRetirarFondos::RequiereAprobacionSocios);
+        }
+
+        self.liberar_fondos_pedido(timestamp, caller, id_compra)
+    }
+
+    /// Lógica de _retirar_fondos, también usada al ejecutar un retiro aprobado por los socios de una tienda compartida.
+    pub(crate) fn liberar_fondos_pedido(&mut self, timestamp: u64, caller: AccountId, id_compra: u128) -> Result<u128, ErrorRetirarFondos> {
         // validar usuario
         if !self.usuarios.contains_key(&caller) {
             return Err(ErrorRetirarFondos::UsuarioNoRegistrado);
@@ -378,6 +652,11 @@ impl RustaceoLibre {
             return Err(ErrorRetirarFondos::FondosYaTransferidos);
         }
 
+        // validar que no exista devolución en curso
+        if self.pedido_con_devolucion_en_curso(id_compra) {
+            return Err(ErrorRetirarFondos::DevolucionEnCurso);
+        }
+
         //
         // retiro sin politica de reclamo
         //
@@ -391,7 +670,7 @@ impl RustaceoLibre {
             puede_retirar_sin_pdr = false;
             0
         };
-        let tiempo_necesario = 259_200_000u64;  // 1000*60*60*24*3 = 3 días
+        let tiempo_necesario = self.plazo_retiro_fondos(pedido.vendedor); // 3 días o el plazo de devolución
         let tiempo_transcurrido = if let Some(tiempo_transcurrido) = timestamp.checked_sub(timestamp_recibido) {
             tiempo_transcurrido
         } else {
@@ -422,13 +701,14 @@ impl RustaceoLibre {
         }
 
         if puede_retirar_sin_pdr {
-            // no existe ninguna disputa, pasaron 3 días o más. puede retirar
+            // no existe ninguna disputa, pasaron 3 días (o el plazo de devolución) o más. puede retirar
 
-            let valor_compra = pedido.valor_total;
+            let valor_compra = pedido.fondos_en_custodia();
 
             // guardar compra
             let mut compra = pedido;
             compra.fondos_fueron_transferidos = true;
+            compra.fondos_liberados = compra.fondos_liberados.saturating_add(valor_compra);
             compra.estado = EstadoPedido::Recibido(timestamp);
             self.pedidos.insert(id_compra, compra);
 
@@ -456,11 +736,12 @@ impl RustaceoLibre {
             return Err(ErrorRetirarFondos::NoConvalidaPoliticaDeReclamo);
         }
 
-        let valor_compra = pedido.valor_total;
+        let valor_compra = pedido.fondos_en_custodia();
 
         // guardar compra
         let mut compra = pedido;
         compra.fondos_fueron_transferidos = true;
+        compra.fondos_liberados = compra.fondos_liberados.saturating_add(valor_compra);
         compra.estado = EstadoPedido::Recibido(timestamp);
         self.pedidos.insert(id_compra, compra);
 
@@ -470,12 +751,130 @@ impl RustaceoLibre {
 
     //
 
+    /// El vendedor puede retirar los fondos correspondientes a las unidades de un pedido
+    /// que el comprador haya confirmado como recibidas, siempre que la última recepción
+    /// haya sido hace al menos tres días, o su plazo de devolución si es mayor, y no exista una disputa en curso.
+    /// 
+    /// Devuelve el valor liberado, que debería transferirse al vendedor en lib.rs.
+    /// Puede dar error si el usuario no está registrado, el pedido no existe, no es el vendedor,
+    /// los fondos ya fueron transferidos, existe una disputa en curso, no pasaron tres días (o el plazo de devolución)
+    /// desde la última recepción, no hay fondos disponibles para retirar, existe una devolución en curso
+    /// o el vendedor es una tienda compartida.
+    pub fn _retirar_fondos_parciales(&mut self, timestamp: u64, caller: AccountId, id_compra: u128) -> Result<u128, ErrorRetirarFondos> {
+        // los retiros de una tienda compartida requieren la aprobación de sus socios
+        if self.tiendas_compartidas.contains_key(&caller) {
+            return Err(ErrorRetirarFondos::RequiereAprobacionSocios);
+        }
+
+        self.liberar_fondos_parciales_pedido(timestamp, caller, id_compra)
+    }
+
+    /// Lógica de _retirar_fondos_parciales, también usada al ejecutar un retiro aprobado por los socios de una tienda compartida.
+    pub(crate) fn liberar_fondos_parciales_pedido(&mut self, timestamp: u64, caller: AccountId, id_compra: u128) -> Result<u128, ErrorRetirarFondos> {
+        // validar usuario
+        if !self.usuarios.contains_key(&caller) {
+            return Err(ErrorRetirarFondos::UsuarioNoRegistrado);
+        }
+
+        // validar pedido
+        let Some(pedido) = self.pedidos.get(&id_compra)
+        else { return Err(ErrorRetirarFondos::PedidoInexistente); };
+
+        // validar usuario es vendedor
+        if caller != pedido.vendedor {
+            return Err(ErrorRetirarFondos::SoloVendedorPuede);
+        }
+
+        // validar que los fondos no hayan sido ya transferidos
+        if pedido.fondos_fueron_transferidos || matches!(pedido.estado, EstadoPedido::Cancelado(_)) {
+            return Err(ErrorRetirarFondos::FondosYaTransferidos);
+        }
+
+        // validar que no exista devolución en curso
+        if self.pedido_con_devolucion_en_curso(id_compra) {
+            return Err(ErrorRetirarFondos::DevolucionEnCurso);
+        }
+
+        // validar que no exista disputa en curso
+        if let Some(id_disputa) = pedido.disputa {
+            if self.disputas_en_curso.contains_key(&id_disputa) {
+                return Err(ErrorRetirarFondos::DisputaEnCurso);
+            }
+        }
+
+        // validar tiempo transcurrido desde la última recepción
+        let Some(ultima_recepcion) = pedido.ultima_recepcion
+        else { return Err(ErrorRetirarFondos::SinFondosDisponibles); };
+
+        let tiempo_necesario = self.plazo_retiro_fondos(pedido.vendedor); // 3 días o el plazo de devolución
+        let Some(tiempo_transcurrido) = timestamp.checked_sub(ultima_recepcion)
+        else { return Err(ErrorRetirarFondos::PlazoDeRetiroNoCumplido); };
+
+        if tiempo_transcurrido < tiempo_necesario {
+            return Err(ErrorRetirarFondos::PlazoDeRetiroNoCumplido);
+        }
+
+        // calcular fondos correspondientes a las unidades recibidas y aún no liberados
+        let valor_recibido = pedido.valor_unitario().saturating_mul(u128::from(pedido.cantidad_recibida)); // safe cast: u32 -> u128
+        let disponible = valor_recibido.saturating_sub(pedido.fondos_liberados).min(pedido.fondos_en_custodia());
+
+        if disponible == 0 {
+            return Err(ErrorRetirarFondos::SinFondosDisponibles);
+        }
+
+        // guardar pedido
+        let mut pedido = pedido.clone();
+        pedido.fondos_liberados = pedido.fondos_liberados.saturating_add(disponible);
+        if pedido.fondos_en_custodia() == 0 {
+            pedido.fondos_fueron_transferidos = true;
+        }
+        self.pedidos.insert(pedido.id, pedido);
+
+        // devolver Ok(valor) debería transferir los fondos en lib.rs
+        Ok(disponible)
+    }
+
+    //
+
     /// Si el pedido indicada está pendiente y el usuario es el vendedor, se establece como recibida.
     /// 
     /// Puede dar error si el usuario no está registrado, el pedido no existe,
-    /// no está pendiente, ya fue recibido, no es el vendedor quien intenta despacharlo
-    /// o ya fue cancelada.
+    /// no está pendiente, ya fue recibido, no es el vendedor quien intenta despacharlo,
+    /// ya fue cancelada o el pedido requiere información de seguimiento.
     pub fn _pedido_despachado(&mut self, timestamp: u64, caller: AccountId, id_venta: u128) -> Result<(), ErrorProductoDespachado> {
+        self.despachar_pedido(timestamp, caller, id_venta, None, None)
+    }
+
+    //
+
+    /// Ídem _pedido_despachado, adjuntando el transportista y el código de seguimiento (o su hash) del envío.
+    /// 
+    /// Puede dar los mismos errores que _pedido_despachado o error si la información de seguimiento no es válida.
+    pub fn _pedido_despachado_con_seguimiento(&mut self, timestamp: u64, caller: AccountId, id_venta: u128, transportista: String, codigo_seguimiento: String) -> Result<(), ErrorProductoDespachado> {
+        let registro = RegistroSeguimiento::new(timestamp, transportista, codigo_seguimiento);
+        self.despachar_pedido(timestamp, caller, id_venta, None, Some(registro))
+    }
+
+    //
+
+    /// Despacha una parte de las unidades pendientes de despacho de un pedido,
+    /// adjuntando opcionalmente el transportista y el código de seguimiento del envío.
+    /// El pedido se establece como despachado cuando la totalidad de sus unidades vigentes lo fueron.
+    /// 
+    /// Puede dar los mismos errores que _pedido_despachado_con_seguimiento
+    /// o error si la cantidad es cero o mayor a la cantidad pendiente de despacho.
+    pub fn _pedido_despachado_parcial(&mut self, timestamp: u64, caller: AccountId, id_venta: u128, cantidad: u32, seguimiento: Option<(String, String)>) -> Result<(), ErrorProductoDespachado> {
+        let registro = seguimiento.map(|(transportista, codigo_seguimiento)| RegistroSeguimiento::new(timestamp, transportista, codigo_seguimiento));
+        self.despachar_pedido(timestamp, caller, id_venta, Some(cantidad), registro)
+    }
+
+    /// Lógica común a _pedido_despachado, _pedido_despachado_con_seguimiento y _pedido_despachado_parcial.
+    /// Si no se indica cantidad, se despachan todas las unidades pendientes.
+    fn despachar_pedido(&mut self, timestamp: u64, caller: AccountId, id_venta: u128, cantidad: Option<u32>, seguimiento: Option<RegistroSeguimiento>) -> Result<(), ErrorProductoDespachado> {
+        // un operador autorizado actúa en nombre del vendedor
+        let caller = self.pedidos.get(&id_venta)
+            .map_or(caller, |p| self.actuar_como_vendedor_de(timestamp, caller, p.vendedor, PermisoOperador::Despachar));
+
         // validar usuario
         let Some(usuario) = self.usuarios.get(&caller).cloned()
         else { return Err(ErrorProductoDespachado::UsuarioNoRegistrado); };
@@ -512,15 +911,136 @@ impl RustaceoLibre {
             return Err(ErrorProductoDespachado::EstadoNoPendiente);
         }
 
+        // validar información de seguimiento
+        match &seguimiento {
+            Some(registro) => if !registro.es_valido() {
+                return Err(ErrorProductoDespachado::SeguimientoInvalido);
+            },
+            None => if self.pedido_requiere_seguimiento(venta) {
+                return Err(ErrorProductoDespachado::SeguimientoRequerido);
+            },
+        }
+
+        // validar cantidad
+        let pendiente_despacho = venta.cantidad_pendiente_despacho();
+        let cantidad_despachada = match cantidad {
+            Some(cantidad) => {
+                if cantidad == 0 || cantidad > pendiente_despacho {
+                    return Err(ErrorProductoDespachado::CantidadInvalida);
+                }
+                venta.cantidad_despachada.saturating_add(cantidad)
+            },
+            None => venta.cantidad_vigente(),
+        };
+
         // hacer cambios y guardar
         let mut venta = venta.clone();
-        venta.estado = EstadoPedido::Despachado(timestamp);
+        venta.cantidad_despachada = cantidad_despachada;
+        if venta.cantidad_pendiente_despacho() == 0 {
+            venta.estado = EstadoPedido::Despachado(timestamp);
+        }
+        if let Some(registro) = seguimiento {
+            venta.seguimiento.push(registro);
+        }
         self.pedidos.insert(venta.id, venta);
 
         // fin
         Ok(())
     }
 
+    /// Devuelve true si el pedido debe despacharse con información de seguimiento,
+    /// ya sea por la categoría de su producto o por superar el valor mínimo establecido por el Staff.
+    pub fn pedido_requiere_seguimiento(&self, pedido: &Pedido) -> bool {
+        // por valor
+        if let Some(valor_minimo) = self.valor_minimo_seguimiento_obligatorio {
+            if pedido.valor_total >= valor_minimo {
+                return true;
+            }
+        }
+
+        // por categoría
+        if self.categorias_seguimiento_obligatorio.is_empty() {
+            return false;
+        }
+
+        let Some(publicacion) = self.publicaciones.get(&pedido.publicacion)
+        else { return false; };
+
+        let Some(producto) = self.productos.get(&publicacion.producto)
+        else { return false; };
+
+        self.categorias_seguimiento_obligatorio.iter()
+            .any(|categoria| self.pertenece_a_categoria(&producto.categoria, categoria))
+    }
+
+    //
+
+    /// Sólo ejecutable por el Staff u OWNER.
+    /// Establece qué pedidos deben despacharse obligatoriamente con información de seguimiento:
+    /// aquellos cuyo producto pertenezca a alguna de las categorías indicadas (o a sus subcategorías)
+    /// o cuyo valor total sea igual o mayor al valor mínimo, si se indica.
+    /// 
+    /// Devolverá true si la operación fue exitosa o false si caller no es Staff ni OWNER.
+    pub fn _staff_establecer_politica_seguimiento(&mut self, caller: AccountId, categorias: Vec<u128>, valor_minimo: Option<u128>) -> bool {
+        if !self.staff.contains(&caller) && caller != self.owner {
+            return false;
+        }
+
+        self.categorias_seguimiento_obligatorio = categorias;
+        self.valor_minimo_seguimiento_obligatorio = valor_minimo;
+        true
+    }
+
+    //
+
+    /// Permite al vendedor actualizar la información de seguimiento de un pedido despachado,
+    /// total o parcialmente. El registro anterior se conserva en el historial de seguimiento del pedido.
+    /// 
+    /// Puede dar error si el usuario no está registrado, el pedido no existe,
+    /// no es el vendedor quien lo actualiza, el pedido no tiene unidades despachadas o la información no es válida.
+    pub fn _actualizar_seguimiento(&mut self, timestamp: u64, caller: AccountId, id_venta: u128, transportista: String, codigo_seguimiento: String) -> Result<(), ErrorActualizarSeguimiento> {
+        // un operador autorizado actúa en nombre del vendedor
+        let caller = self.pedidos.get(&id_venta)
+            .map_or(caller, |p| self.actuar_como_vendedor_de(timestamp, caller, p.vendedor, PermisoOperador::Despachar));
+
+        // validar usuario
+        if !self.usuarios.contains_key(&caller) {
+            return Err(ErrorActualizarSeguimiento::UsuarioNoRegistrado);
+        }
+
+        // validar pedido
+        let Some(pedido) = self.pedidos.get(&id_venta)
+        else { return Err(ErrorActualizarSeguimiento::PedidoInexistente); };
+
+        // validar caller == vendedor
+        if pedido.vendedor != caller {
+            return Err(ErrorActualizarSeguimiento::SoloVendedorPuede);
+        }
+
+        // validar estado: un pedido pendiente puede tener unidades despachadas parcialmente
+        let despachado = match pedido.estado {
+            EstadoPedido::Despachado(_) => true,
+            EstadoPedido::Pendiente(_) => pedido.cantidad_despachada > 0,
+            EstadoPedido::Recibido(_) | EstadoPedido::Cancelado(_) => false,
+        };
+        if !despachado {
+            return Err(ErrorActualizarSeguimiento::PedidoNoDespachado);
+        }
+
+        // validar registro
+        let registro = RegistroSeguimiento::new(timestamp, transportista, codigo_seguimiento);
+        if !registro.es_valido() {
+            return Err(ErrorActualizarSeguimiento::SeguimientoInvalido);
+        }
+
+        // guardar
+        let mut pedido = pedido.clone();
+        pedido.seguimiento.push(registro);
+        self.pedidos.insert(pedido.id, pedido);
+
+        Ok(())
+    }
+
     //
 
     /// Si el pedido indicado fue despachado y el usuario es el comprador, se establece como recibido.
@@ -529,6 +1049,23 @@ impl RustaceoLibre {
     /// la compra no fue despachada, ya fue recibida, no es el comprador quien intenta recibirlo
     /// o ya fue cancelado.
     pub fn _pedido_recibido(&mut self, timestamp: u64, caller: AccountId, id_compra: u128) -> Result<(), ErrorProductoRecibido> {
+        self.recibir_pedido(timestamp, caller, id_compra, None)
+    }
+
+    //
+
+    /// El comprador confirma la recepción de una parte de las unidades ya despachadas de un pedido.
+    /// El pedido se establece como recibido cuando la totalidad de sus unidades vigentes lo fueron.
+    /// 
+    /// Puede dar los mismos errores que _pedido_recibido
+    /// o error si la cantidad es cero o mayor a la cantidad despachada pendiente de recepción.
+    pub fn _pedido_recibido_parcial(&mut self, timestamp: u64, caller: AccountId, id_compra: u128, cantidad: u32) -> Result<(), ErrorProductoRecibido> {
+        self.recibir_pedido(timestamp, caller, id_compra, Some(cantidad))
+    }
+
+    /// Lógica común a _pedido_recibido y _pedido_recibido_parcial.
+    /// Si no se indica cantidad, se reciben todas las unidades vigentes.
+    fn recibir_pedido(&mut self, timestamp: u64, caller: AccountId, id_compra: u128, cantidad: Option<u32>) -> Result<(), ErrorProductoRecibido> {
         // verificar usuario
         let Some(usuario) = self.usuarios.get(&caller).cloned()
         else { return Err(ErrorProductoRecibido::UsuarioNoRegistrado); };
@@ -550,16 +1087,38 @@ impl RustaceoLibre {
             return Err(ErrorProductoRecibido::SoloCompradorPuede);
         }
 
-        // último chequeo: verificar que el pedido haya sido despachado
+        // verificar que el pedido haya sido despachado
+        // un pedido pendiente puede tener unidades despachadas parcialmente
         match pedido.estado {
-            EstadoPedido::Pendiente(_) => return Err(ErrorProductoRecibido::PedidoNoDespachado),
+            EstadoPedido::Pendiente(_) => if cantidad.is_none() || pedido.cantidad_despachada == 0 {
+                return Err(ErrorProductoRecibido::PedidoNoDespachado);
+            },
             EstadoPedido::Despachado(_) => (),
             EstadoPedido::Recibido(_) => return Err(ErrorProductoRecibido::PedidoYaRecibido),
             EstadoPedido::Cancelado(_) => return Err(ErrorProductoRecibido::PedidoCancelado),
         }
 
+        // último chequeo: verificar cantidad
+        let cantidad_recibida = match cantidad {
+            Some(cantidad) => {
+                let pendiente_recepcion = pedido.cantidad_despachada.saturating_sub(pedido.cantidad_recibida);
+                if cantidad == 0 || cantidad > pendiente_recepcion {
+                    return Err(ErrorProductoRecibido::CantidadInvalida);
+                }
+                pedido.cantidad_recibida.saturating_add(cantidad)
+            },
+            None => pedido.cantidad_vigente(),
+        };
+
         let mut compra = pedido.clone();
-        compra.estado = EstadoPedido::Recibido(timestamp);
+        if cantidad.is_none() {
+            compra.cantidad_despachada = compra.cantidad_vigente();
+        }
+        compra.cantidad_recibida = cantidad_recibida;
+        compra.ultima_recepcion = Some(timestamp);
+        if compra.cantidad_recibida >= compra.cantidad_vigente() {
+            compra.estado = EstadoPedido::Recibido(timestamp);
+        }
         self.pedidos.insert(compra.id, compra);
 
         Ok(())
@@ -636,127 +1195,363 @@ impl RustaceoLibre {
             return Ok(())
         }
 
-        // caller != compra.comprador && caller != compra.vendedor
-        Err(ErrorCalificarPedido::UsuarioNoParticipa)
+        // caller != compra.comprador && caller != compra.vendedor
+        Err(ErrorCalificarPedido::UsuarioNoParticipa)
+    }
+
+    //
+
+    /// Cancela el pedido si ambos participantes del mismo ejecutan esta misma función
+    /// y si éste no fue recibida ni ya cancelada.
+    /// Entrega automáticamente los fondos de la compra al comprador y el stock al vendedor.
+    /// Si el comprador ya recibió parte de las unidades, sólo se cancelan las no recibidas:
+    /// se le reembolsa su valor y el vendedor conserva el de las recibidas.
+    /// 
+    /// Política de cancelación unilateral:
+    ///   Si el pedido fue realizado hace más de 14 días y aún no fue despachado,
+    ///   el comprador puede cancelar el mismo de forma unánime y recuperar los fondos.
+    ///   Si el vendedor ya despachó parte de las unidades, sólo se cancelan las aún no despachadas
+    ///   y se le reembolsa su valor; las despachadas siguen su curso.
+    /// 
+    /// Devuelve error si el usuario o pedido no existen, si el usuario no participa en el pedido,
+    /// si el pedido ya fue cancelado o recibido y si quien solicita la cancelación ya la solicitó antes.
+    pub fn _cancelar_pedido(&mut self, timestamp: u64, caller: AccountId, id_pedido: u128) -> Result<Option<(AccountId, u128)>, ErrorCancelarPedido> {
+        // validar usuario
+        let Some(_) = self.usuarios.get(&caller)
+        else { return Err(ErrorCancelarPedido::UsuarioNoRegistrado); };
+
+        // validar compra #2
+        let Some(pedido) = self.pedidos.get(&id_pedido)
+        else { return Err(ErrorCancelarPedido::PedidoInexistente); };
+
+        // validar comprador
+        if pedido.comprador != caller && pedido.vendedor != caller {
+            return Err(ErrorCancelarPedido::UsuarioNoParticipa);
+        }
+
+        // validar estado
+        match pedido.estado {
+            EstadoPedido::Pendiente(_) | EstadoPedido::Despachado(_) => (),
+            EstadoPedido::Recibido(_) => return Err(ErrorCancelarPedido::PedidoYaRecibido),
+            EstadoPedido::Cancelado(_) => return Err(ErrorCancelarPedido::PedidoYaCancelado),
+        }
+
+        // política de cancelación unilateral
+        //    para este caso, las verificaciones de existencia de solicitud de cancelacion
+        //    o la mutualidad no son necesarias
+        if politica_cancelacion_unilateral(timestamp, pedido, caller) {
+            let pedido = pedido.clone();
+
+            // modificar producto: quitar venta
+            if let Some(publicacion) = self.publicaciones.get(&pedido.publicacion) {
+                if let Some(mut producto) = self.productos.get(&publicacion.producto).cloned() {
+                    if let Some(nueva_cant_ventas) = producto.ventas.checked_sub(1) {
+                        producto.ventas = nueva_cant_ventas;
+                        self.productos.insert(publicacion.producto, producto);
+                    }
+                }
+            }
+
+            // fin. se devolverán fondos en lib.rs
+            return Ok(Some(self.cancelar_unidades_no_recibidas(timestamp, pedido)));
+        }
+
+        // despachado parcialmente: el comprador cancela por su cuenta sólo las unidades aún no despachadas,
+        // salvo que el vendedor ya haya solicitado cancelar el pedido
+        if pedido.comprador == caller && matches!(pedido.estado, EstadoPedido::Pendiente(_))
+            && pedido.cantidad_despachada > 0 && pedido.primer_solicitud_cancelacion.is_none() {
+            return Ok(Some(self.cancelar_unidades_no_despachadas(timestamp, pedido.clone())));
+        }
+    
+        //
+        // validar si ya existe una solicitud de cancelación
+        //
+
+        let mut pedido = pedido.clone();
+        let Some(primer_solicitud_cancelacion) = pedido.primer_solicitud_cancelacion 
+        else {
+            pedido.primer_solicitud_cancelacion = Some(caller);
+            self.pedidos.insert(pedido.id, pedido);
+            return Ok(None);
+        };
+
+        //
+        // asegurar que exista mutualidad
+        //
+
+        if primer_solicitud_cancelacion == caller {
+            return Err(ErrorCancelarPedido::EsperandoConfirmacionMutua);
+        }
+
+        // modificar producto: quitar venta, salvo que el comprador haya recibido unidades
+        if pedido.cantidad_recibida == 0 {
+            if let Some(publicacion) = self.publicaciones.get(&pedido.publicacion) {
+                if let Some(mut producto) = self.productos.get(&publicacion.producto).cloned() {
+                    if let Some(nueva_cant_ventas) = producto.ventas.checked_add(1) {
+                        producto.ventas = nueva_cant_ventas;
+                        self.productos.insert(publicacion.producto, producto);
+                    }
+                }
+            }
+        }
+
+        // fin. se devolverán fondos en lib.rs
+        Ok(Some(self.cancelar_unidades_no_recibidas(timestamp, pedido)))
+    }
+
+    /// Cancela las unidades del pedido que el comprador no recibió.
+    /// Las unidades aún no despachadas vuelven a la publicación y se reembolsa al comprador el valor de las no recibidas.
+    /// El valor de las unidades recibidas sigue en custodia para que el vendedor lo retire.
+    /// Si el comprador no recibió ninguna unidad, el pedido se cancela y se le reembolsan todos los fondos en custodia.
+    /// 
+    /// Devuelve el comprador y el valor a reembolsarle.
+    fn cancelar_unidades_no_recibidas(&mut self, timestamp: u64, mut pedido: Pedido) -> (AccountId, u128) {
+        let no_recibidas = pedido.cantidad_vigente().saturating_sub(pedido.cantidad_recibida);
+        let no_despachadas = pedido.cantidad_pendiente_despacho();
+
+        // modificar publicación: devolver stock no despachado
+        if let Some(id_producto) = self.devolver_unidades_no_despachadas(timestamp, &pedido) {
+            // las unidades despachadas que el comprador no recibió no vuelven a la publicación
+            let en_transito = no_recibidas.saturating_sub(no_despachadas);
+            self.registrar_movimiento_stock(timestamp, pedido.vendedor, id_producto, TipoMovimientoStock::CanceladaEnTransito(pedido.id), en_transito);
+        } // si la publicacion no existe, el stock se pierde. para evitarlo debo agregar "id_producto" a compra
+
+        let reembolso = if pedido.cantidad_recibida == 0 {
+            pedido.fondos_en_custodia()
+        } else {
+            pedido.valor_unitario()
+                .saturating_mul(u128::from(no_recibidas)) // safe cast: u32 -> u128
+                .min(pedido.fondos_en_custodia())
+        };
+
+        // modificar compra
+        pedido.cantidad_cancelada = pedido.cantidad_cancelada.saturating_add(no_recibidas);
+        pedido.fondos_reembolsados = pedido.fondos_reembolsados.saturating_add(reembolso);
+        if pedido.cantidad_recibida == 0 {
+            pedido.estado = EstadoPedido::Cancelado(timestamp);
+            pedido.fondos_fueron_transferidos = true;
+        } else {
+            // las unidades vigentes son las recibidas: el vendedor podrá retirar su valor
+            pedido.estado = EstadoPedido::Recibido(pedido.ultima_recepcion.unwrap_or(timestamp));
+            pedido.fondos_fueron_transferidos = pedido.fondos_en_custodia() == 0;
+        }
+
+        let id_comprador = pedido.comprador;
+        self.pedidos.insert(pedido.id, pedido);
+
+        (id_comprador, reembolso)
+    }
+
+    /// Devuelve a la publicación las unidades del pedido aún no despachadas.
+    /// Devuelve el producto de la publicación, o None si la publicación no existe.
+    fn devolver_unidades_no_despachadas(&mut self, timestamp: u64, pedido: &Pedido) -> Option<u128> {
+        let no_despachadas = pedido.cantidad_pendiente_despacho();
+        let mut publicacion = self.publicaciones.get(&pedido.publicacion).cloned()?;
+        let id_producto = publicacion.producto;
+
+        if let Some(nueva_cantidad_ofertada) = publicacion.cantidad_ofertada.checked_add(no_despachadas) {
+            // modificar e insertar publicación con nueva cantidad ofertada
+            publicacion.cantidad_ofertada = nueva_cantidad_ofertada;
+            self.publicaciones.insert(pedido.publicacion, publicacion);
+            self.registrar_movimiento_stock(timestamp, pedido.vendedor, id_producto, TipoMovimientoStock::Cancelacion(pedido.id), no_despachadas);
+        }
+
+        Some(id_producto)
+    }
+
+    //
+
+    /// Sólo ejecutable por el Staff u OWNER.
+    /// Verifica que la dirección en texto plano y la sal brindadas correspondan al compromiso
+    /// de la dirección de entrega del pedido. Pensado para ser consultado sin realizar una transacción,
+    /// de forma que la dirección nunca se haga pública.
+    /// 
+    /// Devuelve true si la dirección corresponde al compromiso.
+    /// Devolverá error si caller no es Staff, el pedido no existe o no tiene dirección de entrega.
+    pub fn _staff_verificar_direccion(&self, caller: AccountId, id_pedido: u128, direccion: Vec<u8>, sal: Vec<u8>) -> Result<bool, ErrorVerificarDireccion> {
+        if !self.staff.contains(&caller) && caller != self.owner {
+            return Err(ErrorVerificarDireccion::UsuarioNoStaff);
+        }
+
+        let Some(pedido) = self.pedidos.get(&id_pedido)
+        else { return Err(ErrorVerificarDireccion::PedidoInexistente); };
+
+        let Some(direccion_entrega) = &pedido.direccion_entrega
+        else { return Err(ErrorVerificarDireccion::PedidoSinDireccion); };
+
+        Ok(DireccionEntrega::calcular_compromiso(&direccion, &sal) == direccion_entrega.compromiso)
+    }
+
+    //
+
+    /// Cancela las unidades de un pedido que aún no fueron despachadas,
+    /// devolviéndolas a la publicación y reembolsando su valor proporcional al comprador.
+    /// 
+    /// El vendedor puede hacerlo en cualquier momento. El comprador sólo si pasaron al menos
+    /// 14 días desde que realizó el pedido. Si ninguna unidad fue despachada, el pedido se cancela.
+    /// 
+    /// Devuelve el comprador y el valor a reembolsarle, que debería transferirse en lib.rs.
+    /// Devolverá error si el usuario o pedido no existen, si el usuario no participa en el pedido,
+    /// si el pedido ya fue recibido o cancelado, si existe una disputa en curso,
+    /// si no hay unidades pendientes de despacho o si el comprador no convalida la política de cancelación.
+    pub fn _cancelar_unidades_restantes(&mut self, timestamp: u64, caller: AccountId, id_pedido: u128) -> Result<(AccountId, u128), ErrorCancelarUnidadesRestantes> {
+        // validar usuario
+        if !self.usuarios.contains_key(&caller) {
+            return Err(ErrorCancelarUnidadesRestantes::UsuarioNoRegistrado);
+        }
+
+        // validar pedido
+        let Some(pedido) = self.pedidos.get(&id_pedido)
+        else { return Err(ErrorCancelarUnidadesRestantes::PedidoInexistente); };
+
+        // validar participación
+        if pedido.comprador != caller && pedido.vendedor != caller {
+            return Err(ErrorCancelarUnidadesRestantes::UsuarioNoParticipa);
+        }
+
+        // validar estado
+        match pedido.estado {
+            EstadoPedido::Pendiente(_) => (),
+            EstadoPedido::Despachado(_) => return Err(ErrorCancelarUnidadesRestantes::SinUnidadesPendientes),
+            EstadoPedido::Recibido(_) => return Err(ErrorCancelarUnidadesRestantes::PedidoYaRecibido),
+            EstadoPedido::Cancelado(_) => return Err(ErrorCancelarUnidadesRestantes::PedidoYaCancelado),
+        }
+
+        // validar disputa
+        if let Some(id_disputa) = pedido.disputa {
+            if self.disputas_en_curso.contains_key(&id_disputa) {
+                return Err(ErrorCancelarUnidadesRestantes::DisputaEnCurso);
+            }
+        }
+
+        // validar unidades pendientes
+        let unidades = pedido.cantidad_pendiente_despacho();
+        if unidades == 0 {
+            return Err(ErrorCancelarUnidadesRestantes::SinUnidadesPendientes);
+        }
+
+        // validar política de cancelación para el comprador: 14 días desde el pedido
+        if caller == pedido.comprador {
+            let milis_14_dias: u64 = 1_209_600_000; // 1000 * 60 * 60 * 24 * 14
+            let tiempo_transcurrido = timestamp.saturating_sub(pedido.timestamp);
+            if tiempo_transcurrido < milis_14_dias {
+                return Err(ErrorCancelarUnidadesRestantes::NoConvalidaPoliticaDeCancelacion);
+            }
+        }
+
+        // todo bien. se devolverán fondos en lib.rs
+        Ok(self.cancelar_unidades_no_despachadas(timestamp, pedido.clone()))
+    }
+
+    /// Cancela las unidades del pedido que aún no fueron despachadas: vuelven a la publicación
+    /// y se reembolsa su valor proporcional al comprador. Las unidades despachadas siguen su curso.
+    /// Si ninguna unidad fue despachada, el pedido se cancela.
+    /// 
+    /// Devuelve el comprador y el valor a reembolsarle.
+    fn cancelar_unidades_no_despachadas(&mut self, timestamp: u64, mut pedido: Pedido) -> (AccountId, u128) {
+        let unidades = pedido.cantidad_pendiente_despacho();
+        let reembolso = pedido.valor_unitario()
+            .saturating_mul(u128::from(unidades)) // safe cast: u32 -> u128
+            .min(pedido.fondos_en_custodia());
+
+        // devolver stock a la publicación
+        self.devolver_unidades_no_despachadas(timestamp, &pedido);
+
+        // actualizar pedido
+        pedido.cantidad_cancelada = pedido.cantidad_cancelada.saturating_add(unidades);
+        pedido.fondos_reembolsados = pedido.fondos_reembolsados.saturating_add(reembolso);
+
+        if pedido.cantidad_vigente() == 0 {
+            // ninguna unidad fue despachada: se cancela el pedido
+            pedido.estado = EstadoPedido::Cancelado(timestamp);
+            pedido.fondos_fueron_transferidos = true;
+
+            if let Some(publicacion) = self.publicaciones.get(&pedido.publicacion) {
+                if let Some(mut producto) = self.productos.get(&publicacion.producto).cloned() {
+                    producto.ventas = producto.ventas.saturating_sub(1);
+                    self.productos.insert(publicacion.producto, producto);
+                }
+            }
+        } else if pedido.cantidad_recibida >= pedido.cantidad_vigente() {
+            // todas las unidades restantes ya fueron recibidas
+            pedido.estado = EstadoPedido::Recibido(pedido.ultima_recepcion.unwrap_or(timestamp));
+        } else {
+            // todas las unidades restantes ya fueron despachadas
+            pedido.estado = EstadoPedido::Despachado(timestamp);
+        }
+
+        let id_comprador = pedido.comprador;
+        self.pedidos.insert(pedido.id, pedido);
+
+        (id_comprador, reembolso)
     }
 
     //
 
-    /// Cancela el pedido si ambos participantes del mismo ejecutan esta misma función
-    /// y si éste no fue recibida ni ya cancelada.
-    /// Entrega automáticamente los fondos de la compra al comprador y el stock al vendedor.
-    /// 
-    /// Política de cancelación unilateral:
-    ///   Si el pedido fue realizado hace más de 14 días y aún no fue despachado,
-    ///   el comprador puede cancelar el mismo de forma unánime y recuperar los fondos.
+    /// El vendedor reembolsa al comprador un valor arbitrario de un pedido sin cancelarlo,
+    /// por ejemplo para compensar un daño menor o una demora en el envío.
+    /// El valor se descuenta de los fondos en custodia, reduciendo lo que luego podrá retirar el vendedor,
+    /// y queda registrado en el historial de reembolsos del pedido.
     /// 
-    /// Devuelve error si el usuario o pedido no existen, si el usuario no participa en el pedido,
-    /// si el pedido ya fue cancelado o recibido y si quien solicita la cancelación ya la solicitó antes.
-    pub fn _cancelar_pedido(&mut self, timestamp: u64, caller: AccountId, id_pedido: u128) -> Result<Option<(AccountId, u128)>, ErrorCancelarPedido> {
+    /// Devolverá la ID del comprador y el valor a reembolsar. La transferencia se realiza en lib.rs.
+    /// Devolverá error si el usuario no está registrado, el pedido no existe, no es el vendedor,
+    /// el pedido fue cancelado, sus fondos ya fueron transferidos, existe una disputa en curso,
+    /// el valor es cero o supera los fondos en custodia o el motivo excede su longitud máxima.
+    pub fn _reembolso_parcial(&mut self, timestamp: u64, caller: AccountId, id_pedido: u128, valor: u128, motivo: String) -> Result<(AccountId, u128), ErrorReembolsoParcial> {
         // validar usuario
-        let Some(_) = self.usuarios.get(&caller)
-        else { return Err(ErrorCancelarPedido::UsuarioNoRegistrado); };
+        if !self.usuarios.contains_key(&caller) {
+            return Err(ErrorReembolsoParcial::UsuarioNoRegistrado);
+        }
 
-        // validar compra #2
+        // validar pedido
         let Some(pedido) = self.pedidos.get(&id_pedido)
-        else { return Err(ErrorCancelarPedido::PedidoInexistente); };
+        else { return Err(ErrorReembolsoParcial::PedidoInexistente); };
 
-        // validar comprador
-        if pedido.comprador != caller && pedido.vendedor != caller {
-            return Err(ErrorCancelarPedido::UsuarioNoParticipa);
+        // validar caller == vendedor
+        if pedido.vendedor != caller {
+            return Err(ErrorReembolsoParcial::SoloVendedorPuede);
         }
 
         // validar estado
-        match pedido.estado {
-            EstadoPedido::Pendiente(_) | EstadoPedido::Despachado(_) => (),
-            EstadoPedido::Recibido(_) => return Err(ErrorCancelarPedido::PedidoYaRecibido),
-            EstadoPedido::Cancelado(_) => return Err(ErrorCancelarPedido::PedidoYaCancelado),
+        if matches!(pedido.estado, EstadoPedido::Cancelado(_)) {
+            return Err(ErrorReembolsoParcial::PedidoCancelado);
         }
 
-        // política de cancelación unilateral
-        //    para este caso, las verificaciones de existencia de solicitud de cancelacion
-        //    o la mutualidad no son necesarias
-        if politica_cancelacion_unilateral(timestamp, pedido, caller) {
-            // modificar publicación: devolver stock
-            if let Some(mut publicacion) = self.publicaciones.get(&pedido.publicacion).cloned() {
-                // modificar pedido: quitar venta
-                if let Some(mut producto) = self.productos.get(&publicacion.producto).cloned() {
-                    if let Some(nueva_cant_ventas) = producto.ventas.checked_sub(1) {
-                        producto.ventas = nueva_cant_ventas;
-                        self.productos.insert(publicacion.producto, producto);
-                    }
-                }
+        // validar fondos en custodia
+        if pedido.fondos_fueron_transferidos {
+            return Err(ErrorReembolsoParcial::FondosYaTransferidos);
+        }
 
-                // devolver stock
-                if let Some(nueva_cantidad_ofertada) = publicacion.cantidad_ofertada.checked_add(pedido.cantidad_comprada) {
-                    // modificar e insertar publicación con nueva cantidad ofertada
-                    publicacion.cantidad_ofertada = nueva_cantidad_ofertada;
-                    self.publicaciones.insert(pedido.publicacion, publicacion);
-                }
+        // validar que no exista disputa en curso
+        if let Some(id_disputa) = pedido.disputa {
+            if self.disputas_en_curso.contains_key(&id_disputa) {
+                return Err(ErrorReembolsoParcial::DisputaEnCurso);
             }
+        }
 
-            let mut pedido = pedido.clone();
-
-            let id_comprador = pedido.comprador;
-            let valor_pedido = pedido.valor_total;
-
-            // modificar compra
-            pedido.estado = EstadoPedido::Cancelado(timestamp);
-            pedido.fondos_fueron_transferidos = true;
-            self.pedidos.insert(pedido.id, pedido);
+        // validar valor: sólo puede reembolsarse lo que siga en custodia (nunca más que valor_total)
+        if valor == 0 || valor > pedido.fondos_en_custodia() {
+            return Err(ErrorReembolsoParcial::ValorInvalido);
+        }
 
-            // fin. se devolverán fondos en lib.rs
-            return Ok(Some((id_comprador, valor_pedido)))
+        // validar motivo
+        if motivo.len() > MAX_LARGO_MOTIVO_REEMBOLSO {
+            return Err(ErrorReembolsoParcial::MotivoInvalido);
         }
-    
-        //
-        // validar si ya existe una solicitud de cancelación
-        //
 
+        // guardar pedido
         let mut pedido = pedido.clone();
-        let Some(primer_solicitud_cancelacion) = pedido.primer_solicitud_cancelacion 
-        else {
-            pedido.primer_solicitud_cancelacion = Some(caller);
-            self.pedidos.insert(pedido.id, pedido);
-            return Ok(None);
-        };
-
-        //
-        // asegurar que exista mutualidad
-        //
-
-        if primer_solicitud_cancelacion == caller {
-            return Err(ErrorCancelarPedido::EsperandoConfirmacionMutua);
+        pedido.fondos_reembolsados = pedido.fondos_reembolsados.saturating_add(valor);
+        pedido.reembolsos.push(RegistroReembolso { timestamp, valor, motivo });
+        if pedido.fondos_en_custodia() == 0 {
+            pedido.fondos_fueron_transferidos = true;
         }
 
         let id_comprador = pedido.comprador;
-        let valor_pedido = pedido.valor_total;
-
-        // modificar publicación: devolver stock
-        if let Some(mut publicacion) = self.publicaciones.get(&pedido.publicacion).cloned() {
-            // modificar pedido: quitar venta
-            if let Some(mut producto) = self.productos.get(&publicacion.producto).cloned() {
-                if let Some(nueva_cant_ventas) = producto.ventas.checked_add(1) {
-                    producto.ventas = nueva_cant_ventas;
-                    self.productos.insert(publicacion.producto, producto);
-                }
-            }
-
-            // devolver stock
-            if let Some(nueva_cantidad_ofertada) = publicacion.cantidad_ofertada.checked_add(pedido.cantidad_comprada) {
-                // modificar e insertar publicación con nueva cantidad ofertada
-                publicacion.cantidad_ofertada = nueva_cantidad_ofertada;
-                self.publicaciones.insert(pedido.publicacion, publicacion);
-            }
-        } // si la publicacion no existe, el stock se pierde. para evitarlo debo agregar "id_producto" a compra
-
-        // modificar compra
-        pedido.estado = EstadoPedido::Cancelado(timestamp);
-        pedido.fondos_fueron_transferidos = true;
         self.pedidos.insert(pedido.id, pedido);
 
         // fin. se devolverán fondos en lib.rs
-        Ok(Some((id_comprador, valor_pedido)))
+        Ok((id_comprador, valor))
     }
 
     //
@@ -801,10 +1596,11 @@ impl RustaceoLibre {
 
     //
 
-    /// Devuelve las compras del usuario que lo ejecuta que estén en el estado especificado
+    /// Devuelve las compras del usuario que lo ejecuta cuyo producto pertenezca a la categoría especificada
+    /// o a alguna de sus subcategorías
     /// 
-    /// Dará error si el usuario no está registrado como comprador o no tiene compras en ese estado
-    pub fn _ver_compras_categoria(&self, caller: AccountId, categoria: CategoriaProducto) -> Result<Vec<Pedido>, ErrorVerCompras> {
+    /// Dará error si el usuario no está registrado como comprador o no tiene compras
+    pub fn _ver_compras_categoria(&self, caller: AccountId, categoria: u128) -> Result<Vec<Pedido>, ErrorVerCompras> {
         let compras = self._ver_compras(caller)?;
         let compras = compras.iter().filter(|compra| {
             // obtener publicación desde id
@@ -815,8 +1611,8 @@ impl RustaceoLibre {
             let Some(producto) = self.productos.get(&publicacion.producto)
             else { return false; };
 
-            // fin
-            producto.categoria == categoria
+            // fin: la categoría o alguna de sus subcategorías
+            self.pertenece_a_categoria(&producto.categoria, &categoria)
         }).cloned().collect();
         Ok(compras)
     }
@@ -863,10 +1659,11 @@ impl RustaceoLibre {
 
     //
 
-    /// Devuelve las compras del usuario que lo ejecuta que estén en el estado especificado
+    /// Devuelve las ventas del usuario que lo ejecuta cuyo producto pertenezca a la categoría especificada
+    /// o a alguna de sus subcategorías
     /// 
-    /// Dará error si el usuario no está registrado como comprador o no tiene compras en ese estado
-    pub fn _ver_ventas_categoria(&self, caller: AccountId, categoria: CategoriaProducto) -> Result<Vec<Pedido>, ErrorVerVentas> {
+    /// Dará error si el usuario no está registrado como vendedor o no tiene ventas
+    pub fn _ver_ventas_categoria(&self, caller: AccountId, categoria: u128) -> Result<Vec<Pedido>, ErrorVerVentas> {
         let ventas = self._ver_ventas(caller)?;
         let ventas = ventas.iter().filter(|ventas| {
             // obtener publicacion desde id
@@ -877,7 +1674,8 @@ impl RustaceoLibre {
             let Some(producto) = self.productos.get(&publicacion.producto)
             else { return false; };
 
-            producto.categoria == categoria
+            // la categoría o alguna de sus subcategorías
+            self.pertenece_a_categoria(&producto.categoria, &categoria)
         }).cloned().collect();
         Ok(ventas)
     }
@@ -897,7 +1695,9 @@ impl RustaceoLibre {
 mod tests {
     use super::*;
     use crate::structs::{
-        producto::CategoriaProducto,
+        invariantes::AmbitoInvariantes,
+        producto::DatosProducto,
+        pruebas::preparar_pedido,
         usuario::{RolDeSeleccion},
     };
     use ink::primitives::AccountId;
@@ -917,14 +1717,15 @@ mod tests {
         // Registrar producto
         let nombre = "Termo".into();
         let descripcion = "Acero inoxidable".into();
-        let categoria = CategoriaProducto::Tecnologia;
+        let categoria = 2;
         let stock = 10;
-        let id_producto = contrato._registrar_producto(vendedor, nombre, descripcion, categoria, stock).unwrap();
+        let id_producto = contrato._registrar_producto(0, vendedor, DatosProducto::new(nombre, descripcion, categoria), stock).unwrap();
+        contrato._staff_aprobar_producto(contrato.owner, id_producto).unwrap();
 
         // Realizar publicación
         let precio_unitario = 100;
         let cantidad_ofertada = 5;
-        let id_publicacion = contrato._realizar_publicacion(vendedor, id_producto, cantidad_ofertada, precio_unitario).unwrap();
+        let id_publicacion = contrato._realizar_publicacion(0, vendedor, id_producto, cantidad_ofertada, precio_unitario).unwrap();
 
         // Comprar producto
         let timestamp = 12345;
@@ -984,12 +1785,13 @@ mod tests {
         // Crear producto y publicarlo
         let nombre = "Cuadro".into();
         let descripcion = "Pintura".into();
-        let categoria = CategoriaProducto::Hogar;
+        let categoria = 1;
         let stock = 10;
 
-        let id_producto = contrato._registrar_producto(vendedor, nombre, descripcion, categoria, stock).unwrap();
+        let id_producto = contrato._registrar_producto(0, vendedor, DatosProducto::new(nombre, descripcion, categoria), stock).unwrap();
+        contrato._staff_aprobar_producto(contrato.owner, id_producto).unwrap();
         let precio_unitario = 100;
-        let id_publicacion = contrato._realizar_publicacion(vendedor, id_producto, stock, precio_unitario).unwrap();
+        let id_publicacion = contrato._realizar_publicacion(0, vendedor, id_producto, stock, precio_unitario).unwrap();
 
         // El vendedor (no comprador) intenta comprar
         let resultado = contrato._comprar_producto(0, vendedor, id_publicacion, 1, 100);
@@ -1027,15 +1829,14 @@ mod tests {
         contrato._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
 
         // Registrar producto y publicación
-        let id_producto = contrato._registrar_producto(
+        let id_producto = contrato._registrar_producto(0, 
             vendedor,
-            "Mate".into(),
-            "Calabaza".into(),
-            CategoriaProducto::Hogar,
+            DatosProducto::new("Mate".into(), "Calabaza".into(), 1),
             10,
         ).unwrap();
+        contrato._staff_aprobar_producto(contrato.owner, id_producto).unwrap();
 
-        let id_publicacion = contrato._realizar_publicacion(vendedor, id_producto, 5, 100).unwrap();
+        let id_publicacion = contrato._realizar_publicacion(0, vendedor, id_producto, 5, 100).unwrap();
 
         // Simular que el vendedor fue eliminado
         contrato.usuarios.remove(&vendedor);
@@ -1059,15 +1860,14 @@ mod tests {
         contrato._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
 
         // Registrar producto y publicación con 5 unidades
-        let id_producto = contrato._registrar_producto(
+        let id_producto = contrato._registrar_producto(0, 
             vendedor,
-            "Lapicera".into(),
-            "Tinta negra".into(),
-            CategoriaProducto::Tecnologia,
+            DatosProducto::new("Lapicera".into(), "Tinta negra".into(), 2),
             5,
         ).unwrap();
+        contrato._staff_aprobar_producto(contrato.owner, id_producto).unwrap();
 
-        let id_publicacion = contrato._realizar_publicacion(vendedor, id_producto, 5, 50).unwrap();
+        let id_publicacion = contrato._realizar_publicacion(0, vendedor, id_producto, 5, 50).unwrap();
 
         // El comprador intenta comprar 10 unidades (más de las ofertadas)
         let resultado = contrato._comprar_producto(0, comprador, id_publicacion, 10, 500);
@@ -1088,15 +1888,14 @@ mod tests {
         contrato._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
 
         // Registrar producto y publicación
-        let id_producto = contrato._registrar_producto(
+        let id_producto = contrato._registrar_producto(0, 
             vendedor,
-            "Cuaderno".into(),
-            "Rayado".into(),
-            CategoriaProducto::Hogar,
+            DatosProducto::new("Cuaderno".into(), "Rayado".into(), 1),
             5,
         ).unwrap();
+        contrato._staff_aprobar_producto(contrato.owner, id_producto).unwrap();
 
-        let id_publicacion = contrato._realizar_publicacion(vendedor, id_producto, 5, 100).unwrap();
+        let id_publicacion = contrato._realizar_publicacion(0, vendedor, id_producto, 5, 100).unwrap();
 
         // Intentar comprar 2 unidades con solo 150 transferidos (se necesitan 200)
         let resultado = contrato._comprar_producto(0, comprador, id_publicacion, 2, 150);
@@ -1117,16 +1916,15 @@ mod tests {
         contrato._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
 
         // Registrar producto y publicación con precio máximo
-        let id_producto = contrato._registrar_producto(
+        let id_producto = contrato._registrar_producto(0, 
             vendedor,
-            "NFT".into(),
-            "Muy caro".into(),
-            CategoriaProducto::Tecnologia,
+            DatosProducto::new("NFT".into(), "Muy caro".into(), 2),
             5,
         ).unwrap();
+        contrato._staff_aprobar_producto(contrato.owner, id_producto).unwrap();
 
         let precio_unitario = u128::MAX;
-        let id_publicacion = contrato._realizar_publicacion(vendedor, id_producto, 5, precio_unitario).unwrap();
+        let id_publicacion = contrato._realizar_publicacion(0, vendedor, id_producto, 5, precio_unitario).unwrap();
 
         // Intentar comprar 2 (precio_unitario * 2) → overflow
         let resultado = contrato._comprar_producto(0, comprador, id_publicacion, 2, u128::MAX);
@@ -1167,6 +1965,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         };
         contrato.pedidos.insert(id_compra, compra);
 
@@ -1214,6 +2021,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // Act
@@ -1275,6 +2091,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         };
         contrato.pedidos.insert(id_compra, compra);
 
@@ -1315,6 +2140,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         };
         contrato.pedidos.insert(id_compra, compra);
 
@@ -1356,6 +2190,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         };
         contrato.pedidos.insert(id_compra, compra);
 
@@ -1396,6 +2239,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         };
         contrato.pedidos.insert(id_compra, compra);
 
@@ -1438,6 +2290,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         };
         contrato.pedidos.insert(id_compra, compra);
 
@@ -1477,6 +2338,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         };
         contrato.pedidos.insert(id_compra, compra);
 
@@ -1515,6 +2385,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         let resultado = contrato._retirar_fondos(timestamp_actual, vendedor, id_compra);
@@ -1553,6 +2432,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         };
 
         // Insertar la compra al contrato
@@ -1610,6 +2498,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         let resultado = contrato._pedido_despachado(123456, vendedor, id_compra);
@@ -1630,6 +2527,98 @@ mod tests {
     }
 
 
+    #[ink::test]
+    fn compra_despachada_con_seguimiento_exitoso() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (vendedor, _, _, _, id_pedido) = preparar_pedido(&mut contrato, 10, 5, 2);
+
+        let resultado = contrato._pedido_despachado_con_seguimiento(100, vendedor, id_pedido, "Correo".into(), "AB123".into());
+        assert_eq!(resultado, Ok(()));
+
+        let pedido = contrato.pedidos.get(&id_pedido).unwrap();
+        assert_eq!(pedido.estado, EstadoPedido::Despachado(100));
+        assert_eq!(pedido.seguimiento_actual(), Some(&RegistroSeguimiento::new(100, "Correo".into(), "AB123".into())));
+    }
+
+    #[ink::test]
+    fn compra_despachada_seguimiento_invalido() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (vendedor, _, _, _, id_pedido) = preparar_pedido(&mut contrato, 10, 5, 2);
+
+        let resultado = contrato._pedido_despachado_con_seguimiento(100, vendedor, id_pedido, "Correo".into(), "".into());
+        assert_eq!(resultado, Err(ErrorProductoDespachado::SeguimientoInvalido));
+
+        let pedido = contrato.pedidos.get(&id_pedido).unwrap();
+        assert_eq!(pedido.estado, EstadoPedido::Pendiente(0));
+    }
+
+    #[ink::test]
+    fn compra_despachada_seguimiento_requerido_por_categoria() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (vendedor, _, _, _, id_pedido) = preparar_pedido(&mut contrato, 10, 5, 2);
+
+        let owner = contrato.owner;
+        assert!(contrato._staff_establecer_politica_seguimiento(owner, vec![1], None));
+
+        let resultado = contrato._pedido_despachado(100, vendedor, id_pedido);
+        assert_eq!(resultado, Err(ErrorProductoDespachado::SeguimientoRequerido));
+
+        let resultado = contrato._pedido_despachado_con_seguimiento(100, vendedor, id_pedido, "Correo".into(), "AB123".into());
+        assert_eq!(resultado, Ok(()));
+    }
+
+    #[ink::test]
+    fn compra_despachada_seguimiento_requerido_por_valor() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (vendedor, _, _, _, id_pedido) = preparar_pedido(&mut contrato, 10, 5, 2);
+
+        // el pedido vale 200
+        let owner = contrato.owner;
+        assert!(contrato._staff_establecer_politica_seguimiento(owner, Vec::new(), Some(500)));
+        assert!(!contrato.pedido_requiere_seguimiento(contrato.pedidos.get(&id_pedido).unwrap()));
+
+        assert!(contrato._staff_establecer_politica_seguimiento(owner, Vec::new(), Some(200)));
+        let resultado = contrato._pedido_despachado(100, vendedor, id_pedido);
+        assert_eq!(resultado, Err(ErrorProductoDespachado::SeguimientoRequerido));
+    }
+
+    #[ink::test]
+    fn politica_seguimiento_solo_staff() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (vendedor, _, _, _, _) = preparar_pedido(&mut contrato, 10, 5, 2);
+
+        assert!(!contrato._staff_establecer_politica_seguimiento(vendedor, Vec::new(), Some(1)));
+        assert_eq!(contrato.valor_minimo_seguimiento_obligatorio, None);
+    }
+
+    #[ink::test]
+    fn actualizar_seguimiento_conserva_historial() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (vendedor, comprador, _, _, id_pedido) = preparar_pedido(&mut contrato, 10, 5, 2);
+
+        // no despachado
+        let resultado = contrato._actualizar_seguimiento(50, vendedor, id_pedido, "Correo".into(), "AB123".into());
+        assert_eq!(resultado, Err(ErrorActualizarSeguimiento::PedidoNoDespachado));
+
+        contrato._pedido_despachado_con_seguimiento(100, vendedor, id_pedido, "Correo".into(), "AB123".into()).unwrap();
+
+        // sólo el vendedor
+        let resultado = contrato._actualizar_seguimiento(200, comprador, id_pedido, "Andreani".into(), "XY999".into());
+        assert_eq!(resultado, Err(ErrorActualizarSeguimiento::SoloVendedorPuede));
+
+        let resultado = contrato._actualizar_seguimiento(200, vendedor, id_pedido, "Andreani".into(), "XY999".into());
+        assert_eq!(resultado, Ok(()));
+
+        let pedido = contrato.pedidos.get(&id_pedido).unwrap();
+        assert_eq!(pedido.seguimiento.len(), 2);
+        assert_eq!(pedido.seguimiento_actual().unwrap().transportista, "Andreani");
+
+        // el comprador lo ve en sus compras
+        let compras = contrato._ver_compras(comprador).unwrap();
+        assert_eq!(compras[0].seguimiento, pedido.seguimiento);
+    }
+
+
     #[ink::test]
     fn reclamar_fondos_estado_recibido() {
         let mut contrato = RustaceoLibre::new(0);
@@ -1656,6 +2645,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         let resultado = contrato._retirar_fondos(timestamp_actual, vendedor, id_compra);
@@ -1690,6 +2688,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         let resultado = contrato._retirar_fondos(timestamp_actual, vendedor, id_compra);
@@ -1723,6 +2730,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         let resultado = contrato._retirar_fondos(timestamp_actual, vendedor, id_compra);
@@ -1770,6 +2786,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // Agregar compra a la lista de compras del usuario
@@ -1811,6 +2836,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // Agregar compra a la lista de compras del usuario
@@ -1852,6 +2886,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // Agregar compra a la lista de compras del usuario
@@ -1893,6 +2936,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // Agregar compra a la lista de compras del comprador (solo del comprador)
@@ -1965,6 +3017,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // Agregar compra a la lista de compras del comprador
@@ -2019,6 +3080,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // Agregar compra a la lista de compras del comprador
@@ -2067,6 +3137,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // Agregar compra a la lista de compras del comprador
@@ -2151,6 +3230,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // Agregar compra a la lista de compras del comprador
@@ -2232,6 +3320,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // Agregar compra a la lista de ventas del vendedor
@@ -2275,6 +3372,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // Agregar compra a la lista de compras del comprador
@@ -2318,6 +3424,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // Agregar compra a la lista de compras del comprador
@@ -2362,6 +3477,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // Agregar compra a la lista de ventas del vendedor
@@ -2392,14 +3516,13 @@ mod tests {
         contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
 
         // Registrar producto y publicación en categoría Hogar
-        let id_producto = contrato._registrar_producto(
+        let id_producto = contrato._registrar_producto(0, 
             vendedor,
-            "Silla".into(),
-            "Plástica".into(),
-            CategoriaProducto::Hogar,
+            DatosProducto::new("Silla".into(), "Plástica".into(), 1),
             10,
         ).unwrap();
-        let id_publicacion = contrato._realizar_publicacion(vendedor, id_producto, 5, 100).unwrap();
+        contrato._staff_aprobar_producto(contrato.owner, id_producto).unwrap();
+        let id_publicacion = contrato._realizar_publicacion(0, vendedor, id_producto, 5, 100).unwrap();
 
         // Insertar compra en categoría Hogar
         contrato.pedidos.insert(id_compra, Pedido {
@@ -2416,6 +3539,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // Agregar compra a la lista de compras del comprador
@@ -2426,7 +3558,7 @@ mod tests {
         }
 
         // Buscar compras en categoría Tecnología (no hay ninguna)
-        let resultado = contrato._ver_compras_categoria(comprador, CategoriaProducto::Tecnologia);
+        let resultado = contrato._ver_compras_categoria(comprador, 2);
         assert!(resultado.is_ok());
         let compras = resultado.unwrap();
         assert_eq!(compras.len(), 0);
@@ -2445,14 +3577,13 @@ mod tests {
         contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
 
         // Registrar producto y publicación en categoría Tecnología
-        let id_producto = contrato._registrar_producto(
+        let id_producto = contrato._registrar_producto(0, 
             vendedor,
-            "Celular".into(),
-            "Android".into(),
-            CategoriaProducto::Tecnologia,
+            DatosProducto::new("Celular".into(), "Android".into(), 2),
             10,
         ).unwrap();
-        let id_publicacion = contrato._realizar_publicacion(vendedor, id_producto, 5, 500).unwrap();
+        contrato._staff_aprobar_producto(contrato.owner, id_producto).unwrap();
+        let id_publicacion = contrato._realizar_publicacion(0, vendedor, id_producto, 5, 500).unwrap();
 
         // Insertar compra en categoría Tecnología
         contrato.pedidos.insert(id_compra, Pedido {
@@ -2469,6 +3600,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // Agregar compra a la lista de compras del comprador
@@ -2479,7 +3619,7 @@ mod tests {
         }
 
         // Buscar compras en categoría Tecnología (hay una)
-        let resultado = contrato._ver_compras_categoria(comprador, CategoriaProducto::Tecnologia);
+        let resultado = contrato._ver_compras_categoria(comprador, 2);
         assert!(resultado.is_ok());
         let compras = resultado.unwrap();
         assert_eq!(compras.len(), 1);
@@ -2499,14 +3639,13 @@ mod tests {
         contrato._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
 
         // Registrar producto y publicación en categoría Hogar
-        let id_producto = contrato._registrar_producto(
+        let id_producto = contrato._registrar_producto(0, 
             vendedor,
-            "Mesa".into(),
-            "Madera".into(),
-            CategoriaProducto::Hogar,
+            DatosProducto::new("Mesa".into(), "Madera".into(), 1),
             10,
         ).unwrap();
-        let id_publicacion = contrato._realizar_publicacion(vendedor, id_producto, 5, 700).unwrap();
+        contrato._staff_aprobar_producto(contrato.owner, id_producto).unwrap();
+        let id_publicacion = contrato._realizar_publicacion(0, vendedor, id_producto, 5, 700).unwrap();
 
         // Insertar compra en categoría Hogar
         contrato.pedidos.insert(id_compra, Pedido {
@@ -2523,6 +3662,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // Agregar compra a la lista de ventas del vendedor
@@ -2533,7 +3681,7 @@ mod tests {
         }
 
         // Buscar ventas en categoría Hogar (hay una)
-        let resultado = contrato._ver_ventas_categoria(vendedor, CategoriaProducto::Hogar);
+        let resultado = contrato._ver_ventas_categoria(vendedor, 1);
         assert!(resultado.is_ok());
         let ventas = resultado.unwrap();
         assert_eq!(ventas.len(), 1);
@@ -2553,14 +3701,13 @@ mod tests {
         contrato._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
 
         // Registrar producto y publicación en categoría Hogar
-        let id_producto = contrato._registrar_producto(
+        let id_producto = contrato._registrar_producto(0, 
             vendedor,
-            "Silla".into(),
-            "Plástica".into(),
-            CategoriaProducto::Hogar,
+            DatosProducto::new("Silla".into(), "Plástica".into(), 1),
             10,
         ).unwrap();
-        let id_publicacion = contrato._realizar_publicacion(vendedor, id_producto, 5, 100).unwrap();
+        contrato._staff_aprobar_producto(contrato.owner, id_producto).unwrap();
+        let id_publicacion = contrato._realizar_publicacion(0, vendedor, id_producto, 5, 100).unwrap();
 
         // Insertar compra en categoría Hogar
         contrato.pedidos.insert(id_compra, Pedido {
@@ -2577,6 +3724,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // Agregar compra a la lista de ventas del vendedor
@@ -2587,7 +3743,7 @@ mod tests {
         }
 
         // Buscar ventas en categoría Tecnología (no hay ninguna)
-        let resultado = contrato._ver_ventas_categoria(vendedor, CategoriaProducto::Tecnologia);
+        let resultado = contrato._ver_ventas_categoria(vendedor, 2);
         assert!(resultado.is_ok());
         let ventas = resultado.unwrap();
         assert_eq!(ventas.len(), 0);
@@ -2667,6 +3823,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         let resultado = contrato._calificar_pedido(comprador, id_compra, 5);
@@ -2700,6 +3865,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         let resultado = contrato._calificar_pedido(comprador, id_compra, 4);
@@ -2735,6 +3909,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // El usuario que no participa intenta calificar
@@ -2769,6 +3952,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // El comprador intenta calificar
@@ -2803,6 +3995,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // El vendedor intenta calificar
@@ -2837,6 +4038,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // El comprador califica la compra
@@ -2874,6 +4084,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // El vendedor califica la compra
@@ -2909,6 +4128,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         let resultado = contrato._calificar_pedido(comprador, id_compra, 5);
@@ -2943,6 +4171,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         let resultado = contrato._retirar_fondos(timestamp_llamado, vendedor, id_compra);
@@ -2994,6 +4231,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         let res = contrato._pedido_recibido(2000, comprador, id_compra);
@@ -3046,6 +4292,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // Simular que pasaron más de 14 días
@@ -3092,6 +4347,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // Intentar retirar ANTES de los 3 días
@@ -3138,6 +4402,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: Some(id_disputa), // TIENE DISPUTA
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // Inyectar disputa en curso en el storage (asumiendo acceso público o mock)
@@ -3190,6 +4463,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // El impostor tiene la venta en su lista (simulando error de datos o hacking)
@@ -3225,6 +4507,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // Vincular venta al usuario
@@ -3260,6 +4551,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         let calif = contrato._ver_calificacion_comprador_pedido(id_pedido);
@@ -3293,6 +4593,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // 1. Vendedor solicita cancelación
@@ -3330,6 +4639,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: Some(id_disputa_fantasma), // ID asignada
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // Aseguramos que los mapas de disputas estén vacíos (por defecto lo están en new())
@@ -3371,6 +4689,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // 1. Vendedor solicita cancelación
@@ -3391,17 +4718,16 @@ mod tests {
         contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
         contrato._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
 
-        // USAMOS CategoriaProducto::Tecnologia
-        let id_prod = contrato._registrar_producto(
-            vendedor, 
-            "Producto Agotable".into(), 
-            "Desc".into(), 
-            CategoriaProducto::Tecnologia, 
-            10
+        // USAMOS la categoría Tecnologia (ID 2)
+        let id_prod = contrato._registrar_producto(0, 
+            vendedor,
+            DatosProducto::new("Producto Agotable".into(), "Desc".into(), 2),
+            10,
         ).unwrap();
+        contrato._staff_aprobar_producto(contrato.owner, id_prod).unwrap();
         
         // Publicamos solo 1 unidad
-        let id_pub = contrato._realizar_publicacion(vendedor, id_prod, 1, 100).unwrap();
+        let id_pub = contrato._realizar_publicacion(0, vendedor, id_prod, 1, 100).unwrap();
 
         // Comprar 1 unidad (todo el stock)
         let res = contrato._comprar_producto(1000, comprador, id_pub, 1, 100);
@@ -3440,6 +4766,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: Some(id_disputa_fantasma), // ID asignada pero no registrada en el sistema de disputas
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // 3 días + 1 ms después
@@ -3464,9 +4799,10 @@ mod tests {
         contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
         contrato._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
 
-        let id_prod = contrato._registrar_producto(vendedor, "T".into(), "D".into(), CategoriaProducto::Tecnologia, 10).unwrap();
+        let id_prod = contrato._registrar_producto(0, vendedor, DatosProducto::new("T".into(), "D".into(), 2), 10).unwrap();
+        contrato._staff_aprobar_producto(contrato.owner, id_prod).unwrap();
         // Precio 100
-        let id_pub = contrato._realizar_publicacion(vendedor, id_prod, 5, 100).unwrap();
+        let id_pub = contrato._realizar_publicacion(0, vendedor, id_prod, 5, 100).unwrap();
 
         // Comprar 1 unidad (Costo 100) pero transferir 150
         let valor_transferido = 150;
@@ -3510,6 +4846,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // Simular paso de tiempo > 14 días (14 dias = 1,209,600,000 ms)
@@ -3554,6 +4899,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // Asignar compra al usuario
@@ -3562,7 +4916,7 @@ mod tests {
         contrato.usuarios.insert(comprador, u);
 
         // Ejecutar la vista
-        let res = contrato._ver_compras_categoria(comprador, CategoriaProducto::Tecnologia);
+        let res = contrato._ver_compras_categoria(comprador, 2);
 
         assert!(res.is_ok());
         let lista = res.unwrap();
@@ -3583,8 +4937,9 @@ mod tests {
         contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
 
         // Crear producto y publicacion para validar devolucion de stock
-        let id_prod = contrato._registrar_producto(vendedor, "T".into(), "D".into(), CategoriaProducto::Tecnologia, 10).unwrap();
-        let id_pub = contrato._realizar_publicacion(vendedor, id_prod, 5, 100).unwrap();
+        let id_prod = contrato._registrar_producto(0, vendedor, DatosProducto::new("T".into(), "D".into(), 2), 10).unwrap();
+        contrato._staff_aprobar_producto(contrato.owner, id_prod).unwrap();
+        let id_pub = contrato._realizar_publicacion(0, vendedor, id_prod, 5, 100).unwrap();
 
         // Pedido creado AHORA (timestamp 1000)
         contrato.pedidos.insert(id_pedido, Pedido {
@@ -3601,6 +4956,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // Intentar cancelar AHORA MISMO (mismo timestamp, tiempo transcurrido = 0)
@@ -3625,11 +4989,12 @@ mod tests {
         contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
         contrato._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
 
-        let id_prod = contrato._registrar_producto(vendedor, "Caro".into(), "Desc".into(), CategoriaProducto::Tecnologia, 10).unwrap();
+        let id_prod = contrato._registrar_producto(0, vendedor, DatosProducto::new("Caro".into(), "Desc".into(), 2), 10).unwrap();
+        contrato._staff_aprobar_producto(contrato.owner, id_prod).unwrap();
         
         // Precio unitario máximo posible (u128::MAX)
         let precio_maximo = u128::MAX;
-        let id_pub = contrato._realizar_publicacion(vendedor, id_prod, 5, precio_maximo).unwrap();
+        let id_pub = contrato._realizar_publicacion(0, vendedor, id_prod, 5, precio_maximo).unwrap();
 
         // Intentamos comprar 2 unidades. 
         // 2 * u128::MAX causa overflow matemático.
@@ -3653,8 +5018,9 @@ mod tests {
         use crate::structs::publicacion::Publicacion; // Asumiendo visibilidad o mock
         // Si no puedes instanciar Publicacion directamente, usamos un truco:
         // Creamos una real y luego borramos el producto.
-        let id_prod_real = contrato._registrar_producto(vendedor, "X".into(), "D".into(), CategoriaProducto::Tecnologia, 10).unwrap();
-        let id_pub_real = contrato._realizar_publicacion(vendedor, id_prod_real, 5, 100).unwrap();
+        let id_prod_real = contrato._registrar_producto(0, vendedor, DatosProducto::new("X".into(), "D".into(), 2), 10).unwrap();
+        contrato._staff_aprobar_producto(contrato.owner, id_prod_real).unwrap();
+        let id_pub_real = contrato._realizar_publicacion(0, vendedor, id_prod_real, 5, 100).unwrap();
         
         // BORRAMOS el producto del mapa para simular corrupción
         contrato.productos.remove(&id_prod_real);
@@ -3674,6 +5040,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // 3. Asignar venta al vendedor
@@ -3682,7 +5057,7 @@ mod tests {
         contrato.usuarios.insert(vendedor, u);
 
         // Ejecutar vista
-        let res = contrato._ver_ventas_categoria(vendedor, CategoriaProducto::Tecnologia);
+        let res = contrato._ver_ventas_categoria(vendedor, 2);
 
         assert!(res.is_ok());
         let lista = res.unwrap();
@@ -3717,6 +5092,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // Simulamos que pasaron 20 días (más de los 14 requeridos)
@@ -3759,6 +5143,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // PERO: No agregamos el `id_pedido` al vector de ventas del usuario `vendedor`.
@@ -3779,8 +5172,9 @@ mod tests {
         contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
         contrato._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
 
-        let id_prod = contrato._registrar_producto(vendedor, "Hit".into(), "D".into(), CategoriaProducto::Tecnologia, 10).unwrap();
-        let id_pub = contrato._realizar_publicacion(vendedor, id_prod, 5, 100).unwrap();
+        let id_prod = contrato._registrar_producto(0, vendedor, DatosProducto::new("Hit".into(), "D".into(), 2), 10).unwrap();
+        contrato._staff_aprobar_producto(contrato.owner, id_prod).unwrap();
+        let id_pub = contrato._realizar_publicacion(0, vendedor, id_prod, 5, 100).unwrap();
 
         // FORZAMOS el contador de ventas al máximo (u32::MAX)
         if let Some(mut p) = contrato.productos.get(&id_prod).cloned() {
@@ -3809,8 +5203,9 @@ mod tests {
         contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
 
         // Creamos publicación dummy
-        let id_prod = contrato._registrar_producto(vendedor, "T".into(), "D".into(), CategoriaProducto::Tecnologia, 10).unwrap();
-        let id_pub = contrato._realizar_publicacion(vendedor, id_prod, 5, 100).unwrap();
+        let id_prod = contrato._registrar_producto(0, vendedor, DatosProducto::new("T".into(), "D".into(), 2), 10).unwrap();
+        contrato._staff_aprobar_producto(contrato.owner, id_prod).unwrap();
+        let id_pub = contrato._realizar_publicacion(0, vendedor, id_prod, 5, 100).unwrap();
 
         // Forzamos el stock de la publicación a u32::MAX
         if let Some(mut publ) = contrato.publicaciones.get(&id_pub).cloned() {
@@ -3833,6 +5228,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // Cancelamos unilateralmente (simulando tiempo pasado para hacerlo en 1 paso)
@@ -3869,6 +5273,15 @@ mod tests {
             calificacion_vendedor: None,
             disputa: None,
             primer_solicitud_cancelacion: None,
+            seguimiento: Vec::new(),
+            direccion_entrega: None,
+            cantidad_despachada: 0,
+            cantidad_recibida: 0,
+            cantidad_cancelada: 0,
+            fondos_liberados: 0,
+            fondos_reembolsados: 0,
+            ultima_recepcion: None,
+            reembolsos: Vec::new(),
         });
 
         // Intentamos cancelar con un timestamp MENOR al de creación (ej. 1000 < 5000)
@@ -3902,4 +5315,287 @@ mod tests {
         
         assert_eq!(res, Err(ErrorVerCompras::NoTieneCompras));
     }
+
+    #[ink::test]
+    fn comprar_producto_con_direccion_guarda_compromiso() {
+        let mut contrato = RustaceoLibre::default();
+        let vendedor = AccountId::from([0x1; 32]);
+        let comprador = AccountId::from([0x2; 32]);
+        let staff = AccountId::from([0x9; 32]);
+        contrato.staff.push(staff);
+
+        contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+        contrato._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
+
+        let id_producto = contrato._registrar_producto(0, vendedor, DatosProducto::new("Mate".into(), "Calabaza".into(), 1), 10).unwrap();
+        contrato._staff_aprobar_producto(contrato.owner, id_producto).unwrap();
+        let id_publicacion = contrato._realizar_publicacion(0, vendedor, id_producto, 5, 100).unwrap();
+
+        let direccion: Vec<u8> = "Calle Falsa 123".into();
+        let sal: Vec<u8> = vec![7; 16];
+        let direccion_entrega = DireccionEntrega {
+            cifrada: vec![0xAA; 64], // cifrada para el vendedor fuera de la cadena
+            compromiso: DireccionEntrega::calcular_compromiso(&direccion, &sal),
+        };
+
+        let id_pedido = contrato._comprar_producto_con_direccion(0, comprador, id_publicacion, 1, 100, Some(direccion_entrega.clone()))
+            .unwrap().id_nueva_transaccion;
+
+        // el vendedor la ve en sus ventas
+        let ventas = contrato._ver_ventas(vendedor).unwrap();
+        assert_eq!(ventas[0].direccion_entrega, Some(direccion_entrega));
+
+        // sólo el staff puede verificarla
+        assert_eq!(contrato._staff_verificar_direccion(comprador, id_pedido, direccion.clone(), sal.clone()), Err(ErrorVerificarDireccion::UsuarioNoStaff));
+        assert_eq!(contrato._staff_verificar_direccion(staff, id_pedido, direccion.clone(), sal.clone()), Ok(true));
+        assert_eq!(contrato._staff_verificar_direccion(staff, id_pedido, "Otra calle 1".into(), sal), Ok(false));
+    }
+
+    #[ink::test]
+    fn comprar_producto_con_direccion_invalida() {
+        let mut contrato = RustaceoLibre::default();
+        let vendedor = AccountId::from([0x1; 32]);
+        let comprador = AccountId::from([0x2; 32]);
+
+        contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+        contrato._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
+
+        let id_producto = contrato._registrar_producto(0, vendedor, DatosProducto::new("Mate".into(), "Calabaza".into(), 1), 10).unwrap();
+        contrato._staff_aprobar_producto(contrato.owner, id_producto).unwrap();
+        let id_publicacion = contrato._realizar_publicacion(0, vendedor, id_producto, 5, 100).unwrap();
+
+        let direccion_entrega = DireccionEntrega { cifrada: Vec::new(), compromiso: [0; 32] };
+        let resultado = contrato._comprar_producto_con_direccion(0, comprador, id_publicacion, 1, 100, Some(direccion_entrega));
+        assert_eq!(resultado, Err(ErrorComprarProducto::DireccionInvalida));
+
+        // la publicación conserva su stock
+        assert_eq!(contrato.publicaciones.get(&id_publicacion).unwrap().cantidad_ofertada, 5);
+    }
+
+    #[ink::test]
+    fn staff_verificar_direccion_pedido_sin_direccion() {
+        let mut contrato = RustaceoLibre::default();
+        let owner = contrato.owner;
+
+        assert_eq!(contrato._staff_verificar_direccion(owner, 0, Vec::new(), Vec::new()), Err(ErrorVerificarDireccion::PedidoInexistente));
+
+        let pedido = Pedido::new(0, 0, 0, 1, 100, AccountId::from([0x1; 32]), AccountId::from([0x2; 32]));
+        contrato.pedidos.insert(0, pedido);
+        assert_eq!(contrato._staff_verificar_direccion(owner, 0, Vec::new(), Vec::new()), Err(ErrorVerificarDireccion::PedidoSinDireccion));
+    }
+
+    #[ink::test]
+    fn despacho_y_recepcion_parcial_liberan_fondos_proporcionales() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (vendedor, comprador, _, _, id_pedido) = preparar_pedido(&mut contrato, 20, 15, 10);
+        let tres_dias = 259_200_000u64;
+
+        // despachar 4 de 10: sigue pendiente
+        assert_eq!(contrato._pedido_despachado_parcial(10, vendedor, id_pedido, 4, None), Ok(()));
+        let pedido = contrato.pedidos.get(&id_pedido).unwrap();
+        assert_eq!(pedido.cantidad_despachada, 4);
+        assert_eq!(pedido.estado, EstadoPedido::Pendiente(0));
+
+        // no se puede recibir más de lo despachado
+        assert_eq!(contrato._pedido_recibido_parcial(20, comprador, id_pedido, 5), Err(ErrorProductoRecibido::CantidadInvalida));
+        assert_eq!(contrato._pedido_recibido_parcial(20, comprador, id_pedido, 3), Ok(()));
+
+        // antes de tres días no se pueden retirar
+        assert_eq!(contrato._retirar_fondos_parciales(20 + tres_dias - 1, vendedor, id_pedido), Err(ErrorRetirarFondos::PlazoDeRetiroNoCumplido));
+        assert_eq!(contrato._retirar_fondos_parciales(20 + tres_dias, vendedor, id_pedido), Ok(300));
+        assert_eq!(contrato._retirar_fondos_parciales(20 + tres_dias, vendedor, id_pedido), Err(ErrorRetirarFondos::SinFondosDisponibles));
+
+        let pedido = contrato.pedidos.get(&id_pedido).unwrap();
+        assert_eq!(pedido.fondos_liberados, 300);
+        assert_eq!(pedido.fondos_en_custodia(), 700);
+        assert!(!pedido.fondos_fueron_transferidos);
+    }
+
+    #[ink::test]
+    fn despacho_parcial_completo_marca_despachado() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (vendedor, comprador, _, _, id_pedido) = preparar_pedido(&mut contrato, 20, 15, 10);
+
+        assert_eq!(contrato._pedido_despachado_parcial(10, vendedor, id_pedido, 0, None), Err(ErrorProductoDespachado::CantidadInvalida));
+        assert_eq!(contrato._pedido_despachado_parcial(10, vendedor, id_pedido, 11, None), Err(ErrorProductoDespachado::CantidadInvalida));
+
+        let seguimiento = Some(("Correo".into(), "AB1".into()));
+        assert_eq!(contrato._pedido_despachado_parcial(10, vendedor, id_pedido, 6, seguimiento), Ok(()));
+        assert_eq!(contrato._pedido_despachado_parcial(20, vendedor, id_pedido, 4, None), Ok(()));
+
+        let pedido = contrato.pedidos.get(&id_pedido).unwrap();
+        assert_eq!(pedido.estado, EstadoPedido::Despachado(20));
+        assert_eq!(pedido.seguimiento.len(), 1);
+
+        // recepción total de lo restante
+        assert_eq!(contrato._pedido_recibido_parcial(30, comprador, id_pedido, 2), Ok(()));
+        assert_eq!(contrato._pedido_recibido(40, comprador, id_pedido), Ok(()));
+
+        let pedido = contrato.pedidos.get(&id_pedido).unwrap();
+        assert_eq!(pedido.estado, EstadoPedido::Recibido(40));
+        assert_eq!(pedido.cantidad_recibida, 10);
+    }
+
+    #[ink::test]
+    fn retirar_fondos_tras_retiro_parcial_paga_el_resto() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (vendedor, comprador, _, _, id_pedido) = preparar_pedido(&mut contrato, 20, 15, 10);
+        let tres_dias = 259_200_000u64;
+
+        contrato._pedido_despachado_parcial(10, vendedor, id_pedido, 10, None).unwrap();
+        contrato._pedido_recibido_parcial(20, comprador, id_pedido, 4).unwrap();
+        assert_eq!(contrato._retirar_fondos_parciales(20 + tres_dias, vendedor, id_pedido), Ok(400));
+
+        contrato._pedido_recibido_parcial(30, comprador, id_pedido, 6).unwrap();
+        assert_eq!(contrato._retirar_fondos(30 + tres_dias + 1, vendedor, id_pedido), Ok(600));
+
+        let pedido = contrato.pedidos.get(&id_pedido).unwrap();
+        assert!(pedido.fondos_fueron_transferidos);
+        assert_eq!(pedido.fondos_liberados, 1000);
+    }
+
+    #[ink::test]
+    fn cancelar_unidades_restantes_vendedor_reembolsa_proporcional() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (vendedor, comprador, _, id_publicacion, id_pedido) = preparar_pedido(&mut contrato, 20, 15, 10);
+
+        contrato._pedido_despachado_parcial(10, vendedor, id_pedido, 7, None).unwrap();
+
+        // publicación: 15 ofertadas - 10 compradas
+        assert_eq!(contrato.publicaciones.get(&id_publicacion).unwrap().cantidad_ofertada, 5);
+
+        assert_eq!(contrato._cancelar_unidades_restantes(20, vendedor, id_pedido), Ok((comprador, 300)));
+        assert_eq!(contrato.publicaciones.get(&id_publicacion).unwrap().cantidad_ofertada, 8);
+
+        let pedido = contrato.pedidos.get(&id_pedido).unwrap();
+        assert_eq!(pedido.cantidad_cancelada, 3);
+        assert_eq!(pedido.cantidad_vigente(), 7);
+        assert_eq!(pedido.fondos_en_custodia(), 700);
+        assert_eq!(pedido.estado, EstadoPedido::Despachado(20));
+
+        // no quedan unidades por cancelar
+        assert_eq!(contrato._cancelar_unidades_restantes(30, vendedor, id_pedido), Err(ErrorCancelarUnidadesRestantes::SinUnidadesPendientes));
+    }
+
+    #[ink::test]
+    fn cancelar_unidades_restantes_comprador_politica_14_dias() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (_, comprador, _, id_publicacion, id_pedido) = preparar_pedido(&mut contrato, 20, 15, 10);
+        let catorce_dias = 1_209_600_000u64;
+
+        assert_eq!(contrato._cancelar_unidades_restantes(catorce_dias - 1, comprador, id_pedido), Err(ErrorCancelarUnidadesRestantes::NoConvalidaPoliticaDeCancelacion));
+        assert_eq!(contrato._cancelar_unidades_restantes(catorce_dias, comprador, id_pedido), Ok((comprador, 1000)));
+
+        // ninguna unidad fue despachada: el pedido se cancela
+        let pedido = contrato.pedidos.get(&id_pedido).unwrap();
+        assert_eq!(pedido.estado, EstadoPedido::Cancelado(catorce_dias));
+        assert!(pedido.fondos_fueron_transferidos);
+        assert_eq!(contrato.publicaciones.get(&id_publicacion).unwrap().cantidad_ofertada, 15);
+    }
+
+    #[ink::test]
+    fn cancelar_pedido_tras_recepcion_parcial_reembolsa_solo_lo_no_recibido() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (vendedor, comprador, _, id_publicacion, id_pedido) = preparar_pedido(&mut contrato, 20, 15, 10);
+        let tres_dias = 259_200_000u64;
+
+        // 6 despachadas, 4 recibidas, 2 en tránsito y 4 sin despachar
+        contrato._pedido_despachado_parcial(10, vendedor, id_pedido, 6, None).unwrap();
+        contrato._pedido_recibido_parcial(20, comprador, id_pedido, 4).unwrap();
+
+        assert_eq!(contrato._cancelar_pedido(30, vendedor, id_pedido), Ok(None));
+        assert_eq!(contrato._cancelar_pedido(40, comprador, id_pedido), Ok(Some((comprador, 600))));
+
+        // sólo vuelven a la publicación las unidades no despachadas
+        assert_eq!(contrato.publicaciones.get(&id_publicacion).unwrap().cantidad_ofertada, 9);
+
+        let pedido = contrato.pedidos.get(&id_pedido).unwrap();
+        assert_eq!(pedido.estado, EstadoPedido::Recibido(20));
+        assert_eq!(pedido.cantidad_vigente(), 4);
+        assert_eq!(pedido.fondos_en_custodia(), 400);
+        assert!(!pedido.fondos_fueron_transferidos);
+
+        // el vendedor conserva el valor de las unidades recibidas
+        assert_eq!(contrato._retirar_fondos(20 + tres_dias + 1, vendedor, id_pedido), Ok(400));
+
+        let owner = contrato.owner;
+        let reporte = contrato._verificar_invariantes(owner, AmbitoInvariantes::Stock, 0, 10, 0).unwrap();
+        assert!(reporte.violaciones.is_empty());
+    }
+
+    #[ink::test]
+    fn cancelar_pedido_despachado_parcialmente_solo_cancela_lo_no_despachado() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (vendedor, comprador, _, id_publicacion, id_pedido) = preparar_pedido(&mut contrato, 20, 15, 10);
+
+        // 9 de 10 despachadas: el vendedor puede actualizar su seguimiento
+        contrato._pedido_despachado_parcial(10, vendedor, id_pedido, 9, None).unwrap();
+        assert_eq!(contrato._actualizar_seguimiento(15, vendedor, id_pedido, "Correo".into(), "AB1".into()), Ok(()));
+
+        // el comprador sólo recupera el valor de la unidad no despachada
+        assert_eq!(contrato._cancelar_pedido(20, comprador, id_pedido), Ok(Some((comprador, 100))));
+        assert_eq!(contrato.publicaciones.get(&id_publicacion).unwrap().cantidad_ofertada, 6);
+
+        let pedido = contrato.pedidos.get(&id_pedido).unwrap();
+        assert_eq!(pedido.estado, EstadoPedido::Despachado(20));
+        assert_eq!(pedido.cantidad_vigente(), 9);
+        assert_eq!(pedido.fondos_en_custodia(), 900);
+
+        // las unidades despachadas sólo se cancelan de común acuerdo
+        assert_eq!(contrato._cancelar_pedido(30, comprador, id_pedido), Ok(None));
+        assert_eq!(contrato.pedidos.get(&id_pedido).unwrap().estado, EstadoPedido::Despachado(20));
+    }
+
+    #[ink::test]
+    fn cancelacion_mutua_tras_recepcion_parcial_no_repone_unidades_despachadas() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (vendedor, comprador, _, id_publicacion, id_pedido) = preparar_pedido(&mut contrato, 20, 15, 10);
+
+        // todo despachado, 4 recibidas
+        contrato._pedido_despachado_parcial(10, vendedor, id_pedido, 10, None).unwrap();
+        contrato._pedido_recibido_parcial(20, comprador, id_pedido, 4).unwrap();
+
+        assert_eq!(contrato._cancelar_pedido(30, comprador, id_pedido), Ok(None));
+        assert_eq!(contrato._cancelar_pedido(40, vendedor, id_pedido), Ok(Some((comprador, 600))));
+        assert_eq!(contrato.publicaciones.get(&id_publicacion).unwrap().cantidad_ofertada, 5);
+
+        let pedido = contrato.pedidos.get(&id_pedido).unwrap();
+        assert_eq!(pedido.estado, EstadoPedido::Recibido(20));
+        assert_eq!(pedido.fondos_en_custodia(), 400);
+    }
+
+
+    #[ink::test]
+    fn reembolso_parcial_reduce_fondos_a_retirar() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (vendedor, comprador, _, _, id_pedido) = preparar_pedido(&mut contrato, 20, 15, 10);
+        let tres_dias = 259_200_000u64;
+
+        assert_eq!(contrato._reembolso_parcial(5, vendedor, id_pedido, 150, "Demora en el envío".into()), Ok((comprador, 150)));
+        assert_eq!(contrato._reembolso_parcial(6, vendedor, id_pedido, 50, String::new()), Ok((comprador, 50)));
+
+        let pedido = contrato.pedidos.get(&id_pedido).unwrap();
+        assert_eq!(pedido.reembolsos.len(), 2);
+        assert_eq!(pedido.reembolsos[0], RegistroReembolso { timestamp: 5, valor: 150, motivo: "Demora en el envío".into() });
+        assert_eq!(pedido.fondos_en_custodia(), 800);
+
+        contrato._pedido_despachado(10, vendedor, id_pedido).unwrap();
+        contrato._pedido_recibido(20, comprador, id_pedido).unwrap();
+        assert_eq!(contrato._retirar_fondos(20 + tres_dias + 1, vendedor, id_pedido), Ok(800));
+    }
+
+    #[ink::test]
+    fn reembolso_parcial_validaciones() {
+        let mut contrato = RustaceoLibre::new(0);
+        let (vendedor, comprador, _, _, id_pedido) = preparar_pedido(&mut contrato, 20, 15, 10);
+
+        assert_eq!(contrato._reembolso_parcial(5, comprador, id_pedido, 100, String::new()), Err(ErrorReembolsoParcial::SoloVendedorPuede));
+        assert_eq!(contrato._reembolso_parcial(5, vendedor, id_pedido, 0, String::new()), Err(ErrorReembolsoParcial::ValorInvalido));
+        assert_eq!(contrato._reembolso_parcial(5, vendedor, id_pedido, 1001, String::new()), Err(ErrorReembolsoParcial::ValorInvalido));
+        assert_eq!(contrato._reembolso_parcial(5, vendedor, 99, 100, String::new()), Err(ErrorReembolsoParcial::PedidoInexistente));
+
+        // reembolsar la totalidad deja el pedido sin fondos en custodia
+        assert_eq!(contrato._reembolso_parcial(5, vendedor, id_pedido, 1000, String::new()), Ok((comprador, 1000)));
+        assert_eq!(contrato._reembolso_parcial(6, vendedor, id_pedido, 1, String::new()), Err(ErrorReembolsoParcial::FondosYaTransferidos));
+    }
 }
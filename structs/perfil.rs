@@ -0,0 +1,288 @@
+use ink::{prelude::string::String, primitives::AccountId};
+
+use crate::{rustaceo_libre::RustaceoLibre, structs::{multimedia::Multimedia, verificacion::NivelVerificacion}};
+
+/// Longitud máxima (en bytes) del nombre visible de un usuario
+pub const MAX_LARGO_NOMBRE_VISIBLE: usize = 32;
+/// Longitud máxima (en bytes) de la biografía de un usuario
+pub const MAX_LARGO_BIOGRAFIA: usize = 512;
+/// Longitud máxima (en bytes) del nombre de una tienda
+pub const MAX_LARGO_NOMBRE_TIENDA: usize = 64;
+/// Longitud máxima (en bytes) del texto de políticas de una tienda
+pub const MAX_LARGO_POLITICAS_TIENDA: usize = 2048;
+/// Cantidad máxima de días de preparación de un pedido que puede declarar una tienda
+pub const MAX_DIAS_PREPARACION: u32 = 60;
+
+/// Datos públicos de la tienda de un vendedor
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub struct Tienda {
+    pub nombre: String,
+    pub politicas: String,          // envíos, devoluciones, garantía, etc.
+    pub dias_preparacion: u32,      // días hábiles hasta despachar un pedido
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub struct PerfilUsuario {
+    pub nombre_visible: String,            // único, sin distinguir mayúsculas ni acentos
+    pub biografia: String,
+    pub avatar: Option<String>,            // CID de la imagen
+    pub hash_contacto: Option<[u8; 32]>,   // hash del dato de contacto. el contrato nunca conoce su contenido
+    pub pais: Option<String>,              // código ISO 3166-1 alfa-2
+    pub tienda: Option<Tienda>,            // sólo vendedores
+}
+
+/// Vista pública de un usuario: su perfil, si lo completó, junto con sus roles, calificaciones e insignia de verificación
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub struct PerfilPublico {
+    pub id: AccountId,
+    pub perfil: Option<PerfilUsuario>,
+    pub es_comprador: bool,
+    pub es_vendedor: bool,
+    pub calificacion_comprador: Option<u8>,
+    pub calificacion_vendedor: Option<u8>,
+    pub verificacion: Option<NivelVerificacion>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ErrorPerfil {
+    UsuarioNoRegistrado,
+    NoEsVendedor,
+    PerfilInexistente,
+    NombreInvalido,
+    NombreEnUso,
+    BiografiaInvalida,
+    AvatarInvalido,
+    PaisInvalido,
+    NombreTiendaInvalido,
+    PoliticasInvalidas,
+    DiasPreparacionInvalidos,
+}
+
+/// Letras no ASCII admitidas en un nombre visible, junto con la letra con la que se indexan
+const LETRAS_ACENTUADAS: [(char, char); 14] = [
+    ('á', 'a'), ('é', 'e'), ('í', 'i'), ('ó', 'o'), ('ú', 'u'), ('ü', 'u'), ('ñ', 'n'),
+    ('Á', 'a'), ('É', 'e'), ('Í', 'i'), ('Ó', 'o'), ('Ú', 'u'), ('Ü', 'u'), ('Ñ', 'n'),
+];
+
+/// Devuelve true si el nombre sólo usa letras ASCII o acentuadas del español, dígitos, '-', '_', '.'
+/// y espacios simples. Así se descartan caracteres invisibles, marcas combinables y letras de otros
+/// alfabetos que se confunden con las latinas, que permitirían registrar nombres indistinguibles de otro.
+fn nombre_visible_valido(nombre: &str) -> bool {
+    !nombre.contains("  ") && nombre.chars().all(|c| {
+        c.is_ascii_alphanumeric() || matches!(c, ' ' | '-' | '_' | '.') || LETRAS_ACENTUADAS.iter().any(|(letra, _)| *letra == c)
+    })
+}
+
+/// Clave del índice de nombres visibles: el nombre en minúsculas y sin acentos,
+/// para que la unicidad no distinga mayúsculas ni acentos
+pub(crate) fn clave_nombre_visible(nombre: &str) -> String {
+    nombre.chars()
+        .map(|c| LETRAS_ACENTUADAS.iter().find(|(letra, _)| *letra == c).map_or(c.to_ascii_lowercase(), |(_, base)| *base))
+        .collect()
+}
+
+impl RustaceoLibre {
+
+    /// Crea o reemplaza el perfil del usuario, conservando los datos de su tienda.
+    ///
+    /// Devolverá error si el usuario no está registrado, el nombre visible está vacío, excede su longitud máxima,
+    /// usa caracteres no admitidos o lo usa otro usuario (sin distinguir mayúsculas ni acentos), la biografía excede su longitud máxima, el avatar no es un CID válido
+    /// o el país no es un código ISO 3166-1 alfa-2.
+    pub fn _editar_perfil(&mut self, caller: AccountId, nombre_visible: String, biografia: String, avatar: Option<String>, hash_contacto: Option<[u8; 32]>, pais: Option<String>) -> Result<(), ErrorPerfil> {
+        // validar usuario
+        if !self.usuarios.contains_key(&caller) {
+            return Err(ErrorPerfil::UsuarioNoRegistrado);
+        }
+
+        // validar nombre
+        let nombre_visible = String::from(nombre_visible.trim());
+        if nombre_visible.is_empty() || nombre_visible.len() > MAX_LARGO_NOMBRE_VISIBLE || !nombre_visible_valido(&nombre_visible) {
+            return Err(ErrorPerfil::NombreInvalido);
+        }
+
+        let clave = clave_nombre_visible(&nombre_visible);
+        if self.nombres_visibles.get(&clave).is_some_and(|id| *id != caller) {
+            return Err(ErrorPerfil::NombreEnUso);
+        }
+
+        // validar biografía
+        if biografia.len() > MAX_LARGO_BIOGRAFIA {
+            return Err(ErrorPerfil::BiografiaInvalida);
+        }
+
+        // validar avatar
+        if avatar.as_ref().is_some_and(|cid| !Multimedia::cid_valido(cid)) {
+            return Err(ErrorPerfil::AvatarInvalido);
+        }
+
+        // validar país
+        if pais.as_ref().is_some_and(|pais| pais.len() != 2 || !pais.chars().all(|c| c.is_ascii_uppercase())) {
+            return Err(ErrorPerfil::PaisInvalido);
+        }
+
+        // liberar el nombre anterior
+        let anterior = self.perfiles.get(&caller).cloned();
+        if let Some(anterior) = &anterior {
+            self.nombres_visibles.remove(&clave_nombre_visible(&anterior.nombre_visible));
+        }
+
+        let perfil = PerfilUsuario {
+            nombre_visible,
+            biografia,
+            avatar,
+            hash_contacto,
+            pais,
+            tienda: anterior.and_then(|anterior| anterior.tienda),
+        };
+
+        self.nombres_visibles.insert(clave, caller);
+        self.perfiles.insert(caller, perfil);
+
+        Ok(())
+    }
+
+    //
+
+    /// Crea o reemplaza los datos de la tienda de un vendedor. Requiere haber creado el perfil.
+    ///
+    /// Devolverá error si el usuario no está registrado, no es vendedor, no tiene perfil,
+    /// el nombre de la tienda está vacío o excede su longitud máxima, las políticas exceden su longitud máxima
+    /// o los días de preparación superan el máximo.
+    pub fn _editar_tienda(&mut self, caller: AccountId, nombre: String, politicas: String, dias_preparacion: u32) -> Result<(), ErrorPerfil> {
+        // validar usuario
+        let Some(usuario) = self.usuarios.get(&caller)
+        else { return Err(ErrorPerfil::UsuarioNoRegistrado); };
+
+        if !usuario.es_vendedor() {
+            return Err(ErrorPerfil::NoEsVendedor);
+        }
+
+        let Some(mut perfil) = self.perfiles.get(&caller).cloned()
+        else { return Err(ErrorPerfil::PerfilInexistente); };
+
+        // validar datos
+        if nombre.is_empty() || nombre.len() > MAX_LARGO_NOMBRE_TIENDA {
+            return Err(ErrorPerfil::NombreTiendaInvalido);
+        }
+
+        if politicas.len() > MAX_LARGO_POLITICAS_TIENDA {
+            return Err(ErrorPerfil::PoliticasInvalidas);
+        }
+
+        if dias_preparacion > MAX_DIAS_PREPARACION {
+            return Err(ErrorPerfil::DiasPreparacionInvalidos);
+        }
+
+        perfil.tienda = Some(Tienda { nombre, politicas, dias_preparacion });
+        self.perfiles.insert(caller, perfil);
+
+        Ok(())
+    }
+
+    //
+
+    /// Devuelve la vista pública de un usuario: perfil, roles, calificaciones promedio como comprador y vendedor
+    /// e insignia de verificación.
+    ///
+    /// Devolverá None si el usuario no está registrado.
+    pub fn _ver_perfil(&self, id: AccountId) -> Option<PerfilPublico> {
+        let usuario = self.usuarios.get(&id)?;
+
+        Some(PerfilPublico {
+            id,
+            perfil: self.perfiles.get(&id).cloned(),
+            es_comprador: usuario.es_comprador(),
+            es_vendedor: usuario.es_vendedor(),
+            calificacion_comprador: self._ver_calificacion_comprador(id),
+            calificacion_vendedor: self._ver_calificacion_vendedor(id),
+            verificacion: self.verificaciones.get(&id).map(|verificacion| verificacion.nivel),
+        })
+    }
+
+    /// Dado un nombre visible, sin distinguir mayúsculas ni acentos, devuelve la ID del usuario que lo usa.
+    pub fn _buscar_usuario_por_nombre(&self, nombre_visible: String) -> Option<AccountId> {
+        self.nombres_visibles.get(&clave_nombre_visible(nombre_visible.trim())).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::usuario::RolDeSeleccion;
+
+    #[ink::test]
+    fn editar_y_ver_perfil() {
+        let mut contrato = RustaceoLibre::new(0);
+        let comprador = AccountId::from([0x1; 32]);
+        let vendedor = AccountId::from([0x2; 32]);
+        contrato._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
+        contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+
+        assert_eq!(contrato._editar_perfil(AccountId::from([0x9; 32]), "X".into(), String::new(), None, None, None), Err(ErrorPerfil::UsuarioNoRegistrado));
+        assert_eq!(contrato._editar_perfil(vendedor, "  ".into(), String::new(), None, None, None), Err(ErrorPerfil::NombreInvalido));
+        assert_eq!(contrato._editar_perfil(vendedor, "Tienda".into(), String::new(), Some("imagen.png".into()), None, None), Err(ErrorPerfil::AvatarInvalido));
+        assert_eq!(contrato._editar_perfil(vendedor, "Tienda".into(), String::new(), None, None, Some("arg".into())), Err(ErrorPerfil::PaisInvalido));
+        assert_eq!(contrato._editar_perfil(vendedor, "Ferretería Juan".into(), "Desde 1990".into(), None, Some([7; 32]), Some("AR".into())), Ok(()));
+
+        // el nombre es único sin distinguir mayúsculas
+        assert_eq!(contrato._editar_perfil(comprador, "FERRETERÍA JUAN".into(), String::new(), None, None, None), Err(ErrorPerfil::NombreEnUso));
+        assert_eq!(contrato._buscar_usuario_por_nombre("ferretería juan".into()), Some(vendedor));
+
+        // tienda
+        assert_eq!(contrato._editar_tienda(comprador, "T".into(), String::new(), 1), Err(ErrorPerfil::NoEsVendedor));
+        assert_eq!(contrato._editar_tienda(vendedor, "T".into(), String::new(), MAX_DIAS_PREPARACION + 1), Err(ErrorPerfil::DiasPreparacionInvalidos));
+        assert_eq!(contrato._editar_tienda(vendedor, "Ferretería Juan".into(), "Sin devoluciones".into(), 2), Ok(()));
+
+        // cambiar de nombre libera el anterior y conserva la tienda
+        assert_eq!(contrato._editar_perfil(vendedor, "Juan".into(), String::new(), None, None, None), Ok(()));
+        assert_eq!(contrato._buscar_usuario_por_nombre("Ferretería Juan".into()), None);
+        assert_eq!(contrato._editar_perfil(comprador, "Ferretería Juan".into(), String::new(), None, None, None), Ok(()));
+
+        let publico = contrato._ver_perfil(vendedor).unwrap();
+        assert!(publico.es_vendedor && !publico.es_comprador);
+        let perfil = publico.perfil.unwrap();
+        assert_eq!(perfil.nombre_visible, "Juan");
+        assert_eq!(perfil.tienda.unwrap().dias_preparacion, 2);
+        assert_eq!(publico.calificacion_vendedor, None);
+        assert_eq!(contrato._ver_perfil(AccountId::from([0x9; 32])), None);
+    }
+
+    #[ink::test]
+    fn nombre_visible_rechaza_caracteres_confundibles() {
+        let mut contrato = RustaceoLibre::new(0);
+        let usuario = AccountId::from([0x1; 32]);
+        let otro = AccountId::from([0x2; 32]);
+        contrato._registrar_usuario(usuario, RolDeSeleccion::Comprador).unwrap();
+        contrato._registrar_usuario(otro, RolDeSeleccion::Comprador).unwrap();
+        assert_eq!(contrato._editar_perfil(usuario, "José Pérez".into(), String::new(), None, None, None), Ok(()));
+
+        // caracteres invisibles, marcas combinables, letras de otros alfabetos y espacios repetidos
+        for nombre in ["José\u{200B} Pérez", "Jose\u{301} Pérez", "Jos\u{0435} Pérez", "José  Pérez"] {
+            assert_eq!(contrato._editar_perfil(otro, nombre.into(), String::new(), None, None, None), Err(ErrorPerfil::NombreInvalido));
+        }
+
+        // la unicidad no distingue acentos
+        assert_eq!(contrato._editar_perfil(otro, "JOSE PEREZ".into(), String::new(), None, None, None), Err(ErrorPerfil::NombreEnUso));
+        assert_eq!(contrato._buscar_usuario_por_nombre("jose perez".into()), Some(usuario));
+    }
+}
@@ -0,0 +1,43 @@
+use ink::primitives::AccountId;
+
+use crate::{
+    rustaceo_libre::RustaceoLibre,
+    structs::{
+        producto::DatosProducto,
+        usuario::RolDeSeleccion,
+    },
+};
+
+/// Registra un producto del vendedor con el stock inicial indicado y lo aprueba.
+pub(crate) fn registrar_producto_aprobado(contrato: &mut RustaceoLibre, vendedor: AccountId, stock: u32) -> u128 {
+    let id_producto = contrato._registrar_producto(0, vendedor, DatosProducto::new("Mate".into(), "Calabaza".into(), 1), stock).unwrap();
+    contrato._staff_aprobar_producto(contrato.owner, id_producto).unwrap();
+    id_producto
+}
+
+/// Registra un vendedor y un comprador, y publica a 100 cada una `cantidad_ofertada` unidades
+/// de un producto aprobado del vendedor con el stock inicial indicado.
+///
+/// Devuelve (vendedor, comprador, ID del producto, ID de la publicación).
+pub(crate) fn preparar_publicacion(contrato: &mut RustaceoLibre, stock: u32, cantidad_ofertada: u32) -> (AccountId, AccountId, u128, u128) {
+    let vendedor = AccountId::from([0x3; 32]); // [0x1; 32] es el owner
+    let comprador = AccountId::from([0x2; 32]);
+
+    contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+    contrato._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
+
+    let id_producto = registrar_producto_aprobado(contrato, vendedor, stock);
+    let id_publicacion = contrato._realizar_publicacion(0, vendedor, id_producto, cantidad_ofertada, 100).unwrap();
+
+    (vendedor, comprador, id_producto, id_publicacion)
+}
+
+/// Ídem preparar_publicacion, y el comprador compra `cantidad` unidades en el timestamp 0.
+///
+/// Devuelve (vendedor, comprador, ID del producto, ID de la publicación, ID del pedido).
+pub(crate) fn preparar_pedido(contrato: &mut RustaceoLibre, stock: u32, cantidad_ofertada: u32, cantidad: u32) -> (AccountId, AccountId, u128, u128, u128) {
+    let (vendedor, comprador, id_producto, id_publicacion) = preparar_publicacion(contrato, stock, cantidad_ofertada);
+    let id_pedido = contrato._comprar_producto(0, comprador, id_publicacion, cantidad, u128::from(cantidad) * 100).unwrap().id_nueva_transaccion;
+
+    (vendedor, comprador, id_producto, id_publicacion, id_pedido)
+}
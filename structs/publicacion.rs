@@ -6,6 +6,12 @@ use ink::primitives::AccountId;
 use ink::prelude::vec::Vec;
 
 use crate::rustaceo_libre::RustaceoLibre;
+use crate::structs::movimiento_stock::TipoMovimientoStock;
+use crate::structs::multimedia::Multimedia;
+use crate::structs::operador::PermisoOperador;
+use crate::structs::producto::EstadoProducto;
+use crate::structs::usuario::Usuario;
+use crate::structs::verificacion::NivelVerificacion;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[ink::scale_derive(Encode, Decode, TypeInfo)]
@@ -18,6 +24,33 @@ pub struct Publicacion {
     pub producto: u128,
     pub cantidad_ofertada: u32,
     pub precio_unitario: u128,
+    pub multimedia: Vec<Multimedia>,
+}
+
+/// Vista de una publicación junto con la insignia de verificación de su vendedor
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub struct DetallePublicacion {
+    pub publicacion: Publicacion,
+    pub verificacion_vendedor: Option<NivelVerificacion>,
+}
+
+/// Configuración de reposición automática de una publicación:
+/// cuando su cantidad ofertada cae por debajo de `minimo`, se repone hasta `objetivo`
+/// tomando unidades del stock personal del vendedor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub struct ReposicionAutomatica {
+    pub minimo: u32,
+    pub objetivo: u32,
 }
 
 //
@@ -31,6 +64,7 @@ impl Publicacion {
             producto,
             cantidad_ofertada,
             precio_unitario: precio,
+            multimedia: Vec::new(),
         }
     }
 }
@@ -51,6 +85,8 @@ pub enum ErrorRealizarPublicacion {
     NoEsVendedor,
     StockInsuficiente,
     PrecioCero,
+    ProductoNoAprobado,
+    VerificacionRequerida, // la categoría del producto exige un nivel de verificación que el vendedor no tiene
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -91,13 +127,74 @@ pub enum ErrorModificarCantidadOfertada {
     SinCambios,
     Desconocido,
     StockVendedorInsuficiente,
+    VerificacionRequerida, // al sumar unidades, la categoría del producto exige un nivel de verificación que el vendedor no tiene
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ErrorReposicionAutomatica {
+    UsuarioInexistente,
+    PublicacionInexistente,
+    NoEsElVendedor,
+    ParametrosInvalidos,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ErrorModificarPrecioPublicacion {
+    UsuarioInexistente,
+    PublicacionInexistente,
+    NoEsElVendedor,
+    PrecioCero,
+    SinCambios,
 }
 
 impl RustaceoLibre {
     /// Realiza una publicación con producto, precio y cantidad.
     /// 
-    /// Devuelve Error si el precio o la cantidad son 0, o si `caller` no existe o no es vendedor.
-    pub(crate) fn _realizar_publicacion(&mut self, caller: AccountId, id_producto: u128, cantidad_ofertada: u32, precio: u128) -> Result<u128, ErrorRealizarPublicacion> {
+    /// Devuelve Error si el precio o la cantidad son 0, si `caller` no existe o no es vendedor,
+    /// o si la categoría del producto exige un nivel de verificación que `caller` no tiene.
+    pub(crate) fn _realizar_publicacion(&mut self, timestamp: u64, caller: AccountId, id_producto: u128, cantidad_ofertada: u32, precio: u128) -> Result<u128, ErrorRealizarPublicacion> {
+        // un operador autorizado actúa en nombre del vendedor
+        let caller = self.actuar_como_vendedor(timestamp, caller, PermisoOperador::GestionarPublicaciones);
+
+        // validar
+        let nuevo_stock_vendedor = self.validar_publicacion(self.usuarios.get(&caller), id_producto, cantidad_ofertada, precio)?;
+        let Some(mut usuario) = self.usuarios.get(&caller).cloned() else {
+            return Err(ErrorRealizarPublicacion::UsuarioNoRegistrado);
+        };
+
+        // sustraer cantidad ofertada del stock del vendedor (pasará a formar parte de la oferta de la publicación)
+        usuario.establecer_stock_producto(&id_producto, &nuevo_stock_vendedor);
+
+        // obtener id de publicación e instanciarla
+        let id_publicacion = self.next_id_publicaciones();
+        let publicacion = Publicacion::new(caller, id_producto, cantidad_ofertada, precio);
+
+        // agregar al map principal
+        self.publicaciones.insert(id_publicacion, publicacion);
+
+        // agregar al vendedor
+        usuario.agregar_publicacion(id_publicacion);
+        self.usuarios.insert(usuario.id, usuario);
+        self.registrar_movimiento_stock(timestamp, caller, id_producto, TipoMovimientoStock::HaciaPublicacion(id_publicacion), cantidad_ofertada);
+
+        // fin
+        Ok(id_publicacion)
+    }
+
+    /// Valida una publicación del usuario dado, sin modificar el contrato.
+    /// 
+    /// Devolverá el stock que le quedaría al usuario tras publicar.
+    pub(crate) fn validar_publicacion(&self, usuario: Option<&Usuario>, id_producto: u128, cantidad_ofertada: u32, precio: u128) -> Result<u32, ErrorRealizarPublicacion> {
         // verificar precio
         if precio == 0 {
             return Err(ErrorRealizarPublicacion::PrecioCero);
@@ -109,7 +206,7 @@ impl RustaceoLibre {
         }
 
         // validar usuario
-        let Some(mut usuario) = self.usuarios.get(&caller).cloned() else {
+        let Some(usuario) = usuario else {
             return Err(ErrorRealizarPublicacion::UsuarioNoRegistrado);
         };
 
@@ -126,36 +223,36 @@ impl RustaceoLibre {
             return Err(ErrorRealizarPublicacion::StockInsuficiente);
         }
 
-        // último check: verificar que el producto exista
-        let Some(_) = self.productos.get(&id_producto)
+        // último check: verificar que el producto exista y haya sido aprobado por el Staff
+        let Some(producto) = self.productos.get(&id_producto)
         else { return Err(ErrorRealizarPublicacion::ProductoInexistente); };
 
-        // sustraer cantidad ofertada del stock del vendedor (pasará a formar parte de la oferta de la publicación)
-        let Some(nuevo_stock_vendedor) = stock_vendedor.checked_sub(cantidad_ofertada)
-        else { return Err(ErrorRealizarPublicacion::StockInsuficiente); };
-        usuario.establecer_stock_producto(&id_producto, &nuevo_stock_vendedor);
-
-        // obtener id de publicación e instanciarla
-        let id_publicacion = self.next_id_publicaciones();
-        let publicacion = Publicacion::new(caller, id_producto, cantidad_ofertada, precio);
+        if producto.estado != EstadoProducto::Aprobado {
+            return Err(ErrorRealizarPublicacion::ProductoNoAprobado);
+        }
 
-        // agregar al map principal
-        self.publicaciones.insert(id_publicacion, publicacion);
+        // verificar el nivel de verificación exigido por la categoría del producto
+        if !self.cumple_verificacion(usuario.id, self.nivel_verificacion_exigido(&producto.categoria)) {
+            return Err(ErrorRealizarPublicacion::VerificacionRequerida);
+        }
 
-        // agregar al vendedor
-        usuario.agregar_publicacion(id_publicacion);
-        self.usuarios.insert(usuario.id, usuario);
+        let Some(nuevo_stock_vendedor) = stock_vendedor.checked_sub(cantidad_ofertada)
+        else { return Err(ErrorRealizarPublicacion::StockInsuficiente); };
 
-        // fin
-        Ok(id_publicacion)
+        Ok(nuevo_stock_vendedor)
     }
 
     /// Modifica la cantidad ofertada en una publicación,
     /// modificando también el stock del vendedor.
     /// 
     /// Devuelve Error si el usuario no está registrado, la venta no existe,
-    /// el usuario no es el vendedor o la operación es imposible por falta de stock/cantidad ofertada.
-    pub(crate) fn _modificar_cantidad_ofertada(&mut self, caller: AccountId, id_publicacion: u128, nueva_cantidad_ofertada: u32) -> Result<(), ErrorModificarCantidadOfertada> {
+    /// el usuario no es el vendedor, la operación es imposible por falta de stock/cantidad ofertada
+    /// o se suman unidades sin el nivel de verificación exigido por la categoría del producto.
+    pub(crate) fn _modificar_cantidad_ofertada(&mut self, timestamp: u64, caller: AccountId, id_publicacion: u128, nueva_cantidad_ofertada: u32) -> Result<(), ErrorModificarCantidadOfertada> {
+        // un operador autorizado actúa en nombre del vendedor
+        let caller = self.publicaciones.get(&id_publicacion)
+            .map_or(caller, |p| self.actuar_como_vendedor_de(timestamp, caller, p.vendedor, PermisoOperador::GestionarPublicaciones));
+
         let Some(usuario) = self.usuarios.get(&caller).cloned()
         else { return Err(ErrorModificarCantidadOfertada::UsuarioInexistente); };
 
@@ -174,6 +271,15 @@ impl RustaceoLibre {
             return Err(ErrorModificarCantidadOfertada::SinCambios);
         }
 
+        // al sumar unidades, verificar el nivel de verificación exigido por la categoría del producto
+        if nueva_cantidad_ofertada > publicacion.cantidad_ofertada {
+            let nivel_exigido = self.productos.get(&publicacion.producto)
+                .and_then(|producto| self.nivel_verificacion_exigido(&producto.categoria));
+            if !self.cumple_verificacion(caller, nivel_exigido) {
+                return Err(ErrorModificarCantidadOfertada::VerificacionRequerida);
+            }
+        }
+
         let Some(stock_vendedor) = usuario.obtener_stock_producto(&publicacion.producto)
         else { return Err(ErrorModificarCantidadOfertada::StockVendedorInsuficiente) };
 
@@ -197,6 +303,13 @@ impl RustaceoLibre {
             nuevo_stock_vendedor = nuevo_stock_vendedor_int;
         }
 
+        // registrar el movimiento según el sentido de la transferencia
+        let movimiento = if nueva_cantidad_ofertada > publicacion.cantidad_ofertada {
+            (TipoMovimientoStock::HaciaPublicacion(id_publicacion), nueva_cantidad_ofertada.abs_diff(publicacion.cantidad_ofertada))
+        } else {
+            (TipoMovimientoStock::DesdePublicacion(id_publicacion), nueva_cantidad_ofertada.abs_diff(publicacion.cantidad_ofertada))
+        };
+
         // todo perfecto: ejecutar cambios
         let mut publicacion = publicacion.clone();
         let mut usuario = usuario;
@@ -205,14 +318,109 @@ impl RustaceoLibre {
 
         // actualizado:
         // se modificaba publicacion.cantidad_ofertada localmente, pero no se guardaba la publicación actualizada en self.publicaciones ni en self.usuarios
+        let id_producto = publicacion.producto;
         self.publicaciones.insert(id_publicacion, publicacion);
         self.usuarios.insert(usuario.id, usuario);
+        self.registrar_movimiento_stock(timestamp, caller, id_producto, movimiento.0, movimiento.1);
 
         Ok(())
     }
 
     //
 
+    /// Modifica el precio unitario de una publicación.
+    /// 
+    /// Devuelve Error si el usuario no está registrado, la publicación no existe,
+    /// el usuario no es el vendedor o el precio es 0 o igual al actual.
+    pub(crate) fn _modificar_precio_publicacion(&mut self, timestamp: u64, caller: AccountId, id_publicacion: u128, nuevo_precio: u128) -> Result<(), ErrorModificarPrecioPublicacion> {
+        // un operador autorizado actúa en nombre del vendedor
+        let caller = self.publicaciones.get(&id_publicacion)
+            .map_or(caller, |p| self.actuar_como_vendedor_de(timestamp, caller, p.vendedor, PermisoOperador::GestionarPublicaciones));
+
+        if !self.usuarios.contains_key(&caller) {
+            return Err(ErrorModificarPrecioPublicacion::UsuarioInexistente);
+        }
+
+        let Some(publicacion) = self.publicaciones.get(&id_publicacion)
+        else { return Err(ErrorModificarPrecioPublicacion::PublicacionInexistente); };
+
+        if publicacion.vendedor != caller {
+            return Err(ErrorModificarPrecioPublicacion::NoEsElVendedor);
+        }
+
+        if nuevo_precio == 0 {
+            return Err(ErrorModificarPrecioPublicacion::PrecioCero);
+        }
+
+        if nuevo_precio == publicacion.precio_unitario {
+            return Err(ErrorModificarPrecioPublicacion::SinCambios);
+        }
+
+        let mut publicacion = publicacion.clone();
+        publicacion.precio_unitario = nuevo_precio;
+        self.publicaciones.insert(id_publicacion, publicacion);
+
+        Ok(())
+    }
+
+    //
+
+    /// Activa, modifica o (con None) desactiva la reposición automática de una publicación.
+    /// 
+    /// Devuelve Error si el usuario no está registrado, la publicación no existe, el usuario no es el vendedor,
+    /// el mínimo es 0 o el objetivo es menor al mínimo.
+    pub(crate) fn _establecer_reposicion_automatica(&mut self, timestamp: u64, caller: AccountId, id_publicacion: u128, reposicion: Option<ReposicionAutomatica>) -> Result<(), ErrorReposicionAutomatica> {
+        // un operador autorizado actúa en nombre del vendedor
+        let caller = self.publicaciones.get(&id_publicacion)
+            .map_or(caller, |p| self.actuar_como_vendedor_de(timestamp, caller, p.vendedor, PermisoOperador::GestionarPublicaciones));
+
+        if !self.usuarios.contains_key(&caller) {
+            return Err(ErrorReposicionAutomatica::UsuarioInexistente);
+        }
+
+        let Some(publicacion) = self.publicaciones.get(&id_publicacion)
+        else { return Err(ErrorReposicionAutomatica::PublicacionInexistente); };
+
+        if publicacion.vendedor != caller {
+            return Err(ErrorReposicionAutomatica::NoEsElVendedor);
+        }
+
+        let Some(reposicion) = reposicion
+        else {
+            self.reposiciones_automaticas.remove(&id_publicacion);
+            return Ok(());
+        };
+
+        if reposicion.minimo == 0 || reposicion.objetivo < reposicion.minimo {
+            return Err(ErrorReposicionAutomatica::ParametrosInvalidos);
+        }
+
+        self.reposiciones_automaticas.insert(id_publicacion, reposicion);
+        Ok(())
+    }
+
+    /// Si la publicación tiene reposición automática y su cantidad ofertada cayó por debajo del mínimo,
+    /// la repone hasta el objetivo desde el stock del vendedor.
+    /// 
+    /// Devuelve true si se repuso. Si el vendedor no tiene stock suficiente
+    /// (StockVendedorInsuficiente) la publicación queda como está.
+    pub(crate) fn reponer_publicacion(&mut self, timestamp: u64, id_publicacion: u128) -> bool {
+        let Some(reposicion) = self.reposiciones_automaticas.get(&id_publicacion).cloned()
+        else { return false; };
+
+        let Some(publicacion) = self.publicaciones.get(&id_publicacion)
+        else { return false; };
+
+        if publicacion.cantidad_ofertada >= reposicion.minimo {
+            return false;
+        }
+
+        let vendedor = publicacion.vendedor;
+        self._modificar_cantidad_ofertada(timestamp, vendedor, id_publicacion, reposicion.objetivo).is_ok()
+    }
+
+    //
+
     /// Dada una ID, devuelve la publicación
     /// 
     /// Devolverá None si la publicación no existe
@@ -220,6 +428,16 @@ impl RustaceoLibre {
         self.publicaciones.get(&id_publicacion).cloned()
     }
 
+    /// Dada una ID, devuelve la publicación junto con la insignia de verificación de su vendedor.
+    /// 
+    /// Devolverá None si la publicación no existe
+    pub(crate) fn _ver_detalle_publicacion(&self, id_publicacion: u128) -> Option<DetallePublicacion> {
+        let publicacion = self.publicaciones.get(&id_publicacion)?.clone();
+        let verificacion_vendedor = self.verificaciones.get(&publicacion.vendedor).map(|verificacion| verificacion.nivel);
+
+        Some(DetallePublicacion { publicacion, verificacion_vendedor })
+    }
+
     //
 
     /// Devuelve todos los productos que correspondan al vendedor que ejecute esta función.
@@ -262,7 +480,7 @@ mod tests {
     
     use super::*;
     use ink::primitives::AccountId;
-    use crate::structs::{producto::{CategoriaProducto, Producto}, usuario::{DataComprador, DataVendedor, Rol, StockProductos, Usuario}};
+    use crate::structs::{producto::{DatosProducto, Producto}, usuario::{DataComprador, DataVendedor, Rol, StockProductos, Usuario}};
 
     #[test]
     fn test_publicacion_new_success() {
@@ -294,7 +512,7 @@ mod tests {
         usuario.establecer_stock_producto(&1, &15); // Configura stock inicial
         rustaceo.usuarios.insert(caller, usuario);
 
-        let result = rustaceo._realizar_publicacion(caller, 1, 10, 0);
+        let result = rustaceo._realizar_publicacion(0, caller, 1, 10, 0);
         assert!(matches!(result, Err(ErrorRealizarPublicacion::PrecioCero)));
     }
 
@@ -313,7 +531,7 @@ mod tests {
         usuario.establecer_stock_producto(&1, &15); // Configura stock inicial
         rustaceo.usuarios.insert(caller, usuario);
 
-        let result = rustaceo._realizar_publicacion(caller, 1, 0, 100);
+        let result = rustaceo._realizar_publicacion(0, caller, 1, 0, 100);
         assert!(matches!(result, Err(ErrorRealizarPublicacion::StockInsuficiente))); // Cantidad 0 implica stock insuficiente
     }
 
@@ -322,7 +540,7 @@ mod tests {
         let mut rustaceo = RustaceoLibre::new(0);
         let caller = AccountId::from([0x1; 32]);
 
-        let result = rustaceo._realizar_publicacion(caller, 1, 10, 100);
+        let result = rustaceo._realizar_publicacion(0, caller, 1, 10, 100);
         assert!(matches!(result, Err(ErrorRealizarPublicacion::UsuarioNoRegistrado)));
     }
 
@@ -335,10 +553,13 @@ mod tests {
             disputas_en_curso: Vec::new(),
             total_calificaciones: 0,
             cant_calificaciones: 0,
+            direcciones: Vec::new(),
+            publicaciones_favoritas: Vec::new(),
+            vendedores_seguidos: Vec::new(),
         }));
         rustaceo.usuarios.insert(caller, usuario);
 
-        let result = rustaceo._realizar_publicacion(caller, 1, 10, 100);
+        let result = rustaceo._realizar_publicacion(0, caller, 1, 10, 100);
         assert!(matches!(result, Err(ErrorRealizarPublicacion::NoEsVendedor)));
     }
 
@@ -356,9 +577,9 @@ mod tests {
         }));
         usuario.establecer_stock_producto(&1, &5); // Stock menor que cantidad ofertada
         rustaceo.usuarios.insert(caller, usuario);
-        rustaceo.productos.insert(1, Producto::new(String::from("Test"), String::from("Desc"), CategoriaProducto::Hogar));
+        rustaceo.productos.insert(1, Producto::new(caller, String::from("Test"), String::from("Desc"), 1));
 
-        let result = rustaceo._realizar_publicacion(caller, 1, 10, 100);
+        let result = rustaceo._realizar_publicacion(0, caller, 1, 10, 100);
         assert!(matches!(result, Err(ErrorRealizarPublicacion::StockInsuficiente)));
     }
 
@@ -377,7 +598,7 @@ mod tests {
         usuario.establecer_stock_producto(&1, &15); // Stock suficiente
         rustaceo.usuarios.insert(caller, usuario);
 
-        let result = rustaceo._realizar_publicacion(caller, 1, 10, 100);
+        let result = rustaceo._realizar_publicacion(0, caller, 1, 10, 100);
         assert!(matches!(result, Err(ErrorRealizarPublicacion::ProductoInexistente)));
     }
 
@@ -395,9 +616,9 @@ mod tests {
         }));
         usuario.establecer_stock_producto(&1, &15); // Stock inicial > cantidad ofertada
         rustaceo.usuarios.insert(caller, usuario);
-        rustaceo.productos.insert(1, Producto::new(String::from("Test"), String::from("Desc"), CategoriaProducto::Hogar));
+        rustaceo.productos.insert(1, Producto { estado: EstadoProducto::Aprobado, ..Producto::new(caller, String::from("Test"), String::from("Desc"), 1) });
 
-        let result = rustaceo._realizar_publicacion(caller, 1, 10, 100);
+        let result = rustaceo._realizar_publicacion(0, caller, 1, 10, 100);
         assert!(result.is_ok());
         let id = result.unwrap();
         assert_eq!(id, 0); // Primer ID generado
@@ -416,7 +637,7 @@ mod tests {
         let mut rustaceo = RustaceoLibre::new(0);
         let caller = AccountId::from([0x1; 32]);
 
-        let result = rustaceo._modificar_cantidad_ofertada(caller, 0, 15);
+        let result = rustaceo._modificar_cantidad_ofertada(0, caller, 0, 15);
         assert!(matches!(result, Err(ErrorModificarCantidadOfertada::UsuarioInexistente)));
     }
 
@@ -429,10 +650,13 @@ mod tests {
             disputas_en_curso: Vec::new(),
             total_calificaciones: 0,
             cant_calificaciones: 0,
+            direcciones: Vec::new(),
+            publicaciones_favoritas: Vec::new(),
+            vendedores_seguidos: Vec::new(),
         }));
         rustaceo.usuarios.insert(caller, usuario);
 
-        let result = rustaceo._modificar_cantidad_ofertada(caller, 0, 15);
+        let result = rustaceo._modificar_cantidad_ofertada(0, caller, 0, 15);
         assert!(matches!(result, Err(ErrorModificarCantidadOfertada::NoEsVendedor)));
     }
 
@@ -450,7 +674,7 @@ mod tests {
         }));
         rustaceo.usuarios.insert(caller, usuario);
 
-        let result = rustaceo._modificar_cantidad_ofertada(caller, 0, 15);
+        let result = rustaceo._modificar_cantidad_ofertada(0, caller, 0, 15);
         assert!(matches!(result, Err(ErrorModificarCantidadOfertada::PublicacionInexistente)));
     }
 
@@ -471,7 +695,7 @@ mod tests {
         let publicacion = Publicacion::new(otro_vendedor, 1, 10, 100);
         rustaceo.publicaciones.insert(0, publicacion);
 
-        let result = rustaceo._modificar_cantidad_ofertada(caller, 0, 15);
+        let result = rustaceo._modificar_cantidad_ofertada(0, caller, 0, 15);
         assert!(matches!(result, Err(ErrorModificarCantidadOfertada::NoEsElVendedor)));
     }
 
@@ -492,7 +716,7 @@ mod tests {
         let publicacion = Publicacion::new(caller, 1, 10, 100);
         rustaceo.publicaciones.insert(0, publicacion);
 
-        let result = rustaceo._modificar_cantidad_ofertada(caller, 0, 10);
+        let result = rustaceo._modificar_cantidad_ofertada(0, caller, 0, 10);
         assert!(matches!(result, Err(ErrorModificarCantidadOfertada::SinCambios)));
     }
 
@@ -512,9 +736,9 @@ mod tests {
         rustaceo.usuarios.insert(caller, usuario);
         let publicacion = Publicacion::new(caller, 1, 10, 100);
         rustaceo.publicaciones.insert(0, publicacion);
-        rustaceo.productos.insert(1, Producto::new(String::from("Test"), String::from("Desc"), CategoriaProducto::Hogar));
+        rustaceo.productos.insert(1, Producto::new(caller, String::from("Test"), String::from("Desc"), 1));
 
-        let result = rustaceo._modificar_cantidad_ofertada(caller, 0, 12); // Aumenta de 10 a 12
+        let result = rustaceo._modificar_cantidad_ofertada(0, caller, 0, 12); // Aumenta de 10 a 12
         assert!(result.is_ok());
         let updated_pub = rustaceo.publicaciones.get(&0).unwrap();
         assert_eq!(updated_pub.cantidad_ofertada, 12);
@@ -539,9 +763,9 @@ mod tests {
         rustaceo.usuarios.insert(caller, usuario);
         let publicacion = Publicacion::new(caller, 1, 10, 100);
         rustaceo.publicaciones.insert(0, publicacion);
-        rustaceo.productos.insert(1, Producto::new(String::from("Test"), String::from("Desc"), CategoriaProducto::Hogar));
+        rustaceo.productos.insert(1, Producto::new(caller, String::from("Test"), String::from("Desc"), 1));
 
-        let result = rustaceo._modificar_cantidad_ofertada(caller, 0, 8); // Disminuye de 10 a 8
+        let result = rustaceo._modificar_cantidad_ofertada(0, caller, 0, 8); // Disminuye de 10 a 8
         assert!(result.is_ok());
         let updated_pub = rustaceo.publicaciones.get(&0).unwrap();
         assert_eq!(updated_pub.cantidad_ofertada, 8); // Now should pass with insert
@@ -566,9 +790,9 @@ mod tests {
         rustaceo.usuarios.insert(caller, usuario);
         let publicacion = Publicacion::new(caller, 1, 10, 100);
         rustaceo.publicaciones.insert(0, publicacion);
-        rustaceo.productos.insert(1, Producto::new(String::from("Test"), String::from("Desc"), CategoriaProducto::Hogar));
+        rustaceo.productos.insert(1, Producto::new(caller, String::from("Test"), String::from("Desc"), 1));
 
-        let result = rustaceo._modificar_cantidad_ofertada(caller, 0, 15); // Aumenta a 15
+        let result = rustaceo._modificar_cantidad_ofertada(0, caller, 0, 15); // Aumenta a 15
         assert!(result.is_ok());
         let updated_pub = rustaceo.publicaciones.get(&0).unwrap();
         assert_eq!(updated_pub.cantidad_ofertada, 15); // Verifica que se actualice a 15
@@ -620,6 +844,9 @@ mod tests {
             disputas_en_curso: Vec::new(),
             total_calificaciones: 0,
             cant_calificaciones: 0,
+            direcciones: Vec::new(),
+            publicaciones_favoritas: Vec::new(),
+            vendedores_seguidos: Vec::new(),
         }));
         rustaceo.usuarios.insert(caller, usuario);
 
@@ -671,4 +898,75 @@ mod tests {
         assert_eq!(publicacion.cantidad_ofertada, 10);
         assert_eq!(publicacion.precio_unitario, 100);
     }
-}
\ No newline at end of file
+
+
+    #[ink::test]
+    fn reposicion_automatica_repone_desde_stock_vendedor() {
+        use crate::structs::usuario::RolDeSeleccion;
+
+        let mut rustaceo = RustaceoLibre::new(0);
+        let vendedor = AccountId::from([0x1; 32]);
+        let comprador = AccountId::from([0x2; 32]);
+        rustaceo._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+        rustaceo._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
+
+        // stock del vendedor: 10 - 4 publicadas = 6
+        let id_producto = rustaceo._registrar_producto(0, vendedor, DatosProducto::new("Yerba".into(), "1kg".into(), 1), 10).unwrap();
+        rustaceo._staff_aprobar_producto(rustaceo.owner, id_producto).unwrap();
+        let id_publicacion = rustaceo._realizar_publicacion(0, vendedor, id_producto, 4, 100).unwrap();
+
+        let reposicion = ReposicionAutomatica { minimo: 2, objetivo: 5 };
+        assert_eq!(rustaceo._establecer_reposicion_automatica(0, comprador, id_publicacion, Some(reposicion.clone())), Err(ErrorReposicionAutomatica::NoEsElVendedor));
+        assert_eq!(rustaceo._establecer_reposicion_automatica(0, vendedor, id_publicacion, Some(ReposicionAutomatica { minimo: 3, objetivo: 2 })), Err(ErrorReposicionAutomatica::ParametrosInvalidos));
+        assert_eq!(rustaceo._establecer_reposicion_automatica(0, vendedor, id_publicacion, Some(reposicion)), Ok(()));
+
+        // 4 -> 2: no cae por debajo del mínimo
+        rustaceo._comprar_producto(0, comprador, id_publicacion, 2, 200).unwrap();
+        assert_eq!(rustaceo.publicaciones.get(&id_publicacion).unwrap().cantidad_ofertada, 2);
+
+        // 2 -> 1: se repone hasta 5 (6 - 4 = 2 quedan en el stock del vendedor)
+        rustaceo._comprar_producto(0, comprador, id_publicacion, 1, 100).unwrap();
+        assert_eq!(rustaceo.publicaciones.get(&id_publicacion).unwrap().cantidad_ofertada, 5);
+        assert_eq!(rustaceo.usuarios.get(&vendedor).unwrap().obtener_stock_producto(&id_producto), Some(2));
+
+        // 5 -> 1: faltan 4 unidades pero el vendedor sólo tiene 2. la publicación queda como está
+        rustaceo._comprar_producto(0, comprador, id_publicacion, 4, 400).unwrap();
+        assert_eq!(rustaceo.publicaciones.get(&id_publicacion).unwrap().cantidad_ofertada, 1);
+        assert_eq!(rustaceo.usuarios.get(&vendedor).unwrap().obtener_stock_producto(&id_producto), Some(2));
+
+        // desactivar
+        assert_eq!(rustaceo._establecer_reposicion_automatica(0, vendedor, id_publicacion, None), Ok(()));
+        assert!(!rustaceo.reposiciones_automaticas.contains_key(&id_publicacion));
+    }
+
+    #[ink::test]
+    fn reposicion_automatica_alerta_stock_bajo_producto() {
+        use crate::structs::{alerta_stock::AlertaStockBajoProducto, usuario::RolDeSeleccion};
+
+        let mut rustaceo = RustaceoLibre::new(0);
+        let vendedor = AccountId::from([0x3; 32]); // [0x1; 32] es el owner
+        let comprador = AccountId::from([0x2; 32]);
+        rustaceo._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+        rustaceo._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
+
+        // stock del vendedor: 10 - 4 publicadas = 6
+        let id_producto = rustaceo._registrar_producto(0, vendedor, DatosProducto::new("Yerba".into(), "1kg".into(), 1), 10).unwrap();
+        rustaceo._staff_aprobar_producto(rustaceo.owner, id_producto).unwrap();
+        let id_publicacion = rustaceo._realizar_publicacion(0, vendedor, id_producto, 4, 100).unwrap();
+        rustaceo._establecer_reposicion_automatica(0, vendedor, id_publicacion, Some(ReposicionAutomatica { minimo: 2, objetivo: 5 })).unwrap();
+        rustaceo._establecer_umbral_stock_producto(0, vendedor, id_producto, 3).unwrap();
+
+        // 4 -> 2: sin reposición, el stock del producto no cambia
+        let resultado = rustaceo._comprar_producto(0, comprador, id_publicacion, 2, 200).unwrap();
+        assert_eq!(resultado.alerta_stock_bajo_producto, None);
+
+        // 2 -> 1: se repone hasta 5 y el stock del producto baja de 6 a 2, por debajo del umbral
+        let resultado = rustaceo._comprar_producto(0, comprador, id_publicacion, 1, 100).unwrap();
+        assert_eq!(resultado.alerta_stock_bajo_producto, Some(AlertaStockBajoProducto {
+            vendedor,
+            producto: id_producto,
+            cantidad: 2,
+            umbral: 3,
+        }));
+    }
+}
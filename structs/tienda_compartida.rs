@@ -0,0 +1,441 @@
+use ink::{prelude::vec::Vec, primitives::AccountId};
+
+use crate::{rustaceo_libre::RustaceoLibre, structs::{operador::{Operador, PermisoOperador}, pedido::ErrorRetirarFondos}};
+
+/// Cantidad máxima de socios de una tienda compartida
+pub const MAX_SOCIOS_TIENDA: usize = 10;
+
+/// Permisos con los que cada socio opera la tienda en el día a día, sin necesidad de aprobación
+const PERMISOS_SOCIO: [PermisoOperador; 4] = [
+    PermisoOperador::GestionarStock,
+    PermisoOperador::GestionarPublicaciones,
+    PermisoOperador::Despachar,
+    PermisoOperador::ResponderDisputas,
+];
+
+/// Cuenta de vendedor administrada por varios socios. Los socios la operan como operadores con todos los permisos,
+/// pero los retiros de fondos y los cambios de dirección de cobro, de socios o de umbral
+/// requieren que una propuesta reúna la aprobación de `umbral` socios.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub struct TiendaCompartida {
+    pub socios: Vec<AccountId>,
+    pub umbral: u32,               // aprobaciones necesarias para ejecutar una propuesta
+    pub direccion_cobro: AccountId, // destino de los fondos que cobra la tienda
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum AccionPropuesta {
+    RetirarFondos(u128),           // ID del pedido
+    RetirarFondosParciales(u128),  // ID del pedido
+    CambiarDireccionCobro(AccountId),
+    CambiarSocios { socios: Vec<AccountId>, umbral: u32 },
+    Disolver,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum EstadoPropuesta {
+    Pendiente,
+    Ejecutada(u64),
+    Cancelada(u64),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub struct PropuestaTienda {
+    pub id: u128,
+    pub tienda: AccountId,
+    pub timestamp: u64,
+    pub proponente: AccountId,
+    pub accion: AccionPropuesta,
+    pub aprobaciones: Vec<AccountId>,
+    pub estado: EstadoPropuesta,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ErrorTiendaCompartida {
+    UsuarioNoRegistrado,
+    NoEsVendedor,
+    TiendaYaCompartida,
+    TiendaInexistente,
+    SociosInvalidos,         // vacíos, repetidos, demasiados, usuarios registrados, el owner o miembros del Staff
+    SocioDeOtroVendedor,
+    UmbralInvalido,
+    NoEsSocio,
+    PedidoInvalido,          // no existe o no es una venta de la tienda
+    PropuestaInexistente,
+    PropuestaNoPendiente,
+    PropuestaYaAprobada,
+    NoEsElProponente,
+    RetiroFallido(ErrorRetirarFondos),
+}
+
+impl RustaceoLibre {
+
+    /// Convierte la cuenta de vendedor del caller en una tienda compartida con los socios, el umbral de aprobación
+    /// y la dirección de cobro indicados. Cada socio queda autorizado como operador de la tienda con todos los permisos.
+    ///
+    /// Devolverá error si el usuario no está registrado, no es vendedor o ya es una tienda compartida,
+    /// los socios son inválidos u operan para otro vendedor, o el umbral es 0 o mayor a la cantidad de socios.
+    pub fn _crear_tienda_compartida(&mut self, caller: AccountId, socios: Vec<AccountId>, umbral: u32, direccion_cobro: AccountId) -> Result<(), ErrorTiendaCompartida> {
+        // validar usuario
+        let Some(usuario) = self.usuarios.get(&caller)
+        else { return Err(ErrorTiendaCompartida::UsuarioNoRegistrado); };
+
+        if !usuario.es_vendedor() {
+            return Err(ErrorTiendaCompartida::NoEsVendedor);
+        }
+
+        if self.tiendas_compartidas.contains_key(&caller) {
+            return Err(ErrorTiendaCompartida::TiendaYaCompartida);
+        }
+
+        // validar socios y umbral
+        self.validar_socios_tienda(caller, &socios, umbral)?;
+
+        self.autorizar_socios_tienda(caller, &socios);
+        self.tiendas_compartidas.insert(caller, TiendaCompartida { socios, umbral, direccion_cobro });
+
+        Ok(())
+    }
+
+    //
+
+    /// Un socio propone una acción sobre la tienda que requiere aprobación.
+    /// La propuesta se ejecuta cuando reúne tantas aprobaciones como el umbral de la tienda.
+    ///
+    /// Devolverá error si la tienda no existe, el caller no es socio, el pedido a retirar no es una venta de la tienda,
+    /// o los socios o el umbral propuestos son inválidos.
+    pub fn _proponer_accion_tienda(&mut self, timestamp: u64, caller: AccountId, tienda: AccountId, accion: AccionPropuesta) -> Result<u128, ErrorTiendaCompartida> {
+        // validar tienda y socio
+        let Some(datos_tienda) = self.tiendas_compartidas.get(&tienda)
+        else { return Err(ErrorTiendaCompartida::TiendaInexistente); };
+
+        if !datos_tienda.socios.contains(&caller) {
+            return Err(ErrorTiendaCompartida::NoEsSocio);
+        }
+
+        // validar acción
+        match &accion {
+            AccionPropuesta::RetirarFondos(id_pedido) | AccionPropuesta::RetirarFondosParciales(id_pedido) => {
+                if !self.pedidos.get(id_pedido).is_some_and(|pedido| pedido.vendedor == tienda) {
+                    return Err(ErrorTiendaCompartida::PedidoInvalido);
+                }
+            },
+            AccionPropuesta::CambiarSocios { socios, umbral } => self.validar_socios_tienda(tienda, socios, *umbral)?,
+            AccionPropuesta::CambiarDireccionCobro(_) | AccionPropuesta::Disolver => (),
+        }
+
+        let id = self.next_id_propuestas_tiendas();
+        let propuesta = PropuestaTienda {
+            id,
+            tienda,
+            timestamp,
+            proponente: caller,
+            accion,
+            aprobaciones: Vec::new(),
+            estado: EstadoPropuesta::Pendiente,
+        };
+        self.propuestas_tiendas.insert(id, propuesta);
+
+        Ok(id)
+    }
+
+    //
+
+    /// Un socio aprueba una propuesta pendiente. Sólo cuentan las aprobaciones de quienes siguen siendo socios.
+    /// Si con esta aprobación se alcanza el umbral, la propuesta se ejecuta. Si la ejecución falla,
+    /// la aprobación no se registra y la propuesta sigue pendiente.
+    ///
+    /// Devolverá la tienda y el valor liberado si se ejecutó un retiro de fondos, que debería transferirse
+    /// a la dirección de cobro de la tienda en lib.rs.
+    /// Devolverá error si la propuesta no existe o no está pendiente, el caller no es socio o ya la aprobó,
+    /// o la ejecución falla.
+    pub fn _aprobar_propuesta_tienda(&mut self, timestamp: u64, caller: AccountId, id_propuesta: u128) -> Result<Option<(AccountId, u128)>, ErrorTiendaCompartida> {
+        // validar propuesta
+        let Some(mut propuesta) = self.propuestas_tiendas.get(&id_propuesta).cloned()
+        else { return Err(ErrorTiendaCompartida::PropuestaInexistente); };
+
+        if propuesta.estado != EstadoPropuesta::Pendiente {
+            return Err(ErrorTiendaCompartida::PropuestaNoPendiente);
+        }
+
+        // validar socio
+        let Some(tienda) = self.tiendas_compartidas.get(&propuesta.tienda).cloned()
+        else { return Err(ErrorTiendaCompartida::TiendaInexistente); };
+
+        if !tienda.socios.contains(&caller) {
+            return Err(ErrorTiendaCompartida::NoEsSocio);
+        }
+
+        propuesta.aprobaciones.retain(|socio| tienda.socios.contains(socio));
+        if propuesta.aprobaciones.contains(&caller) {
+            return Err(ErrorTiendaCompartida::PropuestaYaAprobada);
+        }
+
+        propuesta.aprobaciones.push(caller);
+
+        // sin umbral alcanzado: sólo registrar la aprobación
+        if (propuesta.aprobaciones.len() as u32) < tienda.umbral {
+            self.propuestas_tiendas.insert(id_propuesta, propuesta);
+            return Ok(None);
+        }
+
+        // ejecutar
+        let id_tienda = propuesta.tienda;
+        let pago = match &propuesta.accion {
+            AccionPropuesta::RetirarFondos(id_pedido) => {
+                let valor = self.liberar_fondos_pedido(timestamp, id_tienda, *id_pedido)
+                    .map_err(ErrorTiendaCompartida::RetiroFallido)?;
+                Some((id_tienda, valor))
+            },
+            AccionPropuesta::RetirarFondosParciales(id_pedido) => {
+                let valor = self.liberar_fondos_parciales_pedido(timestamp, id_tienda, *id_pedido)
+                    .map_err(ErrorTiendaCompartida::RetiroFallido)?;
+                Some((id_tienda, valor))
+            },
+            AccionPropuesta::CambiarDireccionCobro(direccion_cobro) => {
+                let mut tienda = tienda;
+                tienda.direccion_cobro = *direccion_cobro;
+                self.tiendas_compartidas.insert(id_tienda, tienda);
+                None
+            },
+            AccionPropuesta::CambiarSocios { socios, umbral } => {
+                // los socios propuestos pudieron dejar de ser válidos mientras la propuesta esperaba aprobación
+                self.validar_socios_tienda(id_tienda, socios, *umbral)?;
+
+                for socio in tienda.socios.iter().filter(|socio| !socios.contains(socio)) {
+                    self.operadores.remove(socio);
+                }
+                self.autorizar_socios_tienda(id_tienda, socios);
+
+                let mut tienda = tienda;
+                tienda.socios = socios.clone();
+                tienda.umbral = *umbral;
+                self.tiendas_compartidas.insert(id_tienda, tienda);
+                None
+            },
+            AccionPropuesta::Disolver => {
+                self.disolver_tienda_compartida(timestamp, id_tienda);
+                None
+            },
+        };
+
+        propuesta.estado = EstadoPropuesta::Ejecutada(timestamp);
+        self.propuestas_tiendas.insert(id_propuesta, propuesta);
+
+        Ok(pago)
+    }
+
+    //
+
+    /// El proponente cancela una propuesta pendiente.
+    ///
+    /// Devolverá error si la propuesta no existe, no está pendiente o el caller no es su proponente.
+    pub fn _cancelar_propuesta_tienda(&mut self, timestamp: u64, caller: AccountId, id_propuesta: u128) -> Result<(), ErrorTiendaCompartida> {
+        let Some(mut propuesta) = self.propuestas_tiendas.get(&id_propuesta).cloned()
+        else { return Err(ErrorTiendaCompartida::PropuestaInexistente); };
+
+        if propuesta.estado != EstadoPropuesta::Pendiente {
+            return Err(ErrorTiendaCompartida::PropuestaNoPendiente);
+        }
+
+        if propuesta.proponente != caller {
+            return Err(ErrorTiendaCompartida::NoEsElProponente);
+        }
+
+        propuesta.estado = EstadoPropuesta::Cancelada(timestamp);
+        self.propuestas_tiendas.insert(id_propuesta, propuesta);
+
+        Ok(())
+    }
+
+    //
+
+    /// Devuelve los datos de una tienda compartida, si existe.
+    pub fn _ver_tienda_compartida(&self, tienda: AccountId) -> Option<TiendaCompartida> {
+        self.tiendas_compartidas.get(&tienda).cloned()
+    }
+
+    /// Devuelve las propuestas de una tienda compartida, de la más antigua a la más reciente.
+    pub fn _ver_propuestas_tienda(&self, tienda: AccountId) -> Vec<PropuestaTienda> {
+        self.propuestas_tiendas.values()
+            .filter(|propuesta| propuesta.tienda == tienda)
+            .cloned()
+            .collect()
+    }
+
+    //
+
+    /// Devuelve la cuenta a la que deben transferirse los fondos que cobra un usuario:
+    /// la dirección de cobro si es una tienda compartida o la propia cuenta en caso contrario.
+    pub(crate) fn direccion_cobro(&self, cuenta: AccountId) -> AccountId {
+        self.tiendas_compartidas.get(&cuenta).map_or(cuenta, |tienda| tienda.direccion_cobro)
+    }
+
+    /// Quita la tienda compartida, la autorización de sus socios y cancela sus propuestas pendientes.
+    /// La cuenta sigue registrada como vendedor.
+    pub(crate) fn disolver_tienda_compartida(&mut self, timestamp: u64, tienda: AccountId) {
+        let Some(datos_tienda) = self.tiendas_compartidas.remove(&tienda)
+        else { return; };
+
+        for socio in datos_tienda.socios.iter() {
+            self.operadores.remove(socio);
+        }
+
+        for propuesta in self.propuestas_tiendas.values_mut() {
+            if propuesta.tienda == tienda && propuesta.estado == EstadoPropuesta::Pendiente {
+                propuesta.estado = EstadoPropuesta::Cancelada(timestamp);
+            }
+        }
+    }
+
+    fn validar_socios_tienda(&self, tienda: AccountId, socios: &[AccountId], umbral: u32) -> Result<(), ErrorTiendaCompartida> {
+        if socios.is_empty() || socios.len() > MAX_SOCIOS_TIENDA {
+            return Err(ErrorTiendaCompartida::SociosInvalidos);
+        }
+
+        for (i, socio) in socios.iter().enumerate() {
+            if socios[..i].contains(socio) || *socio == tienda || self.usuarios.contains_key(socio)
+                || *socio == self.owner || self.staff.contains(socio) {
+                return Err(ErrorTiendaCompartida::SociosInvalidos);
+            }
+
+            if self.operadores.get(socio).is_some_and(|operador| operador.vendedor != tienda) {
+                return Err(ErrorTiendaCompartida::SocioDeOtroVendedor);
+            }
+        }
+
+        if umbral == 0 || umbral as usize > socios.len() {
+            return Err(ErrorTiendaCompartida::UmbralInvalido);
+        }
+
+        Ok(())
+    }
+
+    fn autorizar_socios_tienda(&mut self, tienda: AccountId, socios: &[AccountId]) {
+        for socio in socios {
+            let operador = Operador { vendedor: tienda, permisos: Vec::from(PERMISOS_SOCIO), vencimiento: None };
+            self.operadores.insert(*socio, operador);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::{cuenta::ErrorEliminarCuenta, operador::ErrorOperador, producto::DatosProducto, usuario::{ErrorDescenderRolUsuario, RolDeSeleccion}};
+
+    const CUATRO_DIAS: u64 = 345_600_000;
+
+    #[ink::test]
+    fn crear_tienda_compartida_valida_socios() {
+        let mut contrato = RustaceoLibre::new(0);
+        let tienda = AccountId::from([0x1; 32]);
+        let comprador = AccountId::from([0x2; 32]);
+        let socio_a = AccountId::from([0x3; 32]);
+        let socio_b = AccountId::from([0x4; 32]);
+        contrato._registrar_usuario(tienda, RolDeSeleccion::Vendedor).unwrap();
+        contrato._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
+
+        assert_eq!(contrato._crear_tienda_compartida(comprador, Vec::from([socio_a]), 1, comprador), Err(ErrorTiendaCompartida::NoEsVendedor));
+        assert_eq!(contrato._crear_tienda_compartida(tienda, Vec::new(), 1, tienda), Err(ErrorTiendaCompartida::SociosInvalidos));
+        assert_eq!(contrato._crear_tienda_compartida(tienda, Vec::from([socio_a, socio_a]), 1, tienda), Err(ErrorTiendaCompartida::SociosInvalidos));
+        assert_eq!(contrato._crear_tienda_compartida(tienda, Vec::from([socio_a, comprador]), 1, tienda), Err(ErrorTiendaCompartida::SociosInvalidos));
+        assert_eq!(contrato._crear_tienda_compartida(tienda, Vec::from([socio_a, socio_b]), 3, tienda), Err(ErrorTiendaCompartida::UmbralInvalido));
+        assert_eq!(contrato._crear_tienda_compartida(tienda, Vec::from([socio_a, socio_b]), 2, tienda), Ok(()));
+        assert_eq!(contrato._crear_tienda_compartida(tienda, Vec::from([socio_a]), 1, tienda), Err(ErrorTiendaCompartida::TiendaYaCompartida));
+
+        // los socios operan la tienda y no pueden ser revocados como operadores
+        assert_eq!(contrato.actuar_como_vendedor(0, socio_a, PermisoOperador::Despachar), tienda);
+        assert_eq!(contrato._revocar_operador(tienda, socio_a), Err(ErrorOperador::OperadorEsSocio));
+    }
+
+    #[ink::test]
+    fn retiro_de_fondos_requiere_umbral_de_socios() {
+        let mut contrato = RustaceoLibre::new(0);
+        let tienda = AccountId::from([0x1; 32]);
+        let comprador = AccountId::from([0x2; 32]);
+        let socio_a = AccountId::from([0x3; 32]);
+        let socio_b = AccountId::from([0x4; 32]);
+        let cobro = AccountId::from([0x5; 32]);
+        contrato._registrar_usuario(tienda, RolDeSeleccion::Vendedor).unwrap();
+        contrato._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
+        contrato._crear_tienda_compartida(tienda, Vec::from([socio_a, socio_b]), 2, tienda).unwrap();
+
+        let id_producto = contrato._registrar_producto(0, tienda, DatosProducto::new("A".into(), "B".into(), 1), 10).unwrap();
+        contrato._staff_aprobar_producto(contrato.owner, id_producto).unwrap();
+        let id_publicacion = contrato._realizar_publicacion(0, tienda, id_producto, 5, 100).unwrap();
+        let id_pedido = contrato._comprar_producto(0, comprador, id_publicacion, 1, 100).unwrap().id_nueva_transaccion;
+
+        // un socio despacha sin aprobación de los demás
+        contrato._pedido_despachado(0, socio_a, id_pedido).unwrap();
+        contrato._pedido_recibido(0, comprador, id_pedido).unwrap();
+
+        // la cuenta de la tienda no puede retirar sola
+        assert_eq!(contrato._retirar_fondos(CUATRO_DIAS, tienda, id_pedido), Err(ErrorRetirarFondos::RequiereAprobacionSocios));
+
+        // cambiar la dirección de cobro
+        assert_eq!(contrato._proponer_accion_tienda(0, comprador, tienda, AccionPropuesta::Disolver), Err(ErrorTiendaCompartida::NoEsSocio));
+        let id_cambio = contrato._proponer_accion_tienda(0, socio_a, tienda, AccionPropuesta::CambiarDireccionCobro(cobro)).unwrap();
+        assert_eq!(contrato._aprobar_propuesta_tienda(0, socio_a, id_cambio), Ok(None));
+        assert_eq!(contrato._aprobar_propuesta_tienda(0, socio_a, id_cambio), Err(ErrorTiendaCompartida::PropuestaYaAprobada));
+        assert_eq!(contrato.direccion_cobro(tienda), tienda);
+        assert_eq!(contrato._aprobar_propuesta_tienda(0, socio_b, id_cambio), Ok(None));
+        assert_eq!(contrato.direccion_cobro(tienda), cobro);
+
+        // retirar fondos: un retiro fallido no registra la aprobación
+        let id_retiro = contrato._proponer_accion_tienda(0, socio_b, tienda, AccionPropuesta::RetirarFondos(id_pedido)).unwrap();
+        assert_eq!(contrato._aprobar_propuesta_tienda(0, socio_a, id_retiro), Ok(None));
+        assert_eq!(contrato._aprobar_propuesta_tienda(0, socio_b, id_retiro), Err(ErrorTiendaCompartida::RetiroFallido(ErrorRetirarFondos::EstadoNoEsDespachado)));
+        assert_eq!(contrato._aprobar_propuesta_tienda(CUATRO_DIAS, socio_b, id_retiro), Ok(Some((tienda, 100))));
+        assert_eq!(contrato.propuestas_tiendas.get(&id_retiro).unwrap().estado, EstadoPropuesta::Ejecutada(CUATRO_DIAS));
+        assert_eq!(contrato._aprobar_propuesta_tienda(CUATRO_DIAS, socio_a, id_retiro), Err(ErrorTiendaCompartida::PropuestaNoPendiente));
+    }
+
+    #[ink::test]
+    fn tienda_compartida_debe_disolverse_antes_de_dejar_de_vender() {
+        let mut contrato = RustaceoLibre::new(0);
+        let tienda = AccountId::from([0x1; 32]);
+        let socio_a = AccountId::from([0x3; 32]);
+        let socio_b = AccountId::from([0x4; 32]);
+        contrato._registrar_usuario(tienda, RolDeSeleccion::Ambos).unwrap();
+        contrato._crear_tienda_compartida(tienda, Vec::from([socio_a, socio_b]), 2, tienda).unwrap();
+
+        // la cuenta de la tienda no puede desarmarla sola
+        assert_eq!(contrato._descender_rol_usuario(tienda, RolDeSeleccion::Vendedor), Err(ErrorDescenderRolUsuario::TiendaCompartida));
+        assert_eq!(contrato._eliminar_cuenta(0, tienda), Err(ErrorEliminarCuenta::TiendaCompartida));
+
+        // disuelta por los socios
+        let id_disolver = contrato._proponer_accion_tienda(0, socio_a, tienda, AccionPropuesta::Disolver).unwrap();
+        contrato._aprobar_propuesta_tienda(0, socio_a, id_disolver).unwrap();
+        contrato._aprobar_propuesta_tienda(0, socio_b, id_disolver).unwrap();
+        assert_eq!(contrato._ver_tienda_compartida(tienda), None);
+
+        assert_eq!(contrato._descender_rol_usuario(tienda, RolDeSeleccion::Vendedor), Ok(()));
+        assert_eq!(contrato._eliminar_cuenta(0, tienda), Ok(()));
+    }
+}
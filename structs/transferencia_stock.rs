@@ -0,0 +1,328 @@
+use ink::{prelude::vec::Vec, primitives::AccountId};
+
+use crate::{rustaceo_libre::RustaceoLibre, structs::movimiento_stock::TipoMovimientoStock};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum EstadoTransferenciaStock { // (u64 -> timestamp)
+    Ofrecida(u64),
+    Aceptada(u64),  // por el receptor
+    Rechazada(u64), // por el receptor
+    Cancelada(u64), // por el emisor
+}
+
+/// Transferencia de unidades de un producto desde el stock de un vendedor (emisor) al de otro (receptor).
+/// Las unidades se descuentan del stock del emisor al ofrecerla y quedan retenidas hasta que se acepte,
+/// rechace o cancele. El precio, si no es 0, lo paga el receptor al aceptarla.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub struct TransferenciaStock {
+    pub id: u128,
+    pub emisor: AccountId,
+    pub receptor: AccountId,
+    pub producto: u128,
+    pub cantidad: u32,
+    pub precio: u128, // total a pagar por el receptor (0 si no hay pago)
+    pub estado: EstadoTransferenciaStock,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ErrorTransferenciaStock {
+    UsuarioNoRegistrado,
+    NoEsVendedor,
+    ReceptorInvalido,
+    ProductoInexistente,
+    ProductoNoDisponible,
+    CantidadInvalida,
+    StockInsuficiente,
+    TransferenciaInexistente,
+    NoEsElEmisor,
+    NoEsElReceptor,
+    TransferenciaNoOfrecida,
+    ValorTransferidoInsuficiente,
+    Desconocido,
+}
+
+/// Resultado de aceptar una transferencia de stock: a quién y cuánto pagar y cuánto devolver al receptor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResultadoAceptarTransferenciaStock {
+    pub emisor: AccountId,
+    pub pago: u128,
+    pub monto_transferido_sobrante: u128,
+}
+
+impl RustaceoLibre {
+
+    //
+
+    /// Ofrece a otro vendedor una cantidad de un producto del stock del caller, opcionalmente a cambio de un precio total.
+    /// Las unidades se descuentan del stock del emisor hasta que la transferencia se acepte, rechace o cancele.
+    ///
+    /// Devuelve la ID de la transferencia.
+    /// Devolverá error si el emisor o el receptor no están registrados o no son vendedores, son el mismo usuario,
+    /// el producto no existe, fue rechazado o fusionado, la cantidad es 0 o el emisor no tiene stock suficiente.
+    pub fn _ofrecer_transferencia_stock(&mut self, timestamp: u64, caller: AccountId, receptor: AccountId, id_producto: u128, cantidad: u32, precio: u128) -> Result<u128, ErrorTransferenciaStock> {
+        // validar cantidad
+        if cantidad == 0 {
+            return Err(ErrorTransferenciaStock::CantidadInvalida);
+        }
+
+        // validar emisor
+        let Some(mut emisor) = self.usuarios.get(&caller).cloned()
+        else { return Err(ErrorTransferenciaStock::UsuarioNoRegistrado); };
+
+        if !emisor.es_vendedor() {
+            return Err(ErrorTransferenciaStock::NoEsVendedor);
+        }
+
+        // validar receptor
+        if receptor == caller {
+            return Err(ErrorTransferenciaStock::ReceptorInvalido);
+        }
+
+        let Some(usuario_receptor) = self.usuarios.get(&receptor)
+        else { return Err(ErrorTransferenciaStock::ReceptorInvalido); };
+
+        if !usuario_receptor.es_vendedor() {
+            return Err(ErrorTransferenciaStock::ReceptorInvalido);
+        }
+
+        // validar producto
+        let Some(producto) = self.productos.get(&id_producto)
+        else { return Err(ErrorTransferenciaStock::ProductoInexistente); };
+
+        if !producto.admite_stock() {
+            return Err(ErrorTransferenciaStock::ProductoNoDisponible);
+        }
+
+        // validar stock del emisor
+        let stock_actual = emisor.obtener_stock_producto(&id_producto).unwrap_or(0);
+        let Some(nuevo_stock) = stock_actual.checked_sub(cantidad)
+        else { return Err(ErrorTransferenciaStock::StockInsuficiente); };
+
+        // retener unidades
+        if !emisor.establecer_stock_producto(&id_producto, &nuevo_stock) {
+            return Err(ErrorTransferenciaStock::Desconocido);
+        }
+        self.usuarios.insert(caller, emisor);
+
+        // guardar transferencia
+        let id_transferencia = self.next_id_transferencias_stock();
+        self.transferencias_stock.insert(id_transferencia, TransferenciaStock {
+            id: id_transferencia,
+            emisor: caller,
+            receptor,
+            producto: id_producto,
+            cantidad,
+            precio,
+            estado: EstadoTransferenciaStock::Ofrecida(timestamp),
+        });
+        self.registrar_movimiento_stock(timestamp, caller, id_producto, TipoMovimientoStock::TransferenciaEnviada(id_transferencia), cantidad);
+
+        Ok(id_transferencia)
+    }
+
+    //
+
+    /// El receptor acepta una transferencia ofrecida, pagando su precio con el valor transferido.
+    /// Las unidades se acreditan en su stock y el pago se envía al emisor en la misma transacción.
+    ///
+    /// Devolverá error si el usuario no está registrado o no es vendedor, la transferencia no existe,
+    /// no es su receptor, ya no está ofrecida o el valor transferido no alcanza para pagarla.
+    pub fn _aceptar_transferencia_stock(&mut self, timestamp: u64, caller: AccountId, id_transferencia: u128, valor_transferido: u128) -> Result<ResultadoAceptarTransferenciaStock, ErrorTransferenciaStock> {
+        // validar usuario
+        let Some(mut receptor) = self.usuarios.get(&caller).cloned()
+        else { return Err(ErrorTransferenciaStock::UsuarioNoRegistrado); };
+
+        if !receptor.es_vendedor() {
+            return Err(ErrorTransferenciaStock::NoEsVendedor);
+        }
+
+        // validar transferencia
+        let Some(mut transferencia) = self.transferencias_stock.get(&id_transferencia).cloned()
+        else { return Err(ErrorTransferenciaStock::TransferenciaInexistente); };
+
+        if transferencia.receptor != caller {
+            return Err(ErrorTransferenciaStock::NoEsElReceptor);
+        }
+
+        if !matches!(transferencia.estado, EstadoTransferenciaStock::Ofrecida(_)) {
+            return Err(ErrorTransferenciaStock::TransferenciaNoOfrecida);
+        }
+
+        // validar pago
+        let Some(monto_transferido_sobrante) = valor_transferido.checked_sub(transferencia.precio)
+        else { return Err(ErrorTransferenciaStock::ValorTransferidoInsuficiente); };
+
+        // acreditar unidades
+        let stock_actual = receptor.obtener_stock_producto(&transferencia.producto).unwrap_or(0);
+        let Some(nuevo_stock) = stock_actual.checked_add(transferencia.cantidad)
+        else { return Err(ErrorTransferenciaStock::CantidadInvalida); };
+
+        if !receptor.establecer_stock_producto(&transferencia.producto, &nuevo_stock) {
+            return Err(ErrorTransferenciaStock::Desconocido);
+        }
+        self.usuarios.insert(caller, receptor);
+
+        // actualizar transferencia
+        transferencia.estado = EstadoTransferenciaStock::Aceptada(timestamp);
+        let (emisor, id_producto, cantidad, pago) = (transferencia.emisor, transferencia.producto, transferencia.cantidad, transferencia.precio);
+        self.transferencias_stock.insert(id_transferencia, transferencia);
+        self.registrar_movimiento_stock(timestamp, caller, id_producto, TipoMovimientoStock::TransferenciaRecibida(id_transferencia), cantidad);
+
+        // fin. se transferirán los fondos en lib.rs
+        Ok(ResultadoAceptarTransferenciaStock { emisor, pago, monto_transferido_sobrante })
+    }
+
+    //
+
+    /// El receptor rechaza o el emisor cancela una transferencia ofrecida.
+    /// Las unidades retenidas vuelven al stock del emisor.
+    ///
+    /// Devolverá error si el usuario no está registrado, la transferencia no existe,
+    /// el usuario no participa en ella o ya no está ofrecida.
+    pub fn _anular_transferencia_stock(&mut self, timestamp: u64, caller: AccountId, id_transferencia: u128) -> Result<(), ErrorTransferenciaStock> {
+        // validar usuario
+        if !self.usuarios.contains_key(&caller) {
+            return Err(ErrorTransferenciaStock::UsuarioNoRegistrado);
+        }
+
+        // validar transferencia
+        let Some(mut transferencia) = self.transferencias_stock.get(&id_transferencia).cloned()
+        else { return Err(ErrorTransferenciaStock::TransferenciaInexistente); };
+
+        let nuevo_estado = if transferencia.emisor == caller {
+            EstadoTransferenciaStock::Cancelada(timestamp)
+        } else if transferencia.receptor == caller {
+            EstadoTransferenciaStock::Rechazada(timestamp)
+        } else {
+            return Err(ErrorTransferenciaStock::NoEsElReceptor);
+        };
+
+        if !matches!(transferencia.estado, EstadoTransferenciaStock::Ofrecida(_)) {
+            return Err(ErrorTransferenciaStock::TransferenciaNoOfrecida);
+        }
+
+        // devolver unidades al emisor
+        let Some(mut emisor) = self.usuarios.get(&transferencia.emisor).cloned()
+        else { return Err(ErrorTransferenciaStock::Desconocido); };
+
+        let stock_actual = emisor.obtener_stock_producto(&transferencia.producto).unwrap_or(0);
+        let nuevo_stock = stock_actual.saturating_add(transferencia.cantidad);
+        if !emisor.establecer_stock_producto(&transferencia.producto, &nuevo_stock) {
+            return Err(ErrorTransferenciaStock::Desconocido);
+        }
+        self.usuarios.insert(transferencia.emisor, emisor);
+
+        // actualizar transferencia
+        transferencia.estado = nuevo_estado;
+        let (id_emisor, id_producto, cantidad) = (transferencia.emisor, transferencia.producto, transferencia.cantidad);
+        self.transferencias_stock.insert(id_transferencia, transferencia);
+        self.registrar_movimiento_stock(timestamp, id_emisor, id_producto, TipoMovimientoStock::TransferenciaDevuelta(id_transferencia), cantidad);
+
+        Ok(())
+    }
+
+    //
+
+    /// Devuelve las transferencias de stock en las que participa el usuario, como emisor o receptor.
+    pub fn _ver_transferencias_stock(&self, caller: AccountId) -> Vec<TransferenciaStock> {
+        self.transferencias_stock.values()
+            .filter(|transferencia| transferencia.emisor == caller || transferencia.receptor == caller)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::{
+        producto::DatosProducto,
+        usuario::RolDeSeleccion,
+    };
+
+    #[ink::test]
+    fn transferencia_con_pago_mueve_stock() {
+        let mut contrato = RustaceoLibre::new(0);
+        let distribuidor = AccountId::from([0x1; 32]);
+        let revendedor = AccountId::from([0x2; 32]);
+        contrato._registrar_usuario(distribuidor, RolDeSeleccion::Vendedor).unwrap();
+        contrato._registrar_usuario(revendedor, RolDeSeleccion::Vendedor).unwrap();
+        let id_producto = contrato._registrar_producto(0, distribuidor, DatosProducto::new("Yerba".into(), "1kg".into(), 1), 100).unwrap();
+
+        let id = contrato._ofrecer_transferencia_stock(1, distribuidor, revendedor, id_producto, 40, 4000).unwrap();
+        assert_eq!(contrato.usuarios.get(&distribuidor).unwrap().obtener_stock_producto(&id_producto), Some(60));
+
+        assert_eq!(contrato._aceptar_transferencia_stock(2, revendedor, id, 3999), Err(ErrorTransferenciaStock::ValorTransferidoInsuficiente));
+
+        let resultado = contrato._aceptar_transferencia_stock(2, revendedor, id, 4500).unwrap();
+        assert_eq!(resultado, ResultadoAceptarTransferenciaStock { emisor: distribuidor, pago: 4000, monto_transferido_sobrante: 500 });
+        assert_eq!(contrato.usuarios.get(&revendedor).unwrap().obtener_stock_producto(&id_producto), Some(40));
+        assert_eq!(contrato.transferencias_stock.get(&id).unwrap().estado, EstadoTransferenciaStock::Aceptada(2));
+
+        // no puede aceptarse dos veces
+        assert_eq!(contrato._aceptar_transferencia_stock(3, revendedor, id, 4000), Err(ErrorTransferenciaStock::TransferenciaNoOfrecida));
+
+        let movimientos = contrato._ver_movimientos_stock(revendedor, revendedor, id_producto, 0, 50).unwrap();
+        assert_eq!(movimientos.last().map(|m| m.tipo.clone()), Some(TipoMovimientoStock::TransferenciaRecibida(id)));
+    }
+
+    #[ink::test]
+    fn transferencia_rechazada_o_cancelada_devuelve_stock() {
+        let mut contrato = RustaceoLibre::new(0);
+        let distribuidor = AccountId::from([0x1; 32]);
+        let revendedor = AccountId::from([0x2; 32]);
+        contrato._registrar_usuario(distribuidor, RolDeSeleccion::Vendedor).unwrap();
+        contrato._registrar_usuario(revendedor, RolDeSeleccion::Vendedor).unwrap();
+        let id_producto = contrato._registrar_producto(0, distribuidor, DatosProducto::new("Yerba".into(), "1kg".into(), 1), 100).unwrap();
+
+        let id_a = contrato._ofrecer_transferencia_stock(1, distribuidor, revendedor, id_producto, 30, 0).unwrap();
+        let id_b = contrato._ofrecer_transferencia_stock(1, distribuidor, revendedor, id_producto, 20, 0).unwrap();
+        assert_eq!(contrato.usuarios.get(&distribuidor).unwrap().obtener_stock_producto(&id_producto), Some(50));
+
+        assert_eq!(contrato._anular_transferencia_stock(2, revendedor, id_a), Ok(()));
+        assert_eq!(contrato._anular_transferencia_stock(2, distribuidor, id_b), Ok(()));
+        assert_eq!(contrato.transferencias_stock.get(&id_a).unwrap().estado, EstadoTransferenciaStock::Rechazada(2));
+        assert_eq!(contrato.transferencias_stock.get(&id_b).unwrap().estado, EstadoTransferenciaStock::Cancelada(2));
+        assert_eq!(contrato.usuarios.get(&distribuidor).unwrap().obtener_stock_producto(&id_producto), Some(100));
+
+        assert_eq!(contrato._ver_transferencias_stock(revendedor).len(), 2);
+    }
+
+    #[ink::test]
+    fn ofrecer_transferencia_validaciones() {
+        let mut contrato = RustaceoLibre::new(0);
+        let distribuidor = AccountId::from([0x1; 32]);
+        let revendedor = AccountId::from([0x2; 32]);
+        contrato._registrar_usuario(distribuidor, RolDeSeleccion::Vendedor).unwrap();
+        contrato._registrar_usuario(revendedor, RolDeSeleccion::Vendedor).unwrap();
+        let id_producto = contrato._registrar_producto(0, distribuidor, DatosProducto::new("Yerba".into(), "1kg".into(), 1), 100).unwrap();
+        let comprador = AccountId::from([0x3; 32]);
+        contrato._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
+
+        assert_eq!(contrato._ofrecer_transferencia_stock(0, distribuidor, distribuidor, id_producto, 1, 0), Err(ErrorTransferenciaStock::ReceptorInvalido));
+        assert_eq!(contrato._ofrecer_transferencia_stock(0, distribuidor, comprador, id_producto, 1, 0), Err(ErrorTransferenciaStock::ReceptorInvalido));
+        assert_eq!(contrato._ofrecer_transferencia_stock(0, distribuidor, revendedor, 99, 1, 0), Err(ErrorTransferenciaStock::ProductoInexistente));
+        assert_eq!(contrato._ofrecer_transferencia_stock(0, distribuidor, revendedor, id_producto, 0, 0), Err(ErrorTransferenciaStock::CantidadInvalida));
+        assert_eq!(contrato._ofrecer_transferencia_stock(0, distribuidor, revendedor, id_producto, 101, 0), Err(ErrorTransferenciaStock::StockInsuficiente));
+        assert_eq!(contrato._ofrecer_transferencia_stock(0, revendedor, distribuidor, id_producto, 1, 0), Err(ErrorTransferenciaStock::StockInsuficiente));
+
+        // un producto rechazado no puede transferirse aunque el emisor conserve su stock
+        contrato._staff_rechazar_producto(contrato.owner, id_producto, "Prohibido".into()).unwrap();
+        assert_eq!(contrato._ofrecer_transferencia_stock(0, distribuidor, revendedor, id_producto, 1, 0), Err(ErrorTransferenciaStock::ProductoNoDisponible));
+    }
+}
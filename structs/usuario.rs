@@ -1,6 +1,6 @@
 use ink::{prelude::vec::Vec, primitives::AccountId};
 
-use crate::rustaceo_libre::RustaceoLibre;
+use crate::{rustaceo_libre::RustaceoLibre, structs::{pedido::EstadoPedido, transferencia_stock::EstadoTransferenciaStock}};
 
 //
 // struct custom para almacenar stock de productos
@@ -43,6 +43,13 @@ impl StockProductos {
     }
 }
 
+    /// Quita el producto del vector doble, devolviendo el stock que tenía si existía.
+    pub fn remove(&mut self, id_producto: &u128) -> Option<u32> {
+        let index = self.productos.binary_search(id_producto).ok()?;
+        self.productos.remove(index);
+        Some(self.stock.remove(index))
+    }
+
 }
 
 //
@@ -60,8 +67,20 @@ pub struct DataComprador {
     pub disputas_en_curso: Vec<u128>,
     pub total_calificaciones: u64,
     pub cant_calificaciones: u32,
+    pub direcciones: Vec<Vec<u8>>, // direcciones de entrega cifradas por el comprador. el contrato nunca conoce su contenido
+    pub publicaciones_favoritas: Vec<u128>,
+    pub vendedores_seguidos: Vec<AccountId>,
 }
 
+/// Cantidad máxima de direcciones cifradas que un comprador puede guardar en su perfil
+pub const MAX_DIRECCIONES_COMPRADOR: usize = 5;
+/// Longitud máxima (en bytes) de una dirección cifrada
+pub const MAX_LARGO_DIRECCION_CIFRADA: usize = 512;
+/// Cantidad máxima de publicaciones favoritas de un comprador
+pub const MAX_PUBLICACIONES_FAVORITAS: usize = 100;
+/// Cantidad máxima de vendedores seguidos por un comprador
+pub const MAX_VENDEDORES_SEGUIDOS: usize = 100;
+
 //
 // data vendedor
 //
@@ -177,6 +196,38 @@ impl Usuario {
         self.obtener_data_comprador().map(|data| data.disputas_en_curso)
     }
 
+    /// Reemplaza el registro completo de DataComprador del usuario.
+    /// 
+    /// Devolverá false si no es comprador.
+    pub fn establecer_data_comprador(&mut self, data_comprador: DataComprador) -> bool {
+        let nuevo_rol = match &self.rol {
+            Rol::Comprador(_) => Rol::Comprador(data_comprador),
+            Rol::Vendedor(_) => return false,
+            Rol::Ambos(_, data_vendedor) => Rol::Ambos(data_comprador, data_vendedor.clone()),
+        };
+
+        self.rol = nuevo_rol;
+        true
+    }
+
+    /// Devuelve las direcciones cifradas que el usuario haya guardado.
+    /// Devolverá None si no es comprador.
+    pub fn obtener_direcciones(&self) -> Option<Vec<Vec<u8>>> {
+        self.obtener_data_comprador().map(|data| data.direcciones)
+    }
+
+    /// Devuelve las publicaciones favoritas del usuario.
+    /// Devolverá None si no es comprador.
+    pub fn obtener_publicaciones_favoritas(&self) -> Option<Vec<u128>> {
+        self.obtener_data_comprador().map(|data| data.publicaciones_favoritas)
+    }
+
+    /// Devuelve los vendedores que sigue el usuario.
+    /// Devolverá None si no es comprador.
+    pub fn obtener_vendedores_seguidos(&self) -> Option<Vec<AccountId>> {
+        self.obtener_data_comprador().map(|data| data.vendedores_seguidos)
+    }
+
     /// Devuelve el registro completo de DataVendedor del usuario.
     /// Devolverá None si no es vendedor.
     pub fn obtener_data_vendedor(&self) -> Option<DataVendedor> {
@@ -386,6 +437,22 @@ impl Usuario {
         true
     }
 
+    /// Quita el registro de stock de un producto, devolviendo el stock que tenía.
+    /// 
+    /// Devolverá None si no es vendedor o no tenía registro de stock del producto.
+    pub fn quitar_stock_producto(&mut self, id_producto: &u128) -> Option<u32> {
+        let mut nuevo_data_vendedor = self.obtener_data_vendedor()?;
+        let stock = nuevo_data_vendedor.stock_productos.remove(id_producto)?;
+
+        self.rol = match &self.rol {
+            Rol::Comprador(_) => return None,
+            Rol::Vendedor(_) => Rol::Vendedor(nuevo_data_vendedor),
+            Rol::Ambos(compras, _) => Rol::Ambos(compras.clone(), nuevo_data_vendedor),
+        };
+
+        Some(stock)
+    }
+
     /// Calificar al usuario como comprador
     /// 
     /// Devolverá true si la operación fue exitosa o false en caso contrario.
@@ -487,8 +554,56 @@ pub enum ErrorAscenderRolUsuario {
     MaximoRolAsignado,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ErrorDescenderRolUsuario {
+    UsuarioInexistente,
+    RolInvalido,              // el usuario no es Ambos o se indicó Ambos
+    PedidosPendientes,        // pendientes o despachados en el rol a quitar
+    FondosEnCustodia,
+    DisputasEnCurso,
+    PublicacionesActivas,     // publicaciones con unidades ofertadas
+    TransferenciasPendientes,
+    TiendaCompartida,         // al dejar de vender, debe disolverse antes con una propuesta aprobada por los socios
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ErrorDireccionComprador {
+    UsuarioNoRegistrado,
+    NoEsComprador,
+    DireccionInvalida,
+    MaximoDireccionesAlcanzado,
+    DireccionInexistente,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ErrorListasComprador {
+    UsuarioNoRegistrado,
+    NoEsComprador,
+    PublicacionInexistente,
+    VendedorInexistente,
+    YaIncluido,
+    NoIncluido,
+    MaximoAlcanzado,
+}
+
 impl RustaceoLibre {
     /// Registra un usuario en el Mapping de usuarios.
+    /// Si la cuenta había sido eliminada, recupera sus calificaciones anteriores.
     /// 
     /// Devuelve error si el usuario ya existe.
     pub fn _registrar_usuario(&mut self, caller: AccountId, rol_seleccion: RolDeSeleccion) -> Result<(), ErrorRegistrarUsuario>  {
@@ -503,13 +618,15 @@ impl RustaceoLibre {
             RolDeSeleccion::Ambos => Rol::Ambos(DataComprador::default(), DataVendedor::default())
         };
 
-        let usuario = Usuario::new(caller, rol);
+        let mut usuario = Usuario::new(caller, rol);
+        self.restaurar_calificaciones(&mut usuario, true, true);
         self.usuarios.insert(caller, usuario.clone()); // por algún motivo es un préstamo, se supone que se clona.
 
         Ok(())
     }
 
-    /// Si el usuario es Vendedor o Comprador, cambia su rol a Ambos sin perder información
+    /// Si el usuario es Vendedor o Comprador, cambia su rol a Ambos sin perder información.
+    /// Si antes había dejado el rol que recupera, se restauran los datos archivados de ese rol.
     /// 
     /// Devuelve error si el usuario no existe o ya posee el rol Ambos.
     pub fn _ascender_rol_usuario(&mut self, caller: AccountId) -> Result<(), ErrorAscenderRolUsuario> {
@@ -519,19 +636,332 @@ impl RustaceoLibre {
             return Err(ErrorAscenderRolUsuario::UsuarioInexistente);
         };
 
-        let nuevo_rol = match &usuario.rol {
-            Rol::Comprador(compras) => Rol::Ambos(compras.clone(), Default::default()),
-            Rol::Vendedor(data_vendedor) => Rol::Ambos(Default::default(), data_vendedor.clone()),
+        let archivado_comprador = self.datos_comprador_archivados.get(&caller).cloned();
+        let archivado_vendedor = self.datos_vendedor_archivados.get(&caller).cloned();
+
+        let (nuevo_rol, nuevo_es_comprador, restaurado) = match &usuario.rol {
+            Rol::Comprador(compras) => (Rol::Ambos(compras.clone(), archivado_vendedor.clone().unwrap_or_default()), false, archivado_vendedor.is_some()),
+            Rol::Vendedor(data_vendedor) => (Rol::Ambos(archivado_comprador.clone().unwrap_or_default(), data_vendedor.clone()), true, archivado_comprador.is_some()),
             _ => return Err(ErrorAscenderRolUsuario::MaximoRolAsignado),
         };
 
         let mut usuario = usuario.clone();
         usuario.rol = nuevo_rol;
+
+        if !restaurado {
+            // si la cuenta había sido eliminada, el rol nuevo recupera sus calificaciones anteriores
+            self.restaurar_calificaciones(&mut usuario, nuevo_es_comprador, !nuevo_es_comprador);
+        } else if nuevo_es_comprador {
+            // las favoritas restauradas vuelven al índice de interesados
+            for id_publicacion in usuario.obtener_publicaciones_favoritas().unwrap_or_default() {
+                self.sumar_interesado_publicacion(id_publicacion);
+            }
+            self.datos_comprador_archivados.remove(&caller);
+        } else {
+            self.datos_vendedor_archivados.remove(&caller);
+        }
+
+        self.usuarios.insert(caller, usuario);
+
+        Ok(())
+    }
+
+    /// Si el usuario es Ambos, deja el rol indicado y conserva el otro.
+    /// Los datos del rol que deja (compras o ventas, publicaciones, stock, listas y calificaciones) se archivan
+    /// y se restauran si vuelve a ascender. Al dejar de comprar se quita de los interesados de sus favoritas;
+    /// al dejar de vender se desactivan las reposiciones automáticas de sus publicaciones.
+    /// 
+    /// Devuelve error si el usuario no existe, no es Ambos o se indicó Ambos, o si en el rol que deja tiene
+    /// pedidos pendientes o despachados, fondos en custodia, disputas en curso, publicaciones con unidades
+    /// ofertadas o transferencias de stock ofrecidas. Una tienda compartida no puede dejar de vender
+    /// hasta que sus socios la disuelvan.
+    pub fn _descender_rol_usuario(&mut self, caller: AccountId, rol_a_quitar: RolDeSeleccion) -> Result<(), ErrorDescenderRolUsuario> {
+        let Some(mut usuario) = self.usuarios.get(&caller).cloned()
+        else { return Err(ErrorDescenderRolUsuario::UsuarioInexistente); };
+
+        let Rol::Ambos(data_comprador, data_vendedor) = usuario.rol.clone()
+        else { return Err(ErrorDescenderRolUsuario::RolInvalido); };
+
+        let quita_comprador = match rol_a_quitar {
+            RolDeSeleccion::Comprador => true,
+            RolDeSeleccion::Vendedor => false,
+            RolDeSeleccion::Ambos => return Err(ErrorDescenderRolUsuario::RolInvalido),
+        };
+
+        // validar pedidos del rol que deja
+        let ids_pedidos = if quita_comprador { &data_comprador.compras } else { &data_vendedor.ventas };
+        for pedido in ids_pedidos.iter().filter_map(|id_pedido| self.pedidos.get(id_pedido)) {
+            if matches!(pedido.estado, EstadoPedido::Pendiente(_) | EstadoPedido::Despachado(_)) {
+                return Err(ErrorDescenderRolUsuario::PedidosPendientes);
+            }
+
+            if !pedido.fondos_fueron_transferidos && pedido.fondos_en_custodia() > 0 {
+                return Err(ErrorDescenderRolUsuario::FondosEnCustodia);
+            }
+        }
+
+        let disputas = if quita_comprador { &data_comprador.disputas_en_curso } else { &data_vendedor.disputas_en_curso };
+        if !disputas.is_empty() {
+            return Err(ErrorDescenderRolUsuario::DisputasEnCurso);
+        }
+
+        if quita_comprador {
+            // quitar del índice de interesados
+            for id_publicacion in data_comprador.publicaciones_favoritas.iter() {
+                self.restar_interesado_publicacion(*id_publicacion);
+            }
+
+            usuario.rol = Rol::Vendedor(data_vendedor);
+            self.datos_comprador_archivados.insert(caller, data_comprador);
+        } else {
+            if self.tiendas_compartidas.contains_key(&caller) {
+                return Err(ErrorDescenderRolUsuario::TiendaCompartida);
+            }
+
+            // validar publicaciones y transferencias
+            let publicaciones_activas = data_vendedor.publicaciones.iter()
+                .filter_map(|id| self.publicaciones.get(id))
+                .any(|publicacion| publicacion.cantidad_ofertada > 0);
+            if publicaciones_activas {
+                return Err(ErrorDescenderRolUsuario::PublicacionesActivas);
+            }
+
+            let transferencias_pendientes = self.transferencias_stock.values()
+                .any(|t| (t.emisor == caller || t.receptor == caller) && matches!(t.estado, EstadoTransferenciaStock::Ofrecida(_)));
+            if transferencias_pendientes {
+                return Err(ErrorDescenderRolUsuario::TransferenciasPendientes);
+            }
+
+            for id_publicacion in data_vendedor.publicaciones.iter() {
+                self.reposiciones_automaticas.remove(id_publicacion);
+            }
+
+            usuario.rol = Rol::Comprador(data_comprador);
+            self.datos_vendedor_archivados.insert(caller, data_vendedor);
+        }
+
+        self.usuarios.insert(caller, usuario);
+
+        Ok(())
+    }
+
+    /// Guarda una dirección de entrega cifrada en el perfil del comprador.
+    /// El cifrado se realiza fuera de la cadena: el contrato sólo almacena el contenido cifrado.
+    /// 
+    /// Devuelve el índice de la dirección guardada.
+    /// Devolverá error si el usuario no está registrado, no es comprador,
+    /// la dirección está vacía o excede el tamaño máximo o ya alcanzó el máximo de direcciones.
+    pub fn _agregar_direccion(&mut self, caller: AccountId, direccion_cifrada: Vec<u8>) -> Result<u32, ErrorDireccionComprador> {
+        let Some(mut usuario) = self.usuarios.get(&caller).cloned()
+        else { return Err(ErrorDireccionComprador::UsuarioNoRegistrado); };
+
+        let Some(mut data_comprador) = usuario.obtener_data_comprador()
+        else { return Err(ErrorDireccionComprador::NoEsComprador); };
+
+        if direccion_cifrada.is_empty() || direccion_cifrada.len() > MAX_LARGO_DIRECCION_CIFRADA {
+            return Err(ErrorDireccionComprador::DireccionInvalida);
+        }
+
+        if data_comprador.direcciones.len() >= MAX_DIRECCIONES_COMPRADOR {
+            return Err(ErrorDireccionComprador::MaximoDireccionesAlcanzado);
+        }
+
+        data_comprador.direcciones.push(direccion_cifrada);
+        let indice = data_comprador.direcciones.len().saturating_sub(1) as u32; // safe cast: len <= MAX_DIRECCIONES_COMPRADOR
+
+        usuario.establecer_data_comprador(data_comprador);
+        self.usuarios.insert(caller, usuario);
+
+        Ok(indice)
+    }
+
+    /// Elimina una dirección cifrada del perfil del comprador según su índice.
+    /// 
+    /// Devolverá error si el usuario no está registrado, no es comprador o la dirección no existe.
+    pub fn _eliminar_direccion(&mut self, caller: AccountId, indice: u32) -> Result<(), ErrorDireccionComprador> {
+        let Some(mut usuario) = self.usuarios.get(&caller).cloned()
+        else { return Err(ErrorDireccionComprador::UsuarioNoRegistrado); };
+
+        let Some(mut data_comprador) = usuario.obtener_data_comprador()
+        else { return Err(ErrorDireccionComprador::NoEsComprador); };
+
+        let indice = indice as usize;
+        if indice >= data_comprador.direcciones.len() {
+            return Err(ErrorDireccionComprador::DireccionInexistente);
+        }
+
+        data_comprador.direcciones.remove(indice);
+
+        usuario.establecer_data_comprador(data_comprador);
+        self.usuarios.insert(caller, usuario);
+
+        Ok(())
+    }
+
+    /// Devuelve las direcciones cifradas guardadas en el perfil del comprador.
+    /// 
+    /// Devolverá error si el usuario no está registrado o no es comprador.
+    pub fn _ver_direcciones(&self, caller: AccountId) -> Result<Vec<Vec<u8>>, ErrorDireccionComprador> {
+        let Some(usuario) = self.usuarios.get(&caller)
+        else { return Err(ErrorDireccionComprador::UsuarioNoRegistrado); };
+
+        let Some(direcciones) = usuario.obtener_direcciones()
+        else { return Err(ErrorDireccionComprador::NoEsComprador); };
+
+        Ok(direcciones)
+    }
+
+    /// Agrega una publicación a la lista de favoritas del comprador.
+    /// Mientras esté en la lista, el comprador será notificado si su precio baja o vuelve a tener stock.
+    /// 
+    /// Devolverá error si el usuario no está registrado, no es comprador, la publicación no existe,
+    /// ya está en la lista o la lista alcanzó su máximo.
+    pub fn _agregar_publicacion_favorita(&mut self, caller: AccountId, id_publicacion: u128) -> Result<(), ErrorListasComprador> {
+        let Some(mut usuario) = self.usuarios.get(&caller).cloned()
+        else { return Err(ErrorListasComprador::UsuarioNoRegistrado); };
+
+        let Some(mut data_comprador) = usuario.obtener_data_comprador()
+        else { return Err(ErrorListasComprador::NoEsComprador); };
+
+        if !self.publicaciones.contains_key(&id_publicacion) {
+            return Err(ErrorListasComprador::PublicacionInexistente);
+        }
+
+        if data_comprador.publicaciones_favoritas.contains(&id_publicacion) {
+            return Err(ErrorListasComprador::YaIncluido);
+        }
+
+        if data_comprador.publicaciones_favoritas.len() >= MAX_PUBLICACIONES_FAVORITAS {
+            return Err(ErrorListasComprador::MaximoAlcanzado);
+        }
+
+        data_comprador.publicaciones_favoritas.push(id_publicacion);
+        usuario.establecer_data_comprador(data_comprador);
+        self.usuarios.insert(caller, usuario);
+
+        // índice inverso para notificar a los interesados
+        self.sumar_interesado_publicacion(id_publicacion);
+
+        Ok(())
+    }
+
+    /// Quita una publicación de la lista de favoritas del comprador.
+    /// 
+    /// Devolverá error si el usuario no está registrado, no es comprador o la publicación no está en la lista.
+    pub fn _eliminar_publicacion_favorita(&mut self, caller: AccountId, id_publicacion: u128) -> Result<(), ErrorListasComprador> {
+        let Some(mut usuario) = self.usuarios.get(&caller).cloned()
+        else { return Err(ErrorListasComprador::UsuarioNoRegistrado); };
+
+        let Some(mut data_comprador) = usuario.obtener_data_comprador()
+        else { return Err(ErrorListasComprador::NoEsComprador); };
+
+        let Some(indice) = data_comprador.publicaciones_favoritas.iter().position(|&id| id == id_publicacion)
+        else { return Err(ErrorListasComprador::NoIncluido); };
+
+        data_comprador.publicaciones_favoritas.remove(indice);
+        usuario.establecer_data_comprador(data_comprador);
+        self.usuarios.insert(caller, usuario);
+
+        // índice inverso
+        self.restar_interesado_publicacion(id_publicacion);
+
+        Ok(())
+    }
+
+    /// Suma un comprador interesado en la publicación.
+    fn sumar_interesado_publicacion(&mut self, id_publicacion: u128) {
+        let interesados = self.interesados_publicaciones.get(&id_publicacion).copied().unwrap_or(0);
+        self.interesados_publicaciones.insert(id_publicacion, interesados.saturating_add(1));
+    }
+
+    /// Resta un comprador interesado en la publicación y la quita del índice si no queda ninguno.
+    pub(crate) fn restar_interesado_publicacion(&mut self, id_publicacion: u128) {
+        let Some(interesados) = self.interesados_publicaciones.get(&id_publicacion).copied()
+        else { return; };
+
+        if interesados <= 1 {
+            self.interesados_publicaciones.remove(&id_publicacion);
+        } else {
+            self.interesados_publicaciones.insert(id_publicacion, interesados - 1);
+        }
+    }
+
+    /// Devuelve las publicaciones favoritas del comprador.
+    /// 
+    /// Devolverá error si el usuario no está registrado o no es comprador.
+    pub fn _ver_publicaciones_favoritas(&self, caller: AccountId) -> Result<Vec<u128>, ErrorListasComprador> {
+        let Some(usuario) = self.usuarios.get(&caller)
+        else { return Err(ErrorListasComprador::UsuarioNoRegistrado); };
+
+        let Some(favoritas) = usuario.obtener_publicaciones_favoritas()
+        else { return Err(ErrorListasComprador::NoEsComprador); };
+
+        Ok(favoritas)
+    }
+
+    /// Agrega un vendedor a la lista de vendedores seguidos por el comprador.
+    /// 
+    /// Devolverá error si el usuario no está registrado, no es comprador, el vendedor no existe
+    /// o no es vendedor (incluido uno mismo), ya lo sigue o la lista alcanzó su máximo.
+    pub fn _seguir_vendedor(&mut self, caller: AccountId, id_vendedor: AccountId) -> Result<(), ErrorListasComprador> {
+        let Some(mut usuario) = self.usuarios.get(&caller).cloned()
+        else { return Err(ErrorListasComprador::UsuarioNoRegistrado); };
+
+        let Some(mut data_comprador) = usuario.obtener_data_comprador()
+        else { return Err(ErrorListasComprador::NoEsComprador); };
+
+        if caller == id_vendedor || !self.usuarios.get(&id_vendedor).is_some_and(|vendedor| vendedor.es_vendedor()) {
+            return Err(ErrorListasComprador::VendedorInexistente);
+        }
+
+        if data_comprador.vendedores_seguidos.contains(&id_vendedor) {
+            return Err(ErrorListasComprador::YaIncluido);
+        }
+
+        if data_comprador.vendedores_seguidos.len() >= MAX_VENDEDORES_SEGUIDOS {
+            return Err(ErrorListasComprador::MaximoAlcanzado);
+        }
+
+        data_comprador.vendedores_seguidos.push(id_vendedor);
+        usuario.establecer_data_comprador(data_comprador);
         self.usuarios.insert(caller, usuario);
 
         Ok(())
     }
 
+    /// Quita un vendedor de la lista de vendedores seguidos por el comprador.
+    /// 
+    /// Devolverá error si el usuario no está registrado, no es comprador o no sigue al vendedor.
+    pub fn _dejar_de_seguir_vendedor(&mut self, caller: AccountId, id_vendedor: AccountId) -> Result<(), ErrorListasComprador> {
+        let Some(mut usuario) = self.usuarios.get(&caller).cloned()
+        else { return Err(ErrorListasComprador::UsuarioNoRegistrado); };
+
+        let Some(mut data_comprador) = usuario.obtener_data_comprador()
+        else { return Err(ErrorListasComprador::NoEsComprador); };
+
+        let Some(indice) = data_comprador.vendedores_seguidos.iter().position(|&id| id == id_vendedor)
+        else { return Err(ErrorListasComprador::NoIncluido); };
+
+        data_comprador.vendedores_seguidos.remove(indice);
+        usuario.establecer_data_comprador(data_comprador);
+        self.usuarios.insert(caller, usuario);
+
+        Ok(())
+    }
+
+    /// Devuelve los vendedores seguidos por el comprador.
+    /// 
+    /// Devolverá error si el usuario no está registrado o no es comprador.
+    pub fn _ver_vendedores_seguidos(&self, caller: AccountId) -> Result<Vec<AccountId>, ErrorListasComprador> {
+        let Some(usuario) = self.usuarios.get(&caller)
+        else { return Err(ErrorListasComprador::UsuarioNoRegistrado); };
+
+        let Some(seguidos) = usuario.obtener_vendedores_seguidos()
+        else { return Err(ErrorListasComprador::NoEsComprador); };
+
+        Ok(seguidos)
+    }
+
     // Tendría más sentido una función que devuelva información de todas las órdenes de un usuario
     // si tan solo se pudiese iterar sobre un Mapping.
     /// ReportesView: Cantidad de ordenes
@@ -600,6 +1030,7 @@ impl RustaceoLibre {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::structs::producto::DatosProducto;
     #[ink::test]
     fn registrar_usuario_funciona_correctamente() {
         let mut contrato = RustaceoLibre::default();
@@ -1554,4 +1985,141 @@ mod tests {
         assert_eq!(usuario.obtener_compras().unwrap().len(), 1);
         assert_eq!(usuario.obtener_compras().unwrap()[0], 999);
     }
+
+    #[ink::test]
+    fn agregar_direccion_funciona_correctamente() {
+        let mut contrato = RustaceoLibre::default();
+        let cuenta = AccountId::from([0xE1; 32]);
+
+        // sin registrar
+        assert_eq!(contrato._agregar_direccion(cuenta, vec![1, 2, 3]), Err(ErrorDireccionComprador::UsuarioNoRegistrado));
+
+        contrato._registrar_usuario(cuenta, RolDeSeleccion::Comprador).unwrap();
+
+        assert_eq!(contrato._agregar_direccion(cuenta, vec![1, 2, 3]), Ok(0));
+        assert_eq!(contrato._agregar_direccion(cuenta, vec![4, 5, 6]), Ok(1));
+        assert_eq!(contrato._ver_direcciones(cuenta), Ok(vec![vec![1, 2, 3], vec![4, 5, 6]]));
+    }
+
+    #[ink::test]
+    fn agregar_direccion_falla_validaciones() {
+        let mut contrato = RustaceoLibre::default();
+        let vendedor = AccountId::from([0xE2; 32]);
+        let comprador = AccountId::from([0xE3; 32]);
+
+        contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+        contrato._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
+
+        assert_eq!(contrato._agregar_direccion(vendedor, vec![1]), Err(ErrorDireccionComprador::NoEsComprador));
+        assert_eq!(contrato._agregar_direccion(comprador, Vec::new()), Err(ErrorDireccionComprador::DireccionInvalida));
+        assert_eq!(contrato._agregar_direccion(comprador, vec![0; MAX_LARGO_DIRECCION_CIFRADA + 1]), Err(ErrorDireccionComprador::DireccionInvalida));
+
+        for _ in 0..MAX_DIRECCIONES_COMPRADOR {
+            assert!(contrato._agregar_direccion(comprador, vec![1]).is_ok());
+        }
+        assert_eq!(contrato._agregar_direccion(comprador, vec![1]), Err(ErrorDireccionComprador::MaximoDireccionesAlcanzado));
+    }
+
+    #[ink::test]
+    fn eliminar_direccion_funciona_correctamente() {
+        let mut contrato = RustaceoLibre::default();
+        let cuenta = AccountId::from([0xE4; 32]);
+        contrato._registrar_usuario(cuenta, RolDeSeleccion::Ambos).unwrap();
+
+        contrato._agregar_direccion(cuenta, vec![1]).unwrap();
+        contrato._agregar_direccion(cuenta, vec![2]).unwrap();
+
+        assert_eq!(contrato._eliminar_direccion(cuenta, 5), Err(ErrorDireccionComprador::DireccionInexistente));
+        assert_eq!(contrato._eliminar_direccion(cuenta, 0), Ok(()));
+        assert_eq!(contrato._ver_direcciones(cuenta), Ok(vec![vec![2]]));
+
+        // el lado vendedor no se ve afectado
+        assert!(contrato.usuarios.get(&cuenta).unwrap().es_vendedor());
+    }
+
+
+    #[ink::test]
+    fn publicaciones_favoritas_funciona_correctamente() {
+        let mut contrato = RustaceoLibre::default();
+        let vendedor = AccountId::from([0xF1; 32]);
+        let comprador = AccountId::from([0xF2; 32]);
+
+        contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+        contrato._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
+        let id_producto = contrato._registrar_producto(0, vendedor, DatosProducto::new("Mate".into(), "Calabaza".into(), 1), 10).unwrap();
+        contrato._staff_aprobar_producto(contrato.owner, id_producto).unwrap();
+        let id_publicacion = contrato._realizar_publicacion(0, vendedor, id_producto, 5, 100).unwrap();
+
+        assert_eq!(contrato._agregar_publicacion_favorita(vendedor, id_publicacion), Err(ErrorListasComprador::NoEsComprador));
+        assert_eq!(contrato._agregar_publicacion_favorita(comprador, 99), Err(ErrorListasComprador::PublicacionInexistente));
+        assert_eq!(contrato._agregar_publicacion_favorita(comprador, id_publicacion), Ok(()));
+        assert_eq!(contrato._agregar_publicacion_favorita(comprador, id_publicacion), Err(ErrorListasComprador::YaIncluido));
+
+        assert_eq!(contrato._ver_publicaciones_favoritas(comprador), Ok(vec![id_publicacion]));
+        assert_eq!(contrato.interesados_publicaciones.get(&id_publicacion), Some(&1));
+
+        assert_eq!(contrato._eliminar_publicacion_favorita(comprador, id_publicacion), Ok(()));
+        assert_eq!(contrato._eliminar_publicacion_favorita(comprador, id_publicacion), Err(ErrorListasComprador::NoIncluido));
+        assert_eq!(contrato._ver_publicaciones_favoritas(comprador), Ok(Vec::new()));
+        assert!(!contrato.interesados_publicaciones.contains_key(&id_publicacion));
+    }
+
+    #[ink::test]
+    fn seguir_vendedor_funciona_correctamente() {
+        let mut contrato = RustaceoLibre::default();
+        let vendedor = AccountId::from([0xF3; 32]);
+        let comprador = AccountId::from([0xF4; 32]);
+        let ambos = AccountId::from([0xF5; 32]);
+
+        contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+        contrato._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
+        contrato._registrar_usuario(ambos, RolDeSeleccion::Ambos).unwrap();
+
+        assert_eq!(contrato._seguir_vendedor(comprador, comprador), Err(ErrorListasComprador::VendedorInexistente));
+        assert_eq!(contrato._seguir_vendedor(ambos, ambos), Err(ErrorListasComprador::VendedorInexistente));
+        assert_eq!(contrato._seguir_vendedor(comprador, vendedor), Ok(()));
+        assert_eq!(contrato._seguir_vendedor(comprador, ambos), Ok(()));
+        assert_eq!(contrato._seguir_vendedor(comprador, vendedor), Err(ErrorListasComprador::YaIncluido));
+        assert_eq!(contrato._ver_vendedores_seguidos(comprador), Ok(vec![vendedor, ambos]));
+
+        assert_eq!(contrato._dejar_de_seguir_vendedor(comprador, vendedor), Ok(()));
+        assert_eq!(contrato._ver_vendedores_seguidos(comprador), Ok(vec![ambos]));
+    }
+    #[ink::test]
+    fn descender_rol_usuario_archiva_y_restaura_datos() {
+        let mut contrato = RustaceoLibre::default();
+        let ambos = AccountId::from([0xF6; 32]);
+        let comprador = AccountId::from([0xF7; 32]);
+
+        contrato._registrar_usuario(ambos, RolDeSeleccion::Ambos).unwrap();
+        contrato._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
+
+        assert_eq!(contrato._descender_rol_usuario(comprador, RolDeSeleccion::Comprador), Err(ErrorDescenderRolUsuario::RolInvalido));
+        assert_eq!(contrato._descender_rol_usuario(ambos, RolDeSeleccion::Ambos), Err(ErrorDescenderRolUsuario::RolInvalido));
+
+        let id_producto = contrato._registrar_producto(0, ambos, DatosProducto::new("Mate".into(), "Calabaza".into(), 1), 10).unwrap();
+        contrato._staff_aprobar_producto(contrato.owner, id_producto).unwrap();
+        let id_publicacion = contrato._realizar_publicacion(0, ambos, id_producto, 5, 100).unwrap();
+        contrato._agregar_publicacion_favorita(ambos, id_publicacion).unwrap();
+
+        // no puede dejar de vender con unidades publicadas
+        assert_eq!(contrato._descender_rol_usuario(ambos, RolDeSeleccion::Vendedor), Err(ErrorDescenderRolUsuario::PublicacionesActivas));
+
+        // dejar de comprar: las favoritas se archivan y salen del índice de interesados
+        assert_eq!(contrato._descender_rol_usuario(ambos, RolDeSeleccion::Comprador), Ok(()));
+        assert!(!contrato.usuarios.get(&ambos).unwrap().es_comprador());
+        assert!(!contrato.interesados_publicaciones.contains_key(&id_publicacion));
+
+        assert_eq!(contrato._ascender_rol_usuario(ambos), Ok(()));
+        assert_eq!(contrato._ver_publicaciones_favoritas(ambos), Ok(vec![id_publicacion]));
+        assert_eq!(contrato.interesados_publicaciones.get(&id_publicacion), Some(&1));
+        assert!(!contrato.datos_comprador_archivados.contains_key(&ambos));
+
+        // dejar de vender conserva el stock para cuando vuelva a ascender
+        contrato._modificar_cantidad_ofertada(0, ambos, id_publicacion, 0).unwrap();
+        assert_eq!(contrato._descender_rol_usuario(ambos, RolDeSeleccion::Vendedor), Ok(()));
+        assert!(!contrato.usuarios.get(&ambos).unwrap().es_vendedor());
+        assert_eq!(contrato._ascender_rol_usuario(ambos), Ok(()));
+        assert_eq!(contrato.usuarios.get(&ambos).unwrap().obtener_stock_producto(&id_producto), Some(10));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,393 @@
+use ink::{prelude::{string::String, vec::Vec}, primitives::AccountId};
+
+use crate::rustaceo_libre::RustaceoLibre;
+
+/// Longitud máxima (en bytes) del motivo de rechazo o revocación de una verificación
+pub const MAX_LARGO_MOTIVO_VERIFICACION: usize = 256;
+
+/// Nivel de verificación de un vendedor. Cada nivel incluye a los anteriores.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum NivelVerificacion {
+    Identidad, // identidad de la persona
+    Empresa,   // existencia y titularidad de la empresa
+}
+
+/// Insignia de un vendedor verificado por el Staff
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub struct Verificacion {
+    pub nivel: NivelVerificacion,
+    pub timestamp: u64,
+    pub interventor: AccountId,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum EstadoSolicitudVerificacion {
+    Pendiente,
+    Aprobada(u64),
+    Rechazada(u64),
+    Revocada(u64),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub struct SolicitudVerificacion {
+    pub nivel: NivelVerificacion,
+    pub hash_documentos: [u8; 32], // hash de la documentación presentada. el contrato nunca conoce su contenido
+    pub timestamp: u64,
+    pub estado: EstadoSolicitudVerificacion,
+    pub motivo: Option<String>,    // del rechazo o la revocación
+    pub interventor: Option<AccountId>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(
+    feature = "std",
+    derive(ink::storage::traits::StorageLayout)
+)]
+pub enum ErrorVerificacion {
+    UsuarioNoRegistrado,
+    NoEsVendedor,
+    UsuarioNoStaff,
+    SolicitudPendiente,
+    YaVerificado,            // ya tiene ese nivel o uno superior
+    SolicitudInexistente,    // no existe o no está pendiente
+    NoVerificado,
+    MotivoInvalido,
+    CategoriaInexistente,
+}
+
+impl RustaceoLibre {
+
+    /// El vendedor solicita una verificación del nivel indicado, presentando el hash de su documentación.
+    /// Reemplaza cualquier solicitud anterior ya resuelta.
+    ///
+    /// Devolverá error si el usuario no está registrado, no es vendedor, ya tiene una solicitud pendiente
+    /// o ya está verificado con ese nivel o uno superior.
+    pub fn _solicitar_verificacion(&mut self, timestamp: u64, caller: AccountId, nivel: NivelVerificacion, hash_documentos: [u8; 32]) -> Result<(), ErrorVerificacion> {
+        // validar usuario
+        let Some(usuario) = self.usuarios.get(&caller)
+        else { return Err(ErrorVerificacion::UsuarioNoRegistrado); };
+
+        if !usuario.es_vendedor() {
+            return Err(ErrorVerificacion::NoEsVendedor);
+        }
+
+        // validar solicitud
+        if self.solicitudes_verificacion.get(&caller).is_some_and(|solicitud| solicitud.estado == EstadoSolicitudVerificacion::Pendiente) {
+            return Err(ErrorVerificacion::SolicitudPendiente);
+        }
+
+        if self.verificaciones.get(&caller).is_some_and(|verificacion| verificacion.nivel >= nivel) {
+            return Err(ErrorVerificacion::YaVerificado);
+        }
+
+        let solicitud = SolicitudVerificacion {
+            nivel,
+            hash_documentos,
+            timestamp,
+            estado: EstadoSolicitudVerificacion::Pendiente,
+            motivo: None,
+            interventor: None,
+        };
+        self.solicitudes_verificacion.insert(caller, solicitud);
+
+        Ok(())
+    }
+
+    //
+
+    /// Devuelve la última solicitud de verificación del vendedor.
+    ///
+    /// Devolverá None si no existe o el caller no es el vendedor ni parte del Staff.
+    pub fn _ver_solicitud_verificacion(&self, caller: AccountId, vendedor: AccountId) -> Option<SolicitudVerificacion> {
+        if caller != vendedor && !self.staff.contains(&caller) && caller != self.owner {
+            return None;
+        }
+
+        self.solicitudes_verificacion.get(&vendedor).cloned()
+    }
+
+    /// Devuelve la insignia de verificación del vendedor, si tiene una.
+    pub fn _ver_verificacion(&self, vendedor: AccountId) -> Option<Verificacion> {
+        self.verificaciones.get(&vendedor).cloned()
+    }
+
+    //
+
+    /// Devolverá la lista de vendedores con solicitudes de verificación pendientes.
+    ///
+    /// Devolverá None si el usuario no es parte del Staff.
+    pub fn _staff_ver_solicitudes_verificacion(&self, caller: AccountId) -> Option<Vec<AccountId>> {
+        if !self.staff.contains(&caller) && caller != self.owner {
+            return None;
+        }
+
+        Some(self.solicitudes_verificacion.iter()
+            .filter(|(_, solicitud)| solicitud.estado == EstadoSolicitudVerificacion::Pendiente)
+            .map(|(id, _)| *id)
+            .collect())
+    }
+
+    //
+
+    /// Aprueba la solicitud de verificación pendiente de un vendedor, otorgándole la insignia del nivel solicitado.
+    ///
+    /// Devolverá error si el usuario no es parte del Staff o el vendedor no tiene una solicitud pendiente.
+    pub fn _staff_aprobar_verificacion(&mut self, timestamp: u64, caller: AccountId, vendedor: AccountId) -> Result<(), ErrorVerificacion> {
+        let mut solicitud = self.solicitud_verificacion_pendiente(caller, vendedor)?;
+
+        solicitud.estado = EstadoSolicitudVerificacion::Aprobada(timestamp);
+        solicitud.interventor = Some(caller);
+        let verificacion = Verificacion { nivel: solicitud.nivel, timestamp, interventor: caller };
+
+        self.solicitudes_verificacion.insert(vendedor, solicitud);
+        self.verificaciones.insert(vendedor, verificacion);
+
+        Ok(())
+    }
+
+    /// Rechaza la solicitud de verificación pendiente de un vendedor indicando el motivo.
+    /// La insignia que ya tuviera el vendedor se conserva.
+    ///
+    /// Devolverá error si el usuario no es parte del Staff, el vendedor no tiene una solicitud pendiente
+    /// o el motivo está vacío o excede su longitud máxima.
+    pub fn _staff_rechazar_verificacion(&mut self, timestamp: u64, caller: AccountId, vendedor: AccountId, motivo: String) -> Result<(), ErrorVerificacion> {
+        let mut solicitud = self.solicitud_verificacion_pendiente(caller, vendedor)?;
+
+        if motivo.is_empty() || motivo.len() > MAX_LARGO_MOTIVO_VERIFICACION {
+            return Err(ErrorVerificacion::MotivoInvalido);
+        }
+
+        solicitud.estado = EstadoSolicitudVerificacion::Rechazada(timestamp);
+        solicitud.motivo = Some(motivo);
+        solicitud.interventor = Some(caller);
+        self.solicitudes_verificacion.insert(vendedor, solicitud);
+
+        Ok(())
+    }
+
+    /// Revoca la insignia de verificación de un vendedor indicando el motivo, que queda registrado en su última solicitud.
+    /// Sus publicaciones existentes se conservan, pero no podrá publicar en categorías que exijan verificación.
+    ///
+    /// Devolverá error si el usuario no es parte del Staff, el vendedor no está verificado
+    /// o el motivo está vacío o excede su longitud máxima.
+    pub fn _staff_revocar_verificacion(&mut self, timestamp: u64, caller: AccountId, vendedor: AccountId, motivo: String) -> Result<(), ErrorVerificacion> {
+        // validar staff
+        if !self.staff.contains(&caller) && caller != self.owner {
+            return Err(ErrorVerificacion::UsuarioNoStaff);
+        }
+
+        if !self.verificaciones.contains_key(&vendedor) {
+            return Err(ErrorVerificacion::NoVerificado);
+        }
+
+        if motivo.is_empty() || motivo.len() > MAX_LARGO_MOTIVO_VERIFICACION {
+            return Err(ErrorVerificacion::MotivoInvalido);
+        }
+
+        self.verificaciones.remove(&vendedor);
+
+        // una solicitud pendiente de un nivel superior se conserva
+        if let Some(mut solicitud) = self.solicitudes_verificacion.get(&vendedor).cloned() {
+            if solicitud.estado != EstadoSolicitudVerificacion::Pendiente {
+                solicitud.estado = EstadoSolicitudVerificacion::Revocada(timestamp);
+                solicitud.motivo = Some(motivo);
+                solicitud.interventor = Some(caller);
+                self.solicitudes_verificacion.insert(vendedor, solicitud);
+            }
+        }
+
+        Ok(())
+    }
+
+    //
+
+    /// Establece el nivel de verificación que debe tener un vendedor para publicar productos de una categoría
+    /// o de sus subcategorías. Con None se quita la exigencia. Las publicaciones existentes no se modifican.
+    ///
+    /// Devolverá error si el usuario no es parte del Staff o la categoría no existe.
+    pub fn _staff_exigir_verificacion_categoria(&mut self, caller: AccountId, categoria: u128, nivel: Option<NivelVerificacion>) -> Result<(), ErrorVerificacion> {
+        // validar staff
+        if !self.staff.contains(&caller) && caller != self.owner {
+            return Err(ErrorVerificacion::UsuarioNoStaff);
+        }
+
+        // validar categoría
+        if !self.categorias.contains_key(&categoria) {
+            return Err(ErrorVerificacion::CategoriaInexistente);
+        }
+
+        match nivel {
+            Some(nivel) => self.verificacion_exigida_categorias.insert(categoria, nivel),
+            None => self.verificacion_exigida_categorias.remove(&categoria),
+        };
+
+        Ok(())
+    }
+
+    /// Devuelve el nivel de verificación exigido para publicar productos de la categoría:
+    /// el mayor entre los exigidos para ella y para sus categorías ancestro.
+    pub fn nivel_verificacion_exigido(&self, categoria: &u128) -> Option<NivelVerificacion> {
+        self.verificacion_exigida_categorias.iter()
+            .filter(|(ancestro, _)| self.pertenece_a_categoria(categoria, ancestro))
+            .map(|(_, nivel)| *nivel)
+            .max()
+    }
+
+    /// Devuelve true si el vendedor tiene al menos el nivel de verificación exigido, o si no se exige ninguno
+    pub(crate) fn cumple_verificacion(&self, vendedor: AccountId, nivel_exigido: Option<NivelVerificacion>) -> bool {
+        nivel_exigido.map_or(true, |nivel| self.verificaciones.get(&vendedor).is_some_and(|verificacion| verificacion.nivel >= nivel))
+    }
+
+    //
+
+    /// Devuelve la solicitud pendiente del vendedor, validando que el caller sea parte del Staff
+    fn solicitud_verificacion_pendiente(&self, caller: AccountId, vendedor: AccountId) -> Result<SolicitudVerificacion, ErrorVerificacion> {
+        // validar staff
+        if !self.staff.contains(&caller) && caller != self.owner {
+            return Err(ErrorVerificacion::UsuarioNoStaff);
+        }
+
+        let Some(solicitud) = self.solicitudes_verificacion.get(&vendedor)
+        else { return Err(ErrorVerificacion::SolicitudInexistente); };
+
+        if solicitud.estado != EstadoSolicitudVerificacion::Pendiente {
+            return Err(ErrorVerificacion::SolicitudInexistente);
+        }
+
+        Ok(solicitud.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::{categoria::ErrorCategoria, producto::{DatosProducto, ErrorModerarProducto}, publicacion::{ErrorModificarCantidadOfertada, ErrorRealizarPublicacion, ReposicionAutomatica}, usuario::RolDeSeleccion};
+
+    #[ink::test]
+    fn solicitar_aprobar_y_revocar_verificacion() {
+        let mut contrato = RustaceoLibre::new(0);
+        let owner = contrato.owner;
+        let vendedor = AccountId::from([0x3; 32]); // [0x1; 32] es el owner
+        let comprador = AccountId::from([0x2; 32]);
+        contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+        contrato._registrar_usuario(comprador, RolDeSeleccion::Comprador).unwrap();
+
+        assert_eq!(contrato._solicitar_verificacion(0, comprador, NivelVerificacion::Identidad, [1; 32]), Err(ErrorVerificacion::NoEsVendedor));
+        assert_eq!(contrato._solicitar_verificacion(0, vendedor, NivelVerificacion::Identidad, [1; 32]), Ok(()));
+        assert_eq!(contrato._solicitar_verificacion(0, vendedor, NivelVerificacion::Empresa, [1; 32]), Err(ErrorVerificacion::SolicitudPendiente));
+        assert_eq!(contrato._staff_ver_solicitudes_verificacion(owner), Some(Vec::from([vendedor])));
+        assert_eq!(contrato._staff_ver_solicitudes_verificacion(vendedor), None);
+
+        // rechazo
+        assert_eq!(contrato._staff_rechazar_verificacion(1, vendedor, vendedor, "X".into()), Err(ErrorVerificacion::UsuarioNoStaff));
+        assert_eq!(contrato._staff_rechazar_verificacion(1, owner, vendedor, "".into()), Err(ErrorVerificacion::MotivoInvalido));
+        assert_eq!(contrato._staff_rechazar_verificacion(1, owner, vendedor, "Documento ilegible".into()), Ok(()));
+        assert_eq!(contrato._ver_solicitud_verificacion(vendedor, vendedor).unwrap().estado, EstadoSolicitudVerificacion::Rechazada(1));
+        assert_eq!(contrato._ver_solicitud_verificacion(comprador, vendedor), None);
+
+        // aprobación
+        contrato._solicitar_verificacion(2, vendedor, NivelVerificacion::Empresa, [2; 32]).unwrap();
+        assert_eq!(contrato._staff_aprobar_verificacion(3, owner, vendedor), Ok(()));
+        assert_eq!(contrato._staff_aprobar_verificacion(3, owner, vendedor), Err(ErrorVerificacion::SolicitudInexistente));
+        assert_eq!(contrato._ver_verificacion(vendedor), Some(Verificacion { nivel: NivelVerificacion::Empresa, timestamp: 3, interventor: owner }));
+        assert_eq!(contrato._ver_perfil(vendedor).unwrap().verificacion, Some(NivelVerificacion::Empresa));
+        assert_eq!(contrato._solicitar_verificacion(4, vendedor, NivelVerificacion::Identidad, [3; 32]), Err(ErrorVerificacion::YaVerificado));
+
+        // revocación
+        assert_eq!(contrato._staff_revocar_verificacion(5, owner, comprador, "Fraude".into()), Err(ErrorVerificacion::NoVerificado));
+        assert_eq!(contrato._staff_revocar_verificacion(5, owner, vendedor, "Fraude".into()), Ok(()));
+        assert_eq!(contrato._ver_verificacion(vendedor), None);
+        assert_eq!(contrato._ver_solicitud_verificacion(owner, vendedor).unwrap().estado, EstadoSolicitudVerificacion::Revocada(5));
+    }
+
+    #[ink::test]
+    fn categoria_exige_verificacion_para_publicar() {
+        let mut contrato = RustaceoLibre::new(0);
+        let owner = contrato.owner;
+        let vendedor = AccountId::from([0x3; 32]); // [0x1; 32] es el owner
+        contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+
+        let subcategoria = contrato._staff_crear_categoria(owner, "Celulares".into(), Some(2)).unwrap();
+        let id_producto = contrato._registrar_producto(0, vendedor, DatosProducto::new("A".into(), "B".into(), subcategoria), 10).unwrap();
+        contrato._staff_aprobar_producto(owner, id_producto).unwrap();
+
+        // la exigencia de la categoría alcanza a sus subcategorías
+        assert_eq!(contrato._staff_exigir_verificacion_categoria(vendedor, 2, Some(NivelVerificacion::Empresa)), Err(ErrorVerificacion::UsuarioNoStaff));
+        assert_eq!(contrato._staff_exigir_verificacion_categoria(owner, 2, Some(NivelVerificacion::Empresa)), Ok(()));
+        assert_eq!(contrato.nivel_verificacion_exigido(&subcategoria), Some(NivelVerificacion::Empresa));
+        assert_eq!(contrato._realizar_publicacion(0, vendedor, id_producto, 1, 100), Err(ErrorRealizarPublicacion::VerificacionRequerida));
+
+        // un nivel inferior no alcanza
+        contrato._solicitar_verificacion(0, vendedor, NivelVerificacion::Identidad, [1; 32]).unwrap();
+        contrato._staff_aprobar_verificacion(0, owner, vendedor).unwrap();
+        assert_eq!(contrato._realizar_publicacion(0, vendedor, id_producto, 1, 100), Err(ErrorRealizarPublicacion::VerificacionRequerida));
+
+        contrato._solicitar_verificacion(0, vendedor, NivelVerificacion::Empresa, [2; 32]).unwrap();
+        contrato._staff_aprobar_verificacion(0, owner, vendedor).unwrap();
+        let id_publicacion = contrato._realizar_publicacion(0, vendedor, id_producto, 1, 100).unwrap();
+        assert_eq!(contrato._ver_detalle_publicacion(id_publicacion).unwrap().verificacion_vendedor, Some(NivelVerificacion::Empresa));
+
+        // sin exigencia cualquiera publica
+        contrato._staff_exigir_verificacion_categoria(owner, 2, None).unwrap();
+        assert_eq!(contrato.nivel_verificacion_exigido(&subcategoria), None);
+    }
+
+    #[ink::test]
+    fn publicaciones_existentes_no_suman_unidades_sin_verificacion() {
+        let mut contrato = RustaceoLibre::new(0);
+        let owner = contrato.owner;
+        let vendedor = AccountId::from([0x3; 32]); // [0x1; 32] es el owner
+        contrato._registrar_usuario(vendedor, RolDeSeleccion::Vendedor).unwrap();
+
+        // publicaciones realizadas antes de que se exija verificación
+        let subcategoria = contrato._staff_crear_categoria(owner, "Celulares".into(), Some(1)).unwrap();
+        let id_producto = contrato._registrar_producto(0, vendedor, DatosProducto::new("A".into(), "B".into(), subcategoria), 10).unwrap();
+        let id_duplicado = contrato._registrar_producto(0, vendedor, DatosProducto::new("C".into(), "D".into(), 1), 10).unwrap();
+        let id_destino = contrato._registrar_producto(0, vendedor, DatosProducto::new("E".into(), "F".into(), 2), 0).unwrap();
+        for id in [id_producto, id_duplicado, id_destino] {
+            contrato._staff_aprobar_producto(owner, id).unwrap();
+        }
+        let id_publicacion = contrato._realizar_publicacion(0, vendedor, id_producto, 5, 100).unwrap();
+        contrato._realizar_publicacion(0, vendedor, id_duplicado, 5, 100).unwrap();
+        contrato._staff_exigir_verificacion_categoria(owner, 2, Some(NivelVerificacion::Identidad)).unwrap();
+
+        // el Staff no puede llevarlas a una categoría que exige verificación
+        assert_eq!(contrato._staff_mover_categoria(owner, subcategoria, Some(2)), Err(ErrorCategoria::VerificacionRequerida));
+        assert_eq!(contrato._staff_fusionar_productos(owner, id_duplicado, id_destino), Err(ErrorModerarProducto::VerificacionRequerida));
+
+        // exigida en su categoría, pueden bajar unidades pero no sumarlas, tampoco por reposición automática
+        contrato._staff_exigir_verificacion_categoria(owner, subcategoria, Some(NivelVerificacion::Identidad)).unwrap();
+        assert_eq!(contrato._modificar_cantidad_ofertada(0, vendedor, id_publicacion, 6), Err(ErrorModificarCantidadOfertada::VerificacionRequerida));
+        assert_eq!(contrato._modificar_cantidad_ofertada(0, vendedor, id_publicacion, 2), Ok(()));
+        contrato._establecer_reposicion_automatica(0, vendedor, id_publicacion, Some(ReposicionAutomatica { minimo: 3, objetivo: 5 })).unwrap();
+        assert!(!contrato.reponer_publicacion(0, id_publicacion));
+
+        // verificado, sí
+        contrato._solicitar_verificacion(0, vendedor, NivelVerificacion::Identidad, [1; 32]).unwrap();
+        contrato._staff_aprobar_verificacion(0, owner, vendedor).unwrap();
+        assert!(contrato.reponer_publicacion(0, id_publicacion));
+        assert_eq!(contrato._staff_mover_categoria(owner, subcategoria, Some(2)), Ok(()));
+        assert_eq!(contrato._staff_fusionar_productos(owner, id_duplicado, id_destino), Ok(()));
+    }
+}